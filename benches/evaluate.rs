@@ -0,0 +1,41 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use xlformula_engine::{calculate, parse_formula, types, NoCustomFunction};
+
+fn data_function(s: String) -> types::Value<f64> {
+    match s.as_str() {
+        "A" => types::Value::Number(2.0),
+        "B" => types::Value::Number(3.0),
+        "Name" => types::Value::Text("Test".to_string()),
+        _ => types::Value::Error(types::Error::Value),
+    }
+}
+
+/// Compares the recursive evaluation of a parsed formula, which has to clone the formula for
+/// every evaluation, with the evaluation of the compiled program.
+fn bench_repeated_evaluation(c: &mut Criterion) {
+    for (name, s) in [
+        ("arithmetic", "=(A+1)*(B-2)/4+A^2-B*3+(A+B)*(A-B)"),
+        (
+            "functions",
+            "=IF(A>1,SUM(A,B,3,4),AVERAGE(A,B))+ABS(-A)*PRODUCT(1,2,B)",
+        ),
+        ("text", r#"=IF(A=2,Name&" - "&A,"")&LEFT(Name,2)"#),
+        ("list", "=SUM({1,2,3,4,5,6,7,8}*A)+B"),
+    ] {
+        let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+        let program = calculate::compile_formula(&formula);
+        let mut group = c.benchmark_group(name);
+        group.bench_function("calculate_formula", |b| {
+            b.iter(|| {
+                calculate::calculate_formula(black_box(formula.clone()), Some(&data_function))
+            })
+        });
+        group.bench_function("program", |b| {
+            b.iter(|| black_box(&program).evaluate(Some(&data_function)))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_repeated_evaluation);
+criterion_main!(benches);
@@ -0,0 +1,368 @@
+use crate::{
+    calculate::calculate_formula,
+    optimize::is_volatile,
+    types::{self, XlNum},
+    NoReference,
+};
+use chrono::{FixedOffset, TimeZone};
+use std::{
+    fmt::{self, Debug},
+    ops::{BitOr, BitOrAssign},
+    str::FromStr,
+};
+
+/// A set of kinds of values, combined with `|`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Kinds(u8);
+
+impl Kinds {
+    pub const NONE: Kinds = Kinds(0);
+    pub const NUMBER: Kinds = Kinds(1);
+    pub const TEXT: Kinds = Kinds(1 << 1);
+    pub const BOOLEAN: Kinds = Kinds(1 << 2);
+    pub const DATE: Kinds = Kinds(1 << 3);
+    pub const ARRAY: Kinds = Kinds(1 << 4);
+    pub const ERROR: Kinds = Kinds(1 << 5);
+    pub const BLANK: Kinds = Kinds(1 << 6);
+    pub const ANY: Kinds = Kinds((1 << 7) - 1);
+
+    const NAMES: [(Kinds, &'static str); 7] = [
+        (Kinds::NUMBER, "NUMBER"),
+        (Kinds::TEXT, "TEXT"),
+        (Kinds::BOOLEAN, "BOOLEAN"),
+        (Kinds::DATE, "DATE"),
+        (Kinds::ARRAY, "ARRAY"),
+        (Kinds::ERROR, "ERROR"),
+        (Kinds::BLANK, "BLANK"),
+    ];
+
+    /// Returns the kind of a value.
+    pub fn of<N>(value: &types::Value<N>) -> Kinds
+    where
+        N: XlNum,
+    {
+        match value {
+            types::Value::Number(_) => Kinds::NUMBER,
+            types::Value::Text(_) => Kinds::TEXT,
+            types::Value::Boolean(_) => Kinds::BOOLEAN,
+            types::Value::Date(_) => Kinds::DATE,
+            types::Value::Iterator(_) => Kinds::ARRAY,
+            types::Value::Error(_) => Kinds::ERROR,
+            types::Value::Blank => Kinds::BLANK,
+        }
+    }
+
+    /// Returns true if all kinds of `other` are in this set.
+    pub fn contains(self, other: Kinds) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Kinds {
+    type Output = Kinds;
+
+    fn bitor(self, rhs: Kinds) -> Kinds {
+        Kinds(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Kinds {
+    fn bitor_assign(&mut self, rhs: Kinds) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl Debug for Kinds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "NONE");
+        }
+        let names: Vec<&str> = Kinds::NAMES
+            .iter()
+            .filter(|(kinds, _)| self.contains(*kinds))
+            .map(|(_, name)| *name)
+            .collect();
+        write!(f, "{}", names.join(" | "))
+    }
+}
+
+/// The possible kinds of the value of a formula, and of the values of its arguments or
+/// list elements in the order of the formula.
+///
+/// The kinds of sub-expressions with references are a heuristic, as they are inferred from
+/// sample values: they can miss kinds that only other values lead to, e.g. `=1/(N-7)` for a
+/// number `N` is inferred as NUMBER, although `N` = 7 gives #DIV/0!.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeTree {
+    pub kinds: Kinds,
+    pub arguments: Vec<TypeTree>,
+}
+
+/// A sub-expression that always evaluates to an error, although none of its arguments does.
+/// Only sub-expressions without references are reported, as the sampled values of a reference
+/// cannot show that every value of it leads to an error.
+#[derive(Debug, Clone)]
+pub struct Diagnostic<N>
+where
+    N: XlNum,
+{
+    pub expression: types::Formula<N>,
+    /// The error, if it is always the same.
+    pub error: Option<types::Error>,
+}
+
+/// The result of `analyze_formula`.
+#[derive(Debug, Clone)]
+pub struct Analysis<N>
+where
+    N: XlNum,
+{
+    pub types: TypeTree,
+    /// Guaranteed errors, innermost sub-expressions first.
+    pub diagnostics: Vec<Diagnostic<N>>,
+}
+
+pub type NoTypeHints<'a> = &'a fn(String) -> Kinds;
+
+/// Sets with more values are replaced by the representatives of their kinds.
+const MAX_VALUES: usize = 24;
+/// Operations with more combinations of argument values may return any kind.
+const MAX_COMBINATIONS: usize = 4096;
+
+fn representatives<N>(kinds: Kinds) -> Vec<types::Value<N>>
+where
+    N: XlNum,
+{
+    let number = |x: f64| types::Value::Number(N::from_f64(x).unwrap());
+    let text = |s: &str| types::Value::Text(s.to_string());
+    let mut values = Vec::new();
+    if kinds.contains(Kinds::NUMBER) {
+        values.extend([number(0.0), number(1.0), number(-2.5), number(3.5)]);
+    }
+    if kinds.contains(Kinds::TEXT) {
+        values.extend([text(""), text("2"), text("abc")]);
+    }
+    if kinds.contains(Kinds::BOOLEAN) {
+        values.extend([
+            types::Value::Boolean(types::Boolean::True),
+            types::Value::Boolean(types::Boolean::False),
+        ]);
+    }
+    if kinds.contains(Kinds::DATE) {
+        let date = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2024, 2, 29, 0, 0, 0)
+            .unwrap();
+        values.push(types::Value::Date(date));
+    }
+    if kinds.contains(Kinds::ARRAY) {
+        values.extend([
+            types::Value::Iterator(vec![number(1.0), number(2.0)]),
+            types::Value::Iterator(vec![
+                types::Value::Boolean(types::Boolean::True),
+                types::Value::Boolean(types::Boolean::False),
+            ]),
+        ]);
+    }
+    if kinds.contains(Kinds::ERROR) {
+        values.push(types::Value::Error(types::Error::Value));
+    }
+    if kinds.contains(Kinds::BLANK) {
+        values.push(types::Value::Blank);
+    }
+    values
+}
+
+/// The possible kinds of the value of a volatile function, which is never evaluated during
+/// the analysis.
+fn volatile_kinds(func: types::Function) -> Kinds {
+    match func {
+        types::Function::Rand => Kinds::NUMBER,
+        types::Function::RandArray => Kinds::ARRAY | Kinds::ERROR,
+        _ => Kinds::NUMBER | Kinds::ERROR,
+    }
+}
+
+fn kinds_of<N>(values: &[types::Value<N>]) -> Kinds
+where
+    N: XlNum,
+{
+    values
+        .iter()
+        .fold(Kinds::NONE, |kinds, value| kinds | Kinds::of(value))
+}
+
+fn is_error<N>(value: &types::Value<N>) -> bool
+where
+    N: XlNum,
+{
+    matches!(value, types::Value::Error(_))
+}
+
+/// Evaluates `build` for every combination of the possible argument values.
+fn combine<N>(
+    arguments: &[Vec<types::Value<N>>],
+    build: impl Fn(Vec<types::Formula<N>>) -> types::Formula<N>,
+) -> Vec<types::Value<N>>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let combinations = arguments
+        .iter()
+        .try_fold(1usize, |count, values| count.checked_mul(values.len()));
+    match combinations {
+        Some(0) => return Vec::new(),
+        Some(count) if count <= MAX_COMBINATIONS => (),
+        _ => return representatives(Kinds::ANY),
+    }
+    let mut results: Vec<types::Value<N>> = Vec::new();
+    let mut indices = vec![0; arguments.len()];
+    loop {
+        let values = indices
+            .iter()
+            .zip(arguments)
+            .map(|(i, values)| types::Formula::Value(values[*i].clone()))
+            .collect();
+        let result = calculate_formula(build(values), None::<NoReference<N>>);
+        if !results.contains(&result) {
+            results.push(result);
+        }
+        // Advances the indices like the digits of a counter.
+        let mut position = indices.len();
+        loop {
+            if position == 0 {
+                return results;
+            }
+            position -= 1;
+            indices[position] += 1;
+            if indices[position] < arguments[position].len() {
+                break;
+            }
+            indices[position] = 0;
+        }
+    }
+}
+
+struct Analyzer<'a, N, F>
+where
+    N: XlNum,
+{
+    hints: Option<&'a F>,
+    diagnostics: Vec<Diagnostic<N>>,
+}
+
+impl<N, F> Analyzer<'_, N, F>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+    F: Fn(String) -> Kinds,
+{
+    /// Returns the possible values of the formula, and whether they are known exactly
+    /// because the formula neither has references nor random numbers.
+    fn analyze(&mut self, formula: &types::Formula<N>) -> (Vec<types::Value<N>>, TypeTree, bool) {
+        let (values, arguments, known) = match formula {
+            types::Formula::Value(value) => (vec![value.clone()], Vec::new(), true),
+            types::Formula::Reference(name) => {
+                let kinds = match self.hints {
+                    Some(hints) => hints(name.clone()),
+                    None => Kinds::ANY,
+                };
+                (representatives(kinds), Vec::new(), false)
+            }
+            types::Formula::Operation(exp) => {
+                let (argument_values, arguments, known) = self.analyze_all(&exp.values);
+                let op = exp.op;
+                let volatile = matches!(op, types::Operator::Function(func) if is_volatile(func));
+                let values = match op {
+                    types::Operator::Function(func) if volatile => {
+                        representatives(volatile_kinds(func))
+                    }
+                    _ => combine(&argument_values, |values| {
+                        types::Formula::Operation(types::Expression { op, values })
+                    }),
+                };
+                let always_error = !values.is_empty() && values.iter().all(is_error);
+                if known
+                    && always_error
+                    && argument_values
+                        .iter()
+                        .all(|values| !values.iter().all(is_error))
+                {
+                    let error = match values[0] {
+                        types::Value::Error(error) if values.len() == 1 => Some(error),
+                        _ => None,
+                    };
+                    self.diagnostics.push(Diagnostic {
+                        expression: formula.clone(),
+                        error,
+                    });
+                }
+                (values, arguments, known && !volatile)
+            }
+            types::Formula::Iterator(vec) => {
+                let (argument_values, arguments, known) = self.analyze_all(vec);
+                (
+                    combine(&argument_values, types::Formula::Iterator),
+                    arguments,
+                    known,
+                )
+            }
+        };
+        let kinds = kinds_of(&values);
+        let values = if values.len() > MAX_VALUES {
+            representatives(kinds)
+        } else {
+            values
+        };
+        (values, TypeTree { kinds, arguments }, known)
+    }
+
+    fn analyze_all(
+        &mut self,
+        formulas: &[types::Formula<N>],
+    ) -> (Vec<Vec<types::Value<N>>>, Vec<TypeTree>, bool) {
+        let mut known = true;
+        let (values, types) = formulas
+            .iter()
+            .map(|formula| {
+                let (values, types, formula_known) = self.analyze(formula);
+                known &= formula_known;
+                (values, types)
+            })
+            .unzip();
+        (values, types, known)
+    }
+}
+
+/// Infers the possible kinds of the value of every sub-expression without resolving references.
+/// The kinds of a reference are taken from the optional hints, and are otherwise unknown.
+///
+/// Sub-expressions without references are evaluated. Otherwise operations are evaluated for
+/// combinations of representative values of the possible kinds of their arguments, e.g. the
+/// texts "", "2" and "abc", so that the inferred kinds follow the conversions of the evaluation.
+/// As these are samples, the inferred kinds can miss some kinds of values, see `TypeTree`, and
+/// guaranteed errors are only reported for sub-expressions without references. Volatile
+/// functions like RAND are never evaluated; they may return any value of their kinds.
+pub fn analyze_formula<N>(
+    formula: &types::Formula<N>,
+    hints: Option<&impl Fn(String) -> Kinds>,
+) -> Analysis<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let mut analyzer = Analyzer {
+        hints,
+        diagnostics: Vec::new(),
+    };
+    let (_, types, _) = analyzer.analyze(formula);
+    Analysis {
+        types,
+        diagnostics: analyzer.diagnostics,
+    }
+}
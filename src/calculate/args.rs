@@ -16,6 +16,45 @@ where
     }
 }
 
+/// Evaluates all arguments of a function with a variable number of arguments.
+pub fn get_function_args<N>(
+    exp: types::Expression<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+) -> Vec<types::Value<N>>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    exp.values
+        .into_iter()
+        .map(|formula| calculate_formula(formula, f))
+        .collect()
+}
+
+/// Evaluates all arguments of an aggregate. The value of a reference is wrapped in a list,
+/// so that text and booleans in a referenced cell are ignored like in a range, while they
+/// are converted when typed directly as an argument.
+pub fn get_list_function_args<N>(
+    exp: types::Expression<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+) -> Vec<types::Value<N>>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    exp.values
+        .into_iter()
+        .map(|formula| match formula {
+            types::Formula::Reference(_) => {
+                types::Value::Iterator(vec![calculate_formula(formula, f)])
+            }
+            formula => calculate_formula(formula, f),
+        })
+        .collect()
+}
+
+/// Evaluates the two arguments of a binary function. More arguments are #ARG! for both
+/// of them.
 pub fn get_binary_function_args<N>(
     mut exp: types::Expression<N>,
     f: Option<&impl Fn(String) -> types::Value<N>>,
@@ -24,6 +63,10 @@ where
     N: XlNum,
     <N as FromStr>::Err: Debug,
 {
+    if exp.values.len() > 2 {
+        let error = types::Value::Error(types::Error::Argument);
+        return (error.clone(), error);
+    }
     let rhs = match exp.values.pop() {
         Some(formula) => calculate_formula(formula, f),
         None => types::Value::Error(types::Error::Argument),
@@ -0,0 +1,123 @@
+use super::{
+    iterator::collect_iterator,
+    operation::{calculate_binary_operator, calculate_operation},
+    reference::calculate_reference,
+};
+use crate::{
+    types::{self, XlNum},
+    NoReference,
+};
+use std::{fmt::Debug, str::FromStr};
+
+/// A step of a compiled formula. Instructions take their arguments from and push their result
+/// onto a stack of values.
+#[derive(Debug, Clone)]
+pub enum Instruction<N>
+where
+    N: XlNum,
+{
+    /// Pushes a constant.
+    Value(types::Value<N>),
+    /// Pushes the value of a reference.
+    Reference(String),
+    /// Replaces the given number of values with a list of them.
+    Iterator(usize),
+    /// Replaces the given number of values with the result of an operator or function.
+    Operation(types::Operator, usize),
+}
+
+/// A formula compiled to a flat sequence of instructions. A program can be evaluated any number
+/// of times without cloning or walking the formula tree.
+#[derive(Debug, Clone)]
+pub struct Program<N>
+where
+    N: XlNum,
+{
+    instructions: Vec<Instruction<N>>,
+    stack_size: usize,
+}
+
+fn compile_instructions<N>(
+    formula: &types::Formula<N>,
+    instructions: &mut Vec<Instruction<N>>,
+    depth: usize,
+    stack_size: &mut usize,
+) where
+    N: XlNum,
+{
+    *stack_size = (*stack_size).max(depth + 1);
+    match formula {
+        types::Formula::Operation(exp) => {
+            for (i, value) in exp.values.iter().enumerate() {
+                compile_instructions(value, instructions, depth + i, stack_size);
+            }
+            instructions.push(Instruction::Operation(exp.op, exp.values.len()));
+        }
+        types::Formula::Value(val) => instructions.push(Instruction::Value(val.clone())),
+        types::Formula::Reference(string) => {
+            instructions.push(Instruction::Reference(string.clone()))
+        }
+        types::Formula::Iterator(vec) => {
+            for (i, value) in vec.iter().enumerate() {
+                compile_instructions(value, instructions, depth + i, stack_size);
+            }
+            instructions.push(Instruction::Iterator(vec.len()));
+        }
+    }
+}
+
+pub fn compile<N>(formula: &types::Formula<N>) -> Program<N>
+where
+    N: XlNum,
+{
+    let mut instructions = Vec::new();
+    let mut stack_size = 0;
+    compile_instructions(formula, &mut instructions, 0, &mut stack_size);
+    Program {
+        instructions,
+        stack_size,
+    }
+}
+
+impl<N> Program<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    pub fn instructions(&self) -> &[Instruction<N>] {
+        &self.instructions
+    }
+
+    /// Evaluates the program with the same result as `calculate_formula` on the compiled formula.
+    pub fn evaluate(&self, f: Option<&impl Fn(String) -> types::Value<N>>) -> types::Value<N> {
+        let mut stack: Vec<types::Value<N>> = Vec::with_capacity(self.stack_size);
+        for instruction in &self.instructions {
+            let value = match instruction {
+                Instruction::Value(val) => val.clone(),
+                Instruction::Reference(string) => calculate_reference(string.clone(), f),
+                Instruction::Iterator(len) => collect_iterator(stack.split_off(stack.len() - len)),
+                Instruction::Operation(op @ types::Operator::Function(_), len)
+                | Instruction::Operation(op, len @ 0..=1)
+                | Instruction::Operation(op, len @ 3..) => {
+                    let values = stack
+                        .drain(stack.len() - len..)
+                        .map(types::Formula::Value)
+                        .collect();
+                    calculate_operation(
+                        types::Expression { op: *op, values },
+                        None::<NoReference<N>>,
+                    )
+                }
+                Instruction::Operation(op, _) => {
+                    let rhs = stack.pop().unwrap();
+                    let lhs = stack.pop().unwrap();
+                    calculate_binary_operator(*op, lhs, rhs)
+                }
+            };
+            stack.push(value);
+        }
+        stack
+            .pop()
+            .unwrap_or(types::Value::Error(types::Error::Argument))
+    }
+}
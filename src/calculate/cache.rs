@@ -0,0 +1,146 @@
+use super::{
+    random::RandomSource,
+    reference::{resolve_reference_value, ReferenceValue},
+    walk::{walk, Visitor},
+};
+use crate::types::{self, XlNum};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    str::FromStr,
+};
+
+/// Remembers the evaluated value of every reference resolved during one evaluation, so
+/// that the closure is called and a referenced formula is parsed only once per name.
+#[derive(Debug, Clone)]
+pub struct ReferenceCache<N>
+where
+    N: XlNum,
+{
+    values: HashMap<String, types::Value<N>>,
+    pending: HashSet<String>,
+}
+
+impl<N> Default for ReferenceCache<N>
+where
+    N: XlNum,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> ReferenceCache<N>
+where
+    N: XlNum,
+{
+    pub fn new() -> Self {
+        ReferenceCache {
+            values: HashMap::new(),
+            pending: HashSet::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&types::Value<N>> {
+        self.values.get(name)
+    }
+
+    /// Stores an already evaluated value, which is used unchanged for the name instead of
+    /// resolving it with the closure.
+    pub fn insert(&mut self, name: String, value: types::Value<N>) -> Option<types::Value<N>> {
+        self.values.insert(name, value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Forgets all cached values, e.g. after the data behind the closure changed.
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.pending.clear();
+    }
+}
+
+struct CachedReferences<'a, N, F>
+where
+    N: XlNum,
+{
+    resolve: F,
+    cache: &'a mut ReferenceCache<N>,
+    random: Option<&'a mut dyn RandomSource>,
+}
+
+impl<N, F> Visitor<N> for CachedReferences<'_, N, F>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+    F: Fn(String) -> ReferenceValue<N>,
+{
+    fn reference(&mut self, name: String) -> types::Value<N> {
+        if let Some(value) = self.cache.values.get(&name) {
+            return value.clone();
+        }
+        // A reference that is still being evaluated refers to itself.
+        if !self.cache.pending.insert(name.clone()) {
+            return types::Value::Error(types::Error::Reference);
+        }
+        let value = match (self.resolve)(name.clone()) {
+            ReferenceValue::Value(value) => value,
+            ReferenceValue::Formula(formula) => walk(formula, self),
+        };
+        self.cache.pending.remove(&name);
+        self.cache.values.insert(name, value.clone());
+        value
+    }
+
+    fn random(&mut self) -> Option<&mut dyn RandomSource> {
+        match &mut self.random {
+            Some(random) => Some(&mut **random),
+            None => None,
+        }
+    }
+}
+
+pub fn calculate_cached_formula<'a, N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    cache: &'a mut ReferenceCache<N>,
+    random: Option<&'a mut dyn RandomSource>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let resolve = |name: String| match f {
+        Some(f) => resolve_reference_value(f(name)),
+        None => ReferenceValue::Value(types::Value::Error(types::Error::Reference)),
+    };
+    calculate_cached_reference_values(formula, resolve, cache, random)
+}
+
+/// Same as `calculate_cached_formula`, but the closure returns references that were already
+/// interpreted, e.g. formulas that were parsed before.
+pub fn calculate_cached_reference_values<'a, N>(
+    formula: types::Formula<N>,
+    resolve: impl Fn(String) -> ReferenceValue<N>,
+    cache: &'a mut ReferenceCache<N>,
+    random: Option<&'a mut dyn RandomSource>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    walk(
+        formula,
+        &mut CachedReferences {
+            resolve,
+            cache,
+            random,
+        },
+    )
+}
@@ -1,14 +1,51 @@
 use super::result_to_string;
-use crate::types::{self, XlNum};
+use crate::{
+    format::{decimal_digits, round_digits},
+    types::{self, XlNum},
+};
 
+/// Formats a number like the General format of Excel: rounded to `N::SIGNIFICANT_DIGITS`
+/// significant digits without trailing zeros, and in scientific notation like `1.5E+20` if the
+/// integer part has more digits or the number is smaller than 1E-9.
 pub fn show_number<N>(number: N) -> String
 where
     N: XlNum,
 {
     if number.is_infinite() {
-        types::Error::Div0.to_string()
+        return types::Error::Div0.to_string();
+    }
+    if number.is_nan() {
+        return types::Error::Num.to_string();
+    }
+    let text = number.to_string();
+    let Some((negative, digits, exponent)) = decimal_digits(&text) else {
+        return text;
+    };
+    let (digits, exponent) = round_digits(digits, exponent, N::SIGNIFICANT_DIGITS);
+    if digits.is_empty() {
+        return "0".to_string();
+    }
+    let digits: String = digits.iter().map(|digit| (b'0' + digit) as char).collect();
+    let sign = if negative { "-" } else { "" };
+    if exponent >= N::SIGNIFICANT_DIGITS as i32 || exponent < -9 {
+        let (first, rest) = digits.split_at(1);
+        let point = if rest.is_empty() { "" } else { "." };
+        let exponent_sign = if exponent < 0 { '-' } else { '+' };
+        format!(
+            "{sign}{first}{point}{rest}E{exponent_sign}{:02}",
+            exponent.abs()
+        )
+    } else if exponent < 0 {
+        let zeros = "0".repeat((-exponent - 1) as usize);
+        format!("{sign}0.{zeros}{digits}")
     } else {
-        number.to_string()
+        let integer_length = exponent as usize + 1;
+        if digits.len() <= integer_length {
+            format!("{sign}{digits:0<integer_length$}")
+        } else {
+            let (integer, fraction) = digits.split_at(integer_length);
+            format!("{sign}{integer}.{fraction}")
+        }
     }
 }
 
@@ -30,3 +67,121 @@ where
 {
     show_number(N::zero())
 }
+
+fn operator_symbol(op: types::Operator) -> Option<&'static str> {
+    match op {
+        types::Operator::Plus => Some("+"),
+        types::Operator::Minus => Some("-"),
+        types::Operator::Multiply => Some("*"),
+        types::Operator::Divide => Some("/"),
+        types::Operator::Power => Some("^"),
+        types::Operator::Concat => Some("&"),
+        types::Operator::Equal => Some("="),
+        types::Operator::NotEqual => Some("<>"),
+        types::Operator::Greater => Some(">"),
+        types::Operator::Less => Some("<"),
+        types::Operator::GreaterOrEqual => Some(">="),
+        types::Operator::LessOrEqual => Some("<="),
+        types::Operator::Function(_) => None,
+    }
+}
+
+/// Precedence of the binary operators as used by the parser.
+fn operator_precedence(op: types::Operator) -> u8 {
+    match op {
+        types::Operator::Concat => 1,
+        types::Operator::Equal | types::Operator::NotEqual => 2,
+        types::Operator::Greater
+        | types::Operator::Less
+        | types::Operator::GreaterOrEqual
+        | types::Operator::LessOrEqual => 3,
+        types::Operator::Plus | types::Operator::Minus => 4,
+        types::Operator::Multiply | types::Operator::Divide => 5,
+        types::Operator::Power => 6,
+        types::Operator::Function(_) => 7,
+    }
+}
+
+fn show_operand<N>(formula: &types::Formula<N>, min_precedence: u8) -> String
+where
+    N: XlNum,
+{
+    match formula {
+        types::Formula::Operation(exp) if operator_precedence(exp.op) < min_precedence => {
+            format!("({})", show_formula(formula))
+        }
+        _ => show_formula(formula),
+    }
+}
+
+fn show_value_literal<N>(value: &types::Value<N>) -> String
+where
+    N: XlNum,
+{
+    match value {
+        types::Value::Text(text) => format!("\"{}\"", text.replace('"', "\"\"")),
+        types::Value::Blank => String::new(),
+        _ => result_to_string(value.clone()),
+    }
+}
+
+/// Converts a formula back to its text, without the leading equal sign.
+pub fn show_formula<N>(formula: &types::Formula<N>) -> String
+where
+    N: XlNum,
+{
+    match formula {
+        types::Formula::Operation(exp) => match (exp.op, exp.values.as_slice()) {
+            (types::Operator::Function(types::Function::Negate), [value]) => match value {
+                types::Formula::Reference(_) | types::Formula::Iterator(_) => {
+                    format!("-{}", show_formula(value))
+                }
+                _ => format!("-({})", show_formula(value)),
+            },
+            (types::Operator::Function(types::Function::ImplicitIntersection), [value]) => {
+                match value {
+                    types::Formula::Reference(_) | types::Formula::Iterator(_) => {
+                        format!("@{}", show_formula(value))
+                    }
+                    _ => format!("@({})", show_formula(value)),
+                }
+            }
+            (types::Operator::Function(func), values) => format!(
+                "{func}({})",
+                values
+                    .iter()
+                    .map(show_formula)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            (op, [lhs, rhs]) => {
+                let precedence = operator_precedence(op);
+                let (lhs_precedence, rhs_precedence) = match op {
+                    types::Operator::Power => (precedence + 1, precedence),
+                    _ => (precedence, precedence + 1),
+                };
+                format!(
+                    "{}{}{}",
+                    show_operand(lhs, lhs_precedence),
+                    operator_symbol(op).unwrap_or_default(),
+                    show_operand(rhs, rhs_precedence)
+                )
+            }
+            (op, values) => format!(
+                "{}({})",
+                operator_symbol(op).unwrap_or_default(),
+                values
+                    .iter()
+                    .map(show_formula)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        },
+        types::Formula::Value(value) => show_value_literal(value),
+        types::Formula::Reference(string) => string.clone(),
+        types::Formula::Iterator(vec) => format!(
+            "{{{}}}",
+            vec.iter().map(show_formula).collect::<Vec<_>>().join(",")
+        ),
+    }
+}
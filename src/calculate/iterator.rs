@@ -32,17 +32,25 @@ where
     }
 }
 
+/// Builds the list value of the evaluated elements of a list, given in formula order.
+pub fn collect_iterator<N>(value_vec: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    types::Value::Iterator(value_vec)
+}
+
 pub fn calculate_iterator<N>(
-    mut vec: Vec<types::Formula<N>>,
+    vec: Vec<types::Formula<N>>,
     f: Option<&impl Fn(String) -> types::Value<N>>,
 ) -> types::Value<N>
 where
     N: XlNum,
     <N as FromStr>::Err: Debug,
 {
-    let mut value_vec = Vec::new();
-    while let Some(top) = vec.pop() {
-        value_vec.push(calculate_formula(top, f));
-    }
-    types::Value::Iterator(value_vec)
+    collect_iterator(
+        vec.into_iter()
+            .map(|formula| calculate_formula(formula, f))
+            .collect(),
+    )
 }
@@ -0,0 +1,153 @@
+use super::{
+    random::RandomSource,
+    reference::{resolve_reference_value, ReferenceValue},
+    walk::{walk, Visitor},
+};
+use crate::{
+    parse_formula::{self, nesting_weight, OPERATORS_PER_LEVEL},
+    types::{self, XlNum},
+    NoCustomFunction,
+};
+use std::{fmt::Debug, str::FromStr, sync::atomic::Ordering};
+
+struct Limiter<'a, F> {
+    f: Option<&'a F>,
+    random: Option<&'a mut dyn RandomSource>,
+    limits: &'a types::Limits,
+    /// The nesting depth in `1 / OPERATORS_PER_LEVEL` levels, see `nesting_weight`.
+    depth: usize,
+    /// The nesting weights of the formulas being evaluated, to undo them in `leave`.
+    weights: Vec<usize>,
+    steps: usize,
+    exceeded: Option<types::Error>,
+}
+
+fn count_elements<N>(value: &types::Value<N>) -> usize
+where
+    N: XlNum,
+{
+    match value {
+        types::Value::Iterator(value_vec) => value_vec.iter().map(count_elements).sum(),
+        _ => 1,
+    }
+}
+
+fn exceeds(value: usize, limit: Option<usize>) -> bool {
+    limit.is_some_and(|limit| value > limit)
+}
+
+impl<F> Limiter<'_, F> {
+    fn check_before(&self) -> Option<types::Error> {
+        if let Some(cancel) = &self.limits.cancel {
+            if cancel.load(Ordering::Relaxed) {
+                return Some(types::Error::Cancelled);
+            }
+        }
+        if exceeds(self.steps, self.limits.max_evaluation_steps)
+            || exceeds(
+                self.depth.div_ceil(OPERATORS_PER_LEVEL),
+                self.limits.max_nesting_depth,
+            )
+        {
+            return Some(types::Error::Limit);
+        }
+        None
+    }
+
+    fn check_after<N>(&self, value: &types::Value<N>) -> Option<types::Error>
+    where
+        N: XlNum,
+    {
+        match value {
+            types::Value::Text(text)
+                if exceeds(text.chars().count(), self.limits.max_text_length) =>
+            {
+                Some(types::Error::Limit)
+            }
+            types::Value::Iterator(_)
+                if exceeds(count_elements(value), self.limits.max_array_elements) =>
+            {
+                Some(types::Error::Limit)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<N, F> Visitor<N> for Limiter<'_, F>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+    F: Fn(String) -> types::Value<N>,
+{
+    fn reference(&mut self, name: String) -> types::Value<N> {
+        match self.f {
+            Some(f) => match f(name) {
+                types::Value::Text(s) => walk(
+                    parse_formula::parse_string_to_formula_with_limits(
+                        &s,
+                        None::<NoCustomFunction<N>>,
+                        self.limits,
+                    ),
+                    self,
+                ),
+                value => match resolve_reference_value(value) {
+                    ReferenceValue::Value(value) => value,
+                    ReferenceValue::Formula(formula) => walk(formula, self),
+                },
+            },
+            None => types::Value::Error(types::Error::Reference),
+        }
+    }
+
+    fn enter(&mut self, formula: &types::Formula<N>) -> Option<types::Value<N>> {
+        self.steps += 1;
+        if self.exceeded.is_none() {
+            self.exceeded = self.check_before();
+        }
+        let weight = nesting_weight(formula);
+        self.depth += weight;
+        self.weights.push(weight);
+        self.exceeded.map(types::Value::Error)
+    }
+
+    fn leave(&mut self, value: types::Value<N>) -> types::Value<N> {
+        self.depth -= self.weights.pop().unwrap_or_default();
+        if self.exceeded.is_none() {
+            self.exceeded = self.check_after(&value);
+        }
+        match self.exceeded {
+            Some(error) => types::Value::Error(error),
+            None => value,
+        }
+    }
+
+    fn random(&mut self) -> Option<&mut dyn RandomSource> {
+        match &mut self.random {
+            Some(random) => Some(&mut **random),
+            None => None,
+        }
+    }
+}
+
+pub fn calculate_limited_formula<'a, N>(
+    formula: types::Formula<N>,
+    f: Option<&'a impl Fn(String) -> types::Value<N>>,
+    limits: &'a types::Limits,
+    random: Option<&'a mut dyn RandomSource>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let mut limiter = Limiter {
+        f,
+        random,
+        limits,
+        depth: 0,
+        weights: Vec::new(),
+        steps: 0,
+        exceeded: None,
+    };
+    walk(formula, &mut limiter)
+}
@@ -1,17 +1,43 @@
 mod args;
+mod bytecode;
+mod cache;
 mod display;
 mod iterator;
+mod limits;
 mod operation;
+mod random;
 mod reference;
+mod resolve;
+mod trace;
+mod walk;
+
+pub(crate) use self::reference::calculate_evaluated_formula;
+pub use self::{
+    bytecode::{Instruction, Program},
+    cache::ReferenceCache,
+    random::{DefaultRandom, RandomSource},
+    resolve::AsyncResolver,
+    trace::TraceStep,
+};
 
 use self::{
-    display::{show_blank, show_iterator, show_number},
+    bytecode::compile,
+    cache::calculate_cached_formula,
+    display::{show_blank, show_formula, show_iterator, show_number},
     iterator::calculate_iterator,
+    limits::calculate_limited_formula,
     operation::calculate_operation,
-    reference::calculate_reference,
+    random::calculate_randomized_formula,
+    reference::{calculate_reference, collect_references},
+    resolve::calculate_resolved_formula,
+    trace::calculate_traced_formula,
 };
 use crate::types::{self, XlNum};
-use std::{fmt::Debug, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    str::FromStr,
+};
 
 /// Evaluates a string that was parsed and stored in Expression Struct.
 /// Takes an optional closure with the trait bound Fn(String) -> types::Value.
@@ -31,6 +57,165 @@ where
     }
 }
 
+/// Compiles a formula into a program for a stack machine. Unlike `calculate_formula` the program
+/// does not need a clone of the formula for every evaluation, which mostly pays off for
+/// numeric formulas that are evaluated repeatedly, see `cargo bench`.
+pub fn compile_formula<N>(formula: &types::Formula<N>) -> Program<N>
+where
+    N: XlNum,
+{
+    compile(formula)
+}
+
+/// Evaluates a formula like `calculate_formula`, but resolves and parses every reference only
+/// once. Reuse the cache to share resolved references between several evaluations.
+pub fn calculate_formula_with_cache<N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    cache: &mut ReferenceCache<N>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_cached_formula(formula, f, cache, None)
+}
+
+/// Same as `calculate_formula_with_cache`, but draws random numbers from the random source,
+/// see `calculate_formula_with_random`.
+pub fn calculate_formula_with_cache_and_random<N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    cache: &mut ReferenceCache<N>,
+    random: &mut impl RandomSource,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_cached_formula(formula, f, cache, Some(random))
+}
+
+/// Evaluates a formula like `calculate_formula`, but takes the values of the named references
+/// as already evaluated: they are used unchanged instead of being interpreted like values of
+/// the closure, so errors stay errors and text is not parsed as a formula.
+pub fn calculate_formula_with_values<N>(
+    formula: types::Formula<N>,
+    values: &BTreeMap<String, types::Value<N>>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_evaluated_formula(formula, values, f, None)
+}
+
+/// Evaluates a formula like `calculate_formula`, but stops with #LIMIT! as soon as a limit is
+/// exceeded, or with #CANCEL! once the cancellation flag is set. Referenced formulas are parsed
+/// with `parse_string_to_formula_with_limits`.
+pub fn calculate_formula_with_limits<N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    limits: &types::Limits,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_limited_formula(formula, f, limits, None)
+}
+
+/// Same as `calculate_formula_with_limits`, but draws random numbers from the random source,
+/// see `calculate_formula_with_random`.
+pub fn calculate_formula_with_limits_and_random<N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    limits: &types::Limits,
+    random: &mut impl RandomSource,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_limited_formula(formula, f, limits, Some(random))
+}
+
+/// Evaluates a formula like `calculate_formula`, but draws the random numbers of RAND,
+/// RANDBETWEEN and RANDARRAY from the random source, e.g. a `DefaultRandom` with a fixed seed
+/// for reproducible results. Without a random source they use a generator per thread.
+pub fn calculate_formula_with_random<N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    random: &mut impl RandomSource,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_randomized_formula(formula, f, random)
+}
+
+/// Evaluates a formula like `calculate_formula` and records every sub-expression with its
+/// value in evaluation order: arguments from left to right, each before the operation using it.
+/// The last step is the formula itself.
+pub fn calculate_formula_with_trace<N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+) -> (types::Value<N>, Vec<TraceStep<N>>)
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_traced_formula(formula, f, None)
+}
+
+/// Same as `calculate_formula_with_trace`, but draws random numbers from the random source,
+/// see `calculate_formula_with_random`.
+pub fn calculate_formula_with_trace_and_random<N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    random: &mut impl RandomSource,
+) -> (types::Value<N>, Vec<TraceStep<N>>)
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_traced_formula(formula, f, Some(random))
+}
+
+/// Evaluates a formula like `calculate_formula`, but resolves the references through an
+/// asynchronous resolver before the evaluation. All references of the formula are requested
+/// in one batch, followed by one batch for the references of the referenced formulas, and so on.
+pub async fn calculate_formula_async<N>(
+    formula: types::Formula<N>,
+    resolver: &impl AsyncResolver<N>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    calculate_resolved_formula(formula, resolver).await
+}
+
+/// Returns the names of all references in the formula.
+pub fn formula_references<N>(formula: &types::Formula<N>) -> BTreeSet<String>
+where
+    N: XlNum,
+{
+    let mut references = BTreeSet::new();
+    collect_references(formula, &mut references);
+    references
+}
+
+/// Converts a formula back to a formula string, without the leading equal sign.
+pub fn formula_to_string<N>(formula: &types::Formula<N>) -> String
+where
+    N: XlNum,
+{
+    show_formula(formula)
+}
+
 /// Converts a result from Value Enum to a printable string.
 pub fn result_to_string<N>(value: types::Value<N>) -> String
 where
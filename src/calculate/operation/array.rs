@@ -0,0 +1,167 @@
+use crate::types::{self, XlNum};
+
+/// The elements of an array by row. A list is a single row, a list of lists holds one
+/// list per row. Any other value is a single element.
+struct Grid<N>
+where
+    N: XlNum,
+{
+    rows: Vec<Vec<types::Value<N>>>,
+    width: usize,
+}
+
+impl<N> Grid<N>
+where
+    N: XlNum,
+{
+    fn new(value: types::Value<N>) -> Self {
+        let rows = match value {
+            types::Value::Iterator(value_vec) if is_nested(&value_vec) => value_vec
+                .into_iter()
+                .map(|row| match row {
+                    types::Value::Iterator(row) => row,
+                    _ => unreachable!(),
+                })
+                .collect(),
+            types::Value::Iterator(value_vec) => vec![value_vec],
+            _ => vec![vec![value]],
+        };
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        Grid { rows, width }
+    }
+
+    /// Returns the element at the given position, repeating a single row or column.
+    fn get(&self, row: usize, column: usize) -> Option<&types::Value<N>> {
+        let row = if self.rows.len() == 1 { 0 } else { row };
+        let column = if self.width == 1 { 0 } else { column };
+        self.rows.get(row)?.get(column)
+    }
+}
+
+fn is_nested<N>(value_vec: &[types::Value<N>]) -> bool
+where
+    N: XlNum,
+{
+    !value_vec.is_empty()
+        && value_vec
+            .iter()
+            .all(|value| matches!(value, types::Value::Iterator(_)))
+}
+
+fn is_array<N>(value: &types::Value<N>) -> bool
+where
+    N: XlNum,
+{
+    matches!(value, types::Value::Iterator(_))
+}
+
+/// Size of the result of two arrays of the given sizes along one dimension.
+fn broadcast_size(lhs: usize, rhs: usize) -> usize {
+    match (lhs, rhs) {
+        (1, size) | (size, 1) => size,
+        (lhs, rhs) => lhs.max(rhs),
+    }
+}
+
+/// Applies an operator element by element if one of the operands is an array. A single
+/// value, row or column is repeated to the size of the other operand. Positions outside
+/// of an operand result in #N/A. Returns the operands if neither is an array.
+pub fn broadcast<N>(
+    lhs: types::Value<N>,
+    rhs: types::Value<N>,
+    f: impl Fn(types::Value<N>, types::Value<N>) -> types::Value<N>,
+) -> Result<types::Value<N>, (types::Value<N>, types::Value<N>)>
+where
+    N: XlNum,
+{
+    let pair = |values: Vec<types::Value<N>>| {
+        let [lhs, rhs]: [types::Value<N>; 2] = values.try_into().unwrap();
+        (lhs, rhs)
+    };
+    broadcast_all(vec![lhs, rhs], |values| {
+        let (lhs, rhs) = pair(values);
+        f(lhs, rhs)
+    })
+    .map_err(pair)
+}
+
+/// Applies a function element by element like `broadcast`, but to any number of arguments.
+/// Returns the arguments if none of them is an array.
+pub fn broadcast_all<N>(
+    values: Vec<types::Value<N>>,
+    f: impl Fn(Vec<types::Value<N>>) -> types::Value<N>,
+) -> Result<types::Value<N>, Vec<types::Value<N>>>
+where
+    N: XlNum,
+{
+    if !values.iter().any(is_array) {
+        return Err(values);
+    }
+    let nested = values.iter().any(|value| match value {
+        types::Value::Iterator(value_vec) => is_nested(value_vec),
+        _ => false,
+    });
+    let grids: Vec<Grid<N>> = values.into_iter().map(Grid::new).collect();
+    let height = grids
+        .iter()
+        .map(|grid| grid.rows.len())
+        .fold(1, broadcast_size);
+    let width = grids.iter().map(|grid| grid.width).fold(1, broadcast_size);
+    let mut rows: Vec<types::Value<N>> = (0..height)
+        .map(|row| {
+            types::Value::Iterator(
+                (0..width)
+                    .map(|column| {
+                        let values: Option<Vec<types::Value<N>>> = grids
+                            .iter()
+                            .map(|grid| grid.get(row, column).cloned())
+                            .collect();
+                        match values {
+                            Some(values) => f(values),
+                            None => types::Value::Error(types::Error::NotAvailable),
+                        }
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    if nested {
+        Ok(types::Value::Iterator(rows))
+    } else {
+        Ok(rows.pop().unwrap())
+    }
+}
+
+/// Returns the rows of an array. A list is a single row, and any other value a single element.
+pub fn array_rows<N>(value: types::Value<N>) -> Vec<Vec<types::Value<N>>>
+where
+    N: XlNum,
+{
+    Grid::new(value).rows
+}
+
+/// Builds an array from its rows, a single row as a list like in array literals.
+pub fn array_from_rows<N>(mut rows: Vec<Vec<types::Value<N>>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if rows.len() == 1 {
+        types::Value::Iterator(rows.pop().unwrap())
+    } else {
+        types::Value::Iterator(rows.into_iter().map(types::Value::Iterator).collect())
+    }
+}
+
+/// Returns the top left element of an array, or the value itself if it is not an array.
+pub fn first_element<N>(value: types::Value<N>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    match value {
+        types::Value::Iterator(value_vec) => match value_vec.into_iter().next() {
+            Some(value) => first_element(value),
+            None => types::Value::Error(types::Error::Value),
+        },
+        _ => value,
+    }
+}
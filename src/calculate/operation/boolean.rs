@@ -180,12 +180,8 @@ where
 {
     match value {
         types::Value::Number(n) => types::Value::Number(-n),
-        types::Value::Iterator(mut value_vec) => {
-            let mut result_vec = Vec::new();
-            while let Some(top) = value_vec.pop() {
-                result_vec.push(calculate_negate(top));
-            }
-            types::Value::Iterator(result_vec)
+        types::Value::Iterator(value_vec) => {
+            types::Value::Iterator(value_vec.into_iter().map(calculate_negate).collect())
         }
         types::Value::Blank => types::Value::Blank,
         _ => types::Value::Error(types::Error::Value),
@@ -266,13 +262,8 @@ where
                 types::Value::Boolean(types::Boolean::False)
             }
         }
-        types::Value::Iterator(mut value_vec) => {
-            let mut boolean_vec = Vec::new();
-            while let Some(top) = value_vec.pop() {
-                let value = cast_value_to_boolean(top);
-                boolean_vec.push(value);
-            }
-            types::Value::Iterator(boolean_vec)
+        types::Value::Iterator(value_vec) => {
+            types::Value::Iterator(value_vec.into_iter().map(cast_value_to_boolean).collect())
         }
         types::Value::Date(_) => types::Value::Error(types::Error::Cast),
     }
@@ -0,0 +1,237 @@
+use super::statistics::date_number;
+use crate::types::{self, XlNum};
+use std::{fmt::Debug, str::FromStr};
+
+/// Exponents beyond this size overflow every number type.
+const MAX_EXPONENT: i32 = 400;
+
+/// Builds a number from the digits before and after the decimal point, multiplied with a
+/// power of 10. The digits are shifted as text, so that decimals stay exact.
+pub fn number_from_digits<N>(
+    negative: bool,
+    integer: &str,
+    fraction: &str,
+    exponent: i32,
+) -> Option<N>
+where
+    N: XlNum,
+{
+    if integer.is_empty() && fraction.is_empty() || exponent.abs() > MAX_EXPONENT {
+        return None;
+    }
+    let digits = format!("{integer}{fraction}");
+    let point = integer.len() as i32 + exponent;
+    let plain = if point <= 0 {
+        format!("0.{}{digits}", "0".repeat(-point as usize))
+    } else if point as usize >= digits.len() {
+        format!("{digits}{}", "0".repeat(point as usize - digits.len()))
+    } else {
+        let (integer, fraction) = digits.split_at(point as usize);
+        format!("{integer}.{fraction}")
+    };
+    let number = plain.parse::<N>().ok()?;
+    if number.is_infinite() {
+        return None;
+    }
+    Some(if negative { -number } else { number })
+}
+
+/// Splits off an exponent like `e-3`, returning the mantissa and the exponent.
+fn split_exponent(text: &str) -> Option<(&str, i32)> {
+    match text.find(['e', 'E']) {
+        Some(position) => {
+            let exponent = &text[position + 1..];
+            let digits = exponent.trim_start_matches(['+', '-']);
+            if digits.is_empty()
+                || exponent.len() - digits.len() > 1
+                || !digits.bytes().all(|c| c.is_ascii_digit())
+            {
+                return None;
+            }
+            let exponent = exponent.parse::<i64>().ok()?;
+            let exponent = exponent.clamp(-MAX_EXPONENT as i64 - 1, MAX_EXPONENT as i64 + 1);
+            Some((&text[..position], exponent as i32))
+        }
+        None => Some((text, 0)),
+    }
+}
+
+fn is_digits(text: &str) -> bool {
+    text.bytes().all(|c| c.is_ascii_digit())
+}
+
+/// Converts text to a number like Excel does for arithmetic on text and for `VALUE`:
+/// surrounding spaces, a sign or parentheses for negative numbers, a leading `$`,
+/// thousands separators in groups of three, a trailing `%` and an exponent are allowed,
+/// e.g. `" (1,234.5) "`, `"$3.10"`, `"50%"` or `"1e3"`.
+pub fn text_to_number<N>(text: &str) -> Option<N>
+where
+    N: XlNum,
+{
+    let mut text = text.trim();
+    let mut negative = false;
+    if let Some(inner) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        negative = true;
+        text = inner.trim();
+    }
+    let (text, percent) = match text.strip_suffix('%') {
+        Some(text) => (text.trim_end(), true),
+        None => (text, false),
+    };
+    // The currency symbol may come before or after the sign.
+    let text = text.strip_prefix('$').unwrap_or(text).trim_start();
+    let text = match text.strip_prefix(['-', '+']) {
+        Some(rest) if !negative => {
+            negative = text.starts_with('-');
+            rest.trim_start()
+        }
+        Some(_) => return None,
+        None => text,
+    };
+    let text = text.strip_prefix('$').unwrap_or(text);
+    let (mantissa, exponent) = split_exponent(text)?;
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let mut groups = integer.split(',');
+    let first = groups.next().unwrap_or_default();
+    let groups: Vec<&str> = groups.collect();
+    if !is_digits(first)
+        || !is_digits(fraction)
+        || groups
+            .iter()
+            .any(|group| group.len() != 3 || !is_digits(group))
+        || !groups.is_empty() && first.is_empty()
+    {
+        return None;
+    }
+    let integer = integer.replace(',', "");
+    let exponent = exponent - if percent { 2 } else { 0 };
+    number_from_digits(negative, &integer, fraction, exponent)
+}
+
+/// Converts text to a number with the given decimal and group separators like `NUMBERVALUE`.
+/// Spaces are ignored anywhere, group separators anywhere before the decimal separator, and
+/// every trailing `%` divides by 100. Empty text is 0.
+pub fn text_to_number_with_separators<N>(
+    text: &str,
+    decimal_separator: char,
+    group_separator: char,
+) -> Option<N>
+where
+    N: XlNum,
+{
+    if decimal_separator == group_separator {
+        return None;
+    }
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if text.is_empty() {
+        return Some(N::zero());
+    }
+    let without_percent = text.trim_end_matches('%');
+    let percent = (text.len() - without_percent.len()) as i32;
+    let (negative, text) = match without_percent.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (
+            false,
+            without_percent.strip_prefix('+').unwrap_or(without_percent),
+        ),
+    };
+    let (mantissa, exponent) = split_exponent(text)?;
+    let (integer, fraction) = match mantissa.split_once(decimal_separator) {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (mantissa, ""),
+    };
+    let integer: String = integer.chars().filter(|c| *c != group_separator).collect();
+    if !is_digits(&integer) || !is_digits(fraction) {
+        return None;
+    }
+    number_from_digits(negative, &integer, fraction, exponent - 2 * percent)
+}
+
+/// VALUE: converts text to a number, element by element for arrays. Dates are converted to
+/// their serial numbers.
+pub fn calculate_value<N>(value: types::Value<N>) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    match value {
+        types::Value::Number(_) | types::Value::Error(_) => value,
+        types::Value::Text(text) => match text_to_number(&text) {
+            Some(number) => types::Value::Number(number),
+            None => types::Value::Error(types::Error::Value),
+        },
+        types::Value::Blank => types::Value::Number(N::zero()),
+        types::Value::Iterator(value_vec) => {
+            types::Value::Iterator(value_vec.into_iter().map(calculate_value).collect())
+        }
+        types::Value::Date(date) => match date_number(date) {
+            Ok(number) => types::Value::Number(number),
+            Err(error) => types::Value::Error(error),
+        },
+        types::Value::Boolean(_) => types::Value::Error(types::Error::Value),
+    }
+}
+
+/// Returns the first character of a separator argument, or the default if it is missing.
+fn separator<N>(value: Option<types::Value<N>>, default: char) -> Result<char, types::Value<N>>
+where
+    N: XlNum,
+{
+    match value {
+        None | Some(types::Value::Blank) => Ok(default),
+        Some(types::Value::Text(text)) => text
+            .chars()
+            .next()
+            .ok_or(types::Value::Error(types::Error::Value)),
+        Some(types::Value::Error(error)) => Err(types::Value::Error(error)),
+        Some(_) => Err(types::Value::Error(types::Error::Value)),
+    }
+}
+
+/// NUMBERVALUE: converts text to a number with the given decimal and group separators,
+/// element by element for arrays. The decimal separator is `.` by default, and the group
+/// separator `,`, or `.` for the decimal separator `,`.
+pub fn calculate_numbervalue<N>(mut args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    if args.is_empty() || args.len() > 3 {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let group_separator = if args.len() == 3 { args.pop() } else { None };
+    let decimal_separator = if args.len() == 2 { args.pop() } else { None };
+    let separators = separator(decimal_separator, '.').and_then(|decimal| {
+        let default_group = if decimal == ',' { '.' } else { ',' };
+        Ok((decimal, separator(group_separator, default_group)?))
+    });
+    let (decimal_separator, group_separator) = match separators {
+        Ok(separators) => separators,
+        Err(error) => return error,
+    };
+    numbervalue(args.remove(0), decimal_separator, group_separator)
+}
+
+fn numbervalue<N>(value: types::Value<N>, decimal: char, group: char) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    match value {
+        types::Value::Number(_) | types::Value::Error(_) => value,
+        types::Value::Text(text) => match text_to_number_with_separators(&text, decimal, group) {
+            Some(number) => types::Value::Number(number),
+            None => types::Value::Error(types::Error::Value),
+        },
+        types::Value::Blank => types::Value::Number(N::zero()),
+        types::Value::Iterator(value_vec) => types::Value::Iterator(
+            value_vec
+                .into_iter()
+                .map(|value| numbervalue(value, decimal, group))
+                .collect(),
+        ),
+        types::Value::Boolean(_) | types::Value::Date(_) => {
+            types::Value::Error(types::Error::Value)
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use super::round::{round_to_places, Rounding};
+use crate::types::{self, XlNum};
+
+/// GCD and LCM take numbers less than 2^53 and LCM returns them.
+const MAX_INTEGER: u64 = 1 << 53;
+
+/// Multiplies two numbers. Results that do not fit into the number type are #NUM!.
+fn multiply<N>(lhs: N, rhs: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    lhs.checked_mul(rhs)
+        .filter(|product| !product.is_infinite())
+        .ok_or(types::Error::Num)
+}
+
+/// Truncates a number to a whole number. Negative numbers are #NUM!.
+fn whole_number<N>(number: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let number = number.trunc();
+    if number.is_negative() {
+        return Err(types::Error::Num);
+    }
+    Ok(number)
+}
+
+/// The product of a number of factors, starting at the first one and increasing by the step.
+/// The count stays small, since the product overflows quickly for factors from 2.
+fn product_of_range<N>(first: N, count: N, step: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let mut product = N::one();
+    let mut factor = first;
+    let mut i = N::zero();
+    while i < count {
+        product = multiply(product, factor)?;
+        factor = factor + step;
+        i = i + N::one();
+    }
+    Ok(product)
+}
+
+/// The number of combinations of whole numbers, by multiplying with the numbers of the
+/// smaller side one by one, so that every intermediate result is a whole number.
+fn combinations<N>(number: N, chosen: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let smaller = if chosen + chosen > number {
+        number - chosen
+    } else {
+        chosen
+    };
+    let mut result = N::one();
+    let mut i = N::one();
+    while i <= smaller {
+        let factor = number - smaller + i;
+        result = multiply(result, factor)?
+            .checked_div(i)
+            .ok_or(types::Error::Num)?;
+        i = i + N::one();
+    }
+    // Floats may be slightly off after the divisions.
+    round_to_places(result, 0, Rounding::HalfAwayFromZero)
+}
+
+/// FACT: the factorial of a number, truncated to a whole number. Negative numbers are #NUM!.
+pub fn fact<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    let number = whole_number(number).ok()?;
+    product_of_range(N::one(), number, N::one()).ok()
+}
+
+/// FACTDOUBLE: the double factorial of a number, the product of every other number down
+/// from it. Negative numbers are #NUM!.
+pub fn factdouble<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    let number = whole_number(number).ok()?;
+    let two = N::one() + N::one();
+    if number.is_zero() {
+        return Some(N::one());
+    }
+    let first = if (number % two).is_zero() {
+        two
+    } else {
+        N::one()
+    };
+    let count = ((number - first) / two).trunc() + N::one();
+    product_of_range(first, count, two).ok()
+}
+
+/// COMBIN: the number of ways to choose a number of items from a set, ignoring the order.
+/// Negative numbers and choosing more items than there are are #NUM!.
+pub fn combin<N>(number: N, chosen: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let (number, chosen) = (whole_number(number)?, whole_number(chosen)?);
+    if chosen > number {
+        return Err(types::Error::Num);
+    }
+    combinations(number, chosen)
+}
+
+/// COMBINA: the number of ways to choose a number of items from a set, ignoring the order
+/// and with repetitions. Negative numbers are #NUM!.
+pub fn combina<N>(number: N, chosen: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let (number, chosen) = (whole_number(number)?, whole_number(chosen)?);
+    if number.is_zero() {
+        return Ok(if chosen.is_zero() {
+            N::one()
+        } else {
+            N::zero()
+        });
+    }
+    combinations(number + chosen - N::one(), chosen)
+}
+
+/// PERMUT: the number of ways to choose a number of items from a set in order. A number of
+/// zero or less, a negative number of chosen items and choosing more items than there are
+/// are #NUM!.
+pub fn permut<N>(number: N, chosen: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let (number, chosen) = (whole_number(number)?, whole_number(chosen)?);
+    if number.is_zero() || chosen > number {
+        return Err(types::Error::Num);
+    }
+    product_of_range(number - chosen + N::one(), chosen, N::one())
+}
+
+/// Truncates numbers to whole numbers. Negative numbers and numbers from 2^53 are #NUM!.
+fn integers<N>(numbers: Vec<N>) -> Result<Vec<u64>, types::Error>
+where
+    N: XlNum,
+{
+    numbers
+        .into_iter()
+        .map(|number| {
+            whole_number(number)?
+                .to_u64()
+                .filter(|integer| *integer < MAX_INTEGER)
+                .ok_or(types::Error::Num)
+        })
+        .collect()
+}
+
+fn greatest_common_divisor(mut lhs: u64, mut rhs: u64) -> u64 {
+    while rhs != 0 {
+        (lhs, rhs) = (rhs, lhs % rhs);
+    }
+    lhs
+}
+
+/// GCD: the greatest common divisor of whole numbers, 0 if all are 0.
+pub fn gcd<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let gcd = integers(numbers)?
+        .into_iter()
+        .fold(0, greatest_common_divisor);
+    N::from_u64(gcd).ok_or(types::Error::Num)
+}
+
+/// LCM: the least common multiple of whole numbers, 0 if one of them is 0. Multiples from
+/// 2^53 are #NUM!.
+pub fn lcm<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let lcm = integers(numbers)?
+        .into_iter()
+        .try_fold(1u64, |lcm, integer| {
+            if lcm == 0 || integer == 0 {
+                return Some(0);
+            }
+            (lcm / greatest_common_divisor(lcm, integer))
+                .checked_mul(integer)
+                .filter(|lcm| *lcm < MAX_INTEGER)
+        })
+        .ok_or(types::Error::Num)?;
+    N::from_u64(lcm).ok_or(types::Error::Num)
+}
+
+/// MULTINOMIAL: the factorial of the sum of whole numbers divided by the product of their
+/// factorials, calculated as a product of combinations to avoid overflowing factorials.
+pub fn multinomial<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let mut sum = N::zero();
+    let mut result = N::one();
+    for number in numbers {
+        let number = whole_number(number)?;
+        sum = sum.checked_add(number).ok_or(types::Error::Num)?;
+        result = multiply(result, combinations(sum, number)?)?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,345 @@
+use super::{
+    array::broadcast_all,
+    coerce::text_to_number,
+    number::number_result,
+    statistics::{calculate_aggregate, date_number},
+    string::matches_with_wildcards,
+};
+use crate::types::{self, XlNum};
+use std::cmp::Ordering;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Operators a criterion can start with. Longer operators come first.
+    const PREFIXES: [(&'static str, Comparison); 6] = [
+        (">=", Comparison::GreaterOrEqual),
+        ("<=", Comparison::LessOrEqual),
+        ("<>", Comparison::NotEqual),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+        ("=", Comparison::Equal),
+    ];
+
+    /// Whether a value compared with the operand of a criterion meets the comparison. Values
+    /// that cannot be compared with the operand, e.g. text with a number, only meet `<>`.
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match (self, ordering) {
+            (Comparison::Equal, Some(ordering)) => ordering == Ordering::Equal,
+            (Comparison::NotEqual, Some(ordering)) => ordering != Ordering::Equal,
+            (Comparison::Less, Some(ordering)) => ordering == Ordering::Less,
+            (Comparison::LessOrEqual, Some(ordering)) => ordering != Ordering::Greater,
+            (Comparison::Greater, Some(ordering)) => ordering == Ordering::Greater,
+            (Comparison::GreaterOrEqual, Some(ordering)) => ordering != Ordering::Less,
+            (comparison, None) => comparison == Comparison::NotEqual,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand<N>
+where
+    N: XlNum,
+{
+    Number(N),
+    Boolean(bool),
+    /// Text with the wildcards `?` and `*`, escaped with `~`.
+    Text(String),
+    /// Matches blank cells and empty text.
+    Blank,
+}
+
+/// A condition of the conditional aggregates like `SUMIF` and `COUNTIFS`, e.g. `">=10"`,
+/// `"<>x"`, `"=*abc?"` or a plain value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Criterion<N>
+where
+    N: XlNum,
+{
+    comparison: Comparison,
+    operand: Operand<N>,
+}
+
+impl<N> Criterion<N>
+where
+    N: XlNum,
+{
+    /// Creates the criterion of a value. Text may start with a comparison operator, and the
+    /// rest is compared as a number, a boolean or as text with wildcards, ignoring case. An
+    /// empty rest matches blank cells. Other values are compared for equality, a blank value
+    /// like 0. Errors are returned.
+    pub fn new(value: &types::Value<N>) -> Result<Criterion<N>, types::Error> {
+        let operand = match value {
+            types::Value::Text(text) => return Ok(Criterion::parse(text)),
+            types::Value::Number(number) => Operand::Number(*number),
+            types::Value::Boolean(boolean) => Operand::Boolean((*boolean).into()),
+            types::Value::Date(date) => Operand::Number(date_number(*date)?),
+            types::Value::Blank => Operand::Number(N::zero()),
+            types::Value::Error(error) => return Err(*error),
+            types::Value::Iterator(_) => return Err(types::Error::Value),
+        };
+        Ok(Criterion {
+            comparison: Comparison::Equal,
+            operand,
+        })
+    }
+
+    fn parse(text: &str) -> Criterion<N> {
+        let (comparison, rest) = Comparison::PREFIXES
+            .iter()
+            .find_map(|(prefix, comparison)| Some((*comparison, text.strip_prefix(prefix)?)))
+            .unwrap_or((Comparison::Equal, text));
+        let operand = if rest.is_empty() {
+            Operand::Blank
+        } else if let Some(number) = text_to_number(rest) {
+            Operand::Number(number)
+        } else if rest.eq_ignore_ascii_case("TRUE") {
+            Operand::Boolean(true)
+        } else if rest.eq_ignore_ascii_case("FALSE") {
+            Operand::Boolean(false)
+        } else {
+            Operand::Text(rest.to_string())
+        };
+        Criterion {
+            comparison,
+            operand,
+        }
+    }
+
+    /// Whether a value meets the criterion. Numbers and dates are only compared with numbers,
+    /// booleans with booleans and text with text, so that `">5"` never matches text. Only
+    /// the criteria `=` and `<>` of a number also compare text that converts to a number, so
+    /// that `"5"` matches both 5 and "5".
+    pub fn matches(&self, value: &types::Value<N>) -> bool {
+        let ordering = match (&self.operand, value) {
+            (Operand::Number(operand), types::Value::Number(number)) => number.partial_cmp(operand),
+            (Operand::Number(operand), types::Value::Text(text))
+                if matches!(self.comparison, Comparison::Equal | Comparison::NotEqual) =>
+            {
+                text_to_number::<N>(text).and_then(|number| number.partial_cmp(operand))
+            }
+            (Operand::Number(operand), types::Value::Date(date)) => date_number::<N>(*date)
+                .ok()
+                .and_then(|number| number.partial_cmp(operand)),
+            (Operand::Boolean(operand), types::Value::Boolean(boolean)) => {
+                Some(bool::from(*boolean).cmp(operand))
+            }
+            (Operand::Text(pattern), types::Value::Text(text)) => match self.comparison {
+                Comparison::Equal | Comparison::NotEqual => {
+                    matches_with_wildcards(pattern, text).then_some(Ordering::Equal)
+                }
+                _ => Some(text.to_lowercase().cmp(&pattern.to_lowercase())),
+            },
+            (Operand::Text(pattern), types::Value::Error(error)) => pattern
+                .eq_ignore_ascii_case(&error.to_string())
+                .then_some(Ordering::Equal),
+            (Operand::Blank, types::Value::Blank) => Some(Ordering::Equal),
+            (Operand::Blank, types::Value::Text(text)) if text.is_empty() => Some(Ordering::Equal),
+            _ => None,
+        };
+        self.comparison.holds(ordering)
+    }
+}
+
+/// The cells of a range or array row by row. A single value is a range of one cell.
+fn cells<N>(value: types::Value<N>) -> Vec<types::Value<N>>
+where
+    N: XlNum,
+{
+    match value {
+        types::Value::Iterator(value_vec) => value_vec.into_iter().flat_map(cells).collect(),
+        value => vec![value],
+    }
+}
+
+/// Applies a function to the criterion of a value, for every criterion of an array.
+fn with_criteria<N>(
+    criteria: types::Value<N>,
+    f: &impl Fn(&Criterion<N>) -> types::Value<N>,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    match criteria {
+        types::Value::Iterator(value_vec) => types::Value::Iterator(
+            value_vec
+                .into_iter()
+                .map(|criteria| with_criteria(criteria, f))
+                .collect(),
+        ),
+        value => match Criterion::new(&value) {
+            Ok(criterion) => f(&criterion),
+            Err(error) => types::Value::Error(error),
+        },
+    }
+}
+
+/// COUNTIF: counts the cells of a range that meet a criterion, for every criterion of an
+/// array.
+pub fn calculate_countif<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let [range, criteria] = match <[types::Value<N>; 2]>::try_from(args) {
+        Ok(args) => args,
+        Err(_) => return types::Value::Error(types::Error::Argument),
+    };
+    let range = cells(range);
+    with_criteria(criteria, &|criterion| {
+        number_result(N::from_usize(
+            range.iter().filter(|cell| criterion.matches(cell)).count(),
+        ))
+    })
+}
+
+/// SUMIF and AVERAGEIF: aggregates the numbers of the cells of the value range, by default
+/// the range, whose cells in the range meet a criterion. For every criterion of an array.
+pub fn calculate_aggregate_if<N>(
+    args: Vec<types::Value<N>>,
+    f: fn(Vec<N>) -> Result<N, types::Error>,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.len() < 2 || args.len() > 3 {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let mut args = args.into_iter();
+    let range = cells(args.next().unwrap_or(types::Value::Blank));
+    let criteria = args.next().unwrap_or(types::Value::Blank);
+    let values = match args.next() {
+        Some(values) => cells(values),
+        None => range.clone(),
+    };
+    with_criteria(criteria, &|criterion| {
+        let matches = range
+            .iter()
+            .zip(&values)
+            .filter(|(cell, _)| criterion.matches(cell))
+            .map(|(_, value)| value.clone())
+            .collect();
+        calculate_aggregate(vec![types::Value::Iterator(matches)], f)
+    })
+}
+
+/// The cells of every range and the criteria of the ranges.
+type RangesAndCriteria<N> = (Vec<Vec<types::Value<N>>>, Vec<types::Value<N>>);
+
+/// Splits arguments alternating between a range and its criterion into the cells of the
+/// ranges and the criteria. All ranges must have the given number of cells.
+fn ranges_and_criteria<N>(
+    mut args: impl Iterator<Item = types::Value<N>>,
+    size: usize,
+) -> Result<RangesAndCriteria<N>, types::Error>
+where
+    N: XlNum,
+{
+    let (mut ranges, mut criteria) = (Vec::new(), Vec::new());
+    while let Some(range) = args.next() {
+        let range = cells(range);
+        if range.len() != size {
+            return Err(types::Error::Value);
+        }
+        ranges.push(range);
+        criteria.push(args.next().ok_or(types::Error::Argument)?);
+    }
+    Ok((ranges, criteria))
+}
+
+/// Applies a function to the criteria of values, for every position of the arrays among
+/// them like the elements of an array operation.
+fn with_all_criteria<N>(
+    criteria: Vec<types::Value<N>>,
+    f: &impl Fn(&[Criterion<N>]) -> types::Value<N>,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let apply = |values: Vec<types::Value<N>>| {
+        let criteria: Result<Vec<Criterion<N>>, types::Error> =
+            values.iter().map(Criterion::new).collect();
+        match criteria {
+            Ok(criteria) => f(&criteria),
+            Err(error) => types::Value::Error(error),
+        }
+    };
+    broadcast_all(criteria, apply).unwrap_or_else(apply)
+}
+
+/// Whether the cells at every position meet all criteria, one for each range.
+fn meet_criteria<N>(
+    ranges: &[Vec<types::Value<N>>],
+    criteria: &[Criterion<N>],
+    size: usize,
+) -> Vec<bool>
+where
+    N: XlNum,
+{
+    let mut meet = vec![true; size];
+    for (range, criterion) in ranges.iter().zip(criteria) {
+        for (meets, cell) in meet.iter_mut().zip(range) {
+            *meets = *meets && criterion.matches(cell);
+        }
+    }
+    meet
+}
+
+/// COUNTIFS: counts the positions at which the cells of all ranges meet their criteria, for
+/// every position of array criteria.
+pub fn calculate_countifs<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.is_empty() || !args.len().is_multiple_of(2) {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let size = cells(args[0].clone()).len();
+    let (ranges, criteria) = match ranges_and_criteria(args.into_iter(), size) {
+        Ok(split) => split,
+        Err(error) => return types::Value::Error(error),
+    };
+    with_all_criteria(criteria, &|criteria| {
+        let meet = meet_criteria(&ranges, criteria, size);
+        number_result(N::from_usize(
+            meet.into_iter().filter(|meets| *meets).count(),
+        ))
+    })
+}
+
+/// SUMIFS, AVERAGEIFS, MAXIFS and MINIFS: aggregates the numbers of the cells of the first
+/// range at the positions at which the cells of all other ranges meet their criteria, for
+/// every position of array criteria.
+pub fn calculate_aggregate_ifs<N>(
+    args: Vec<types::Value<N>>,
+    f: fn(Vec<N>) -> Result<N, types::Error>,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.len() < 3 || args.len().is_multiple_of(2) {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let mut args = args.into_iter();
+    let values = cells(args.next().unwrap_or(types::Value::Blank));
+    let (ranges, criteria) = match ranges_and_criteria(args, values.len()) {
+        Ok(split) => split,
+        Err(error) => return types::Value::Error(error),
+    };
+    with_all_criteria(criteria, &|criteria| {
+        let matches = values
+            .iter()
+            .zip(meet_criteria(&ranges, criteria, values.len()))
+            .filter(|(_, meets)| *meets)
+            .map(|(value, _)| value.clone())
+            .collect();
+        calculate_aggregate(vec![types::Value::Iterator(matches)], f)
+    })
+}
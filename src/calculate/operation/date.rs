@@ -8,7 +8,7 @@ where
     <N as FromStr>::Err: Debug,
 {
     match rhs {
-        types::Value::Number(x) => types::Value::Date(d + Duration::days(x.as_())),
+        types::Value::Number(x) => types::Value::Date(d + Duration::days(x.as_i64())),
         _ => types::Value::Error(types::Error::Value),
     }
 }
@@ -19,7 +19,7 @@ where
     <N as FromStr>::Err: Debug,
 {
     match rhs {
-        types::Value::Number(x) => types::Value::Date(d - Duration::days(x.as_())),
+        types::Value::Number(x) => types::Value::Date(d - Duration::days(x.as_i64())),
         _ => types::Value::Error(types::Error::Value),
     }
 }
@@ -0,0 +1,571 @@
+use super::math::{from_float, to_float};
+use crate::types::{self, XlNum};
+use std::f64::consts::PI;
+
+/// The coefficients of the Lanczos approximation of the gamma function with g = 7, which is
+/// accurate to about 15 significant digits.
+const LANCZOS_G: f64 = 7.0;
+const LANCZOS_COEFFICIENTS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// The relative precision and the maximum number of iterations of the series and continued
+/// fractions of the incomplete gamma and beta functions, which are NaN and so #NUM! if they
+/// do not converge within them.
+const EPSILON: f64 = 1e-15;
+const MAX_ITERATIONS: usize = 10_000;
+
+/// From this shape on, the incomplete gamma function is integrated numerically, since its
+/// series and continued fraction take about the square root of the shape of iterations, and
+/// the logarithms of densities are taken relative to their mode.
+const LARGE_SHAPE: f64 = 100.0;
+
+/// The number of nodes of the quadrature of the incomplete gamma function of large shapes,
+/// which is precise to about 13 significant digits.
+const QUADRATURE_NODES: usize = 40;
+
+/// CHISQ.DIST takes at most this number of degrees of freedom.
+const MAX_DEGREES_OF_FREEDOM: f64 = 1e10;
+
+/// The Lanczos sum and the base of the power of the approximation, for x of at least 0.5.
+fn lanczos(x: f64) -> (f64, f64) {
+    let x = x - 1.0;
+    let sum = LANCZOS_COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS_COEFFICIENTS[0], |sum, (i, coefficient)| {
+            sum + coefficient / (x + i as f64 + 1.0)
+        });
+    (sum, x + LANCZOS_G + 0.5)
+}
+
+/// The natural logarithm of the gamma function of a positive number.
+fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        // The reflection formula, since the approximation is for x of at least 0.5.
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    if x == 1.0 || x == 2.0 {
+        // Exactly 0, where the approximation is off in the last digits.
+        return 0.0;
+    }
+    let (sum, t) = lanczos(x);
+    0.5 * (2.0 * PI).ln() + (x - 0.5) * t.ln() - t + sum.ln()
+}
+
+/// The remainder of Stirling's approximation of the logarithm of the gamma function,
+/// ln Γ(x) - ((x - 1/2) ln x - x + ln(2π) / 2), by its asymptotic series for large numbers.
+fn stirling_remainder(x: f64) -> f64 {
+    if x < 10.0 {
+        return ln_gamma(x) - ((x - 0.5) * x.ln() - x + 0.5 * (2.0 * PI).ln());
+    }
+    let square = x * x;
+    (1.0 / 12.0
+        - (1.0 / 360.0
+            - (1.0 / 1260.0 - (1.0 / 1680.0 - 1.0 / (1188.0 * square)) / square) / square)
+            / square)
+        / x
+}
+
+/// ln(1 + x) - x, whose terms are both small or cancel out for small numbers.
+fn ln_1p_minus(x: f64) -> f64 {
+    x.ln_1p() - x
+}
+
+/// The gamma function, NaN for 0 and negative whole numbers.
+fn gamma(x: f64) -> f64 {
+    if x <= 0.0 && x == x.trunc() {
+        return f64::NAN;
+    }
+    if x < 0.5 {
+        return PI / ((PI * x).sin() * gamma(1.0 - x));
+    }
+    let (sum, t) = lanczos(x);
+    // The power is split in two, so that it does not overflow before the division by e^t.
+    let power = t.powf((x - 0.5) / 2.0);
+    (2.0 * PI).sqrt() * power * (power * (-t).exp()) * sum
+}
+
+/// The density of the gamma distribution with a scale of 1, x^(a - 1) e^-x / Γ(a). For large
+/// shapes, the logarithm is taken relative to the mode a - 1, with Stirling's series for
+/// Γ(a), since the terms of the direct formula cancel out to a few significant digits.
+fn gamma_density(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return if a < 1.0 {
+            f64::INFINITY
+        } else if a == 1.0 {
+            1.0
+        } else {
+            0.0
+        };
+    }
+    if a < LARGE_SHAPE {
+        return ((a - 1.0) * x.ln() - x - ln_gamma(a)).exp();
+    }
+    let mode = a - 1.0;
+    (mode * ln_1p_minus((x - mode) / mode) - stirling_remainder(mode)).exp()
+        / (2.0 * PI * mode).sqrt()
+}
+
+/// The regularized lower and upper incomplete gamma functions P(a, x) and Q(a, x), by a
+/// series for x less than a + 1 and by a continued fraction otherwise, or by quadrature for
+/// large shapes.
+fn regularized_gamma(a: f64, x: f64) -> (f64, f64) {
+    if x <= 0.0 {
+        return (0.0, 1.0);
+    }
+    if a >= LARGE_SHAPE {
+        return integrated_gamma(a, x);
+    }
+    let prefactor = (a * x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        let lower = (gamma_series(a, x) * prefactor).clamp(0.0, 1.0);
+        (lower, 1.0 - lower)
+    } else {
+        let upper = (continued_fraction(|i| (i * (a - i), x - a + 2.0 * i + 1.0), x + 1.0 - a)
+            * prefactor)
+            .clamp(0.0, 1.0);
+        (1.0 - upper, upper)
+    }
+}
+
+/// The series of the lower incomplete gamma function without its prefactor x^a e^-x / Γ(a).
+fn gamma_series(a: f64, x: f64) -> f64 {
+    let mut term = 1.0 / a;
+    let mut sum = term;
+    let mut n = a;
+    for _ in 0..MAX_ITERATIONS {
+        n += 1.0;
+        term *= x / n;
+        sum += term;
+        if term.abs() < sum.abs() * EPSILON {
+            return sum;
+        }
+    }
+    f64::NAN
+}
+
+/// The regularized incomplete gamma functions of large shapes, by quadrature of the density
+/// from x over the tail on its side of the mode. The tail is cut off a few standard
+/// deviations from the mode and from x, where the density is negligible.
+fn integrated_gamma(a: f64, x: f64) -> (f64, f64) {
+    let mode = a - 1.0;
+    let sd = mode.sqrt();
+    let integral = |from: f64, to: f64| {
+        (to - from)
+            * gauss_legendre()
+                .iter()
+                .map(|(node, weight)| weight * gamma_density(a, from + (to - from) * node))
+                .sum::<f64>()
+    };
+    if x > mode {
+        let upper = integral(x, (mode + 11.5 * sd).max(x + 6.0 * sd));
+        (1.0 - upper, upper)
+    } else {
+        let lower = integral((mode - 7.5 * sd).min(x - 5.0 * sd).max(0.0), x);
+        (lower, 1.0 - lower)
+    }
+}
+
+/// The nodes and weights of the Gauss-Legendre quadrature from 0 to 1, with the roots of the
+/// Legendre polynomial found by Newton's method.
+fn gauss_legendre() -> [(f64, f64); QUADRATURE_NODES] {
+    let n = QUADRATURE_NODES as f64;
+    let mut nodes = [(0.0, 0.0); QUADRATURE_NODES];
+    for (i, node) in nodes.iter_mut().enumerate() {
+        let mut x = (PI * (i as f64 + 0.75) / (n + 0.5)).cos();
+        let mut derivative = 1.0;
+        for _ in 0..MAX_ITERATIONS {
+            let (mut previous, mut legendre) = (1.0, x);
+            for k in 2..=QUADRATURE_NODES {
+                let k = k as f64;
+                (previous, legendre) = (
+                    legendre,
+                    ((2.0 * k - 1.0) * x * legendre - (k - 1.0) * previous) / k,
+                );
+            }
+            derivative = n * (x * legendre - previous) / (x * x - 1.0);
+            let step = legendre / derivative;
+            x -= step;
+            if step.abs() < EPSILON {
+                break;
+            }
+        }
+        *node = (
+            (1.0 - x) / 2.0,
+            1.0 / ((1.0 - x * x) * derivative * derivative),
+        );
+    }
+    nodes
+}
+
+/// Evaluates 1 / (b0 + a1 / (b1 + a2 / (b2 + ...))) with the modified Lentz's method, where
+/// the terms give the numerator and the denominator of the fraction at an index from 1.
+fn continued_fraction(terms: impl Fn(f64) -> (f64, f64), b0: f64) -> f64 {
+    let tiny = f64::MIN_POSITIVE / EPSILON;
+    let nonzero = |number: f64| if number.abs() < tiny { tiny } else { number };
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / nonzero(b0);
+    let mut result = d;
+    for i in 1..MAX_ITERATIONS {
+        let (a, b) = terms(i as f64);
+        d = 1.0 / nonzero(b + a * d);
+        c = nonzero(b + a / c);
+        let delta = c * d;
+        result *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            return result;
+        }
+    }
+    f64::NAN
+}
+
+/// The regularized incomplete beta function I_x(a, b), by a continued fraction on the side
+/// where it converges quickly. 1 - x is passed as y, so that it stays precise for x close
+/// to 1.
+fn regularized_beta(x: f64, y: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if y <= 0.0 {
+        return 1.0;
+    }
+    if x > (a + 1.0) / (a + b + 2.0) {
+        return 1.0 - regularized_beta(y, x, b, a);
+    }
+    let terms = |i: f64| {
+        let m = (i / 2.0).trunc();
+        let numerator = if i % 2.0 == 0.0 {
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m))
+        } else {
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0))
+        };
+        (numerator, 1.0)
+    };
+    beta_power_terms(x, y, a, b) * continued_fraction(terms, 1.0) / a
+}
+
+/// x^a y^b / B(a, b) for y = 1 - x. For large parameters, the logarithms are taken relative
+/// to x = a / (a + b) with Stirling's approximation of B(a, b), since the terms of the direct
+/// formula cancel out to a few significant digits.
+fn beta_power_terms(x: f64, y: f64, a: f64, b: f64) -> f64 {
+    let c = a + b;
+    if a.max(b) < LARGE_SHAPE {
+        return (ln_gamma(c) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * y.ln()).exp();
+    }
+    // The distance to the mode, x (a + b) - a, without subtracting numbers close to each other.
+    let distance = x * b - y * a;
+    (a * ln_1p_minus(distance / a)
+        + b * ln_1p_minus(-distance / b)
+        + 0.5 * (a * b / (2.0 * PI * c)).ln()
+        + stirling_remainder(c)
+        - stirling_remainder(a)
+        - stirling_remainder(b))
+    .exp()
+}
+
+fn standard_normal_pdf(z: f64) -> f64 {
+    (-z * z / 2.0).exp() / (2.0 * PI).sqrt()
+}
+
+/// The cumulative standard normal distribution, by the complementary error function
+/// erfc(z) = Q(1/2, z^2), so that the small probabilities of the tails stay precise.
+fn standard_normal_cdf(z: f64) -> f64 {
+    let tail = regularized_gamma(0.5, z * z / 2.0).1 / 2.0;
+    if z > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// The inverse of the cumulative standard normal distribution, by Acklam's rational
+/// approximation and a step of Halley's method.
+fn standard_normal_inverse(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.02425;
+    let polynomial = |coefficients: &[f64], x: f64| {
+        coefficients
+            .iter()
+            .fold(0.0, |result, coefficient| result * x + coefficient)
+    };
+    let tail = |p: f64| {
+        let q = (-2.0 * p.ln()).sqrt();
+        polynomial(&C, q) / (polynomial(&D, q) * q + 1.0)
+    };
+    let z = if p < P_LOW {
+        tail(p)
+    } else if p > 1.0 - P_LOW {
+        -tail(1.0 - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        polynomial(&A, r) * q / (polynomial(&B, r) * r + 1.0)
+    };
+    let step = (standard_normal_cdf(z) - p) / standard_normal_pdf(z);
+    z - step / (1.0 + z * step / 2.0)
+}
+
+/// The cumulative t-distribution, by the regularized incomplete beta function of
+/// v / (v + t^2), with the complement t^2 / (v + t^2) for precise probabilities around the
+/// median.
+fn t_cdf(t: f64, degrees_of_freedom: f64) -> f64 {
+    let v = degrees_of_freedom;
+    let tail = regularized_beta(v / (v + t * t), t * t / (v + t * t), v / 2.0, 0.5) / 2.0;
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// The density of the t-distribution. For large v, the ratio Γ((v + 1) / 2) / Γ(v / 2) is
+/// taken by Stirling's approximation, since the logarithms of the gamma functions cancel out.
+fn t_pdf(t: f64, degrees_of_freedom: f64) -> f64 {
+    let v = degrees_of_freedom;
+    let half = v / 2.0;
+    if half < LARGE_SHAPE {
+        return (ln_gamma(half + 0.5) - ln_gamma(half) - (v + 1.0) / 2.0 * (t * t / v).ln_1p())
+            .exp()
+            / (v * PI).sqrt();
+    }
+    (half * ln_1p_minus(0.5 / half) + stirling_remainder(half + 0.5)
+        - stirling_remainder(half)
+        - (v + 1.0) / 2.0 * (t * t / v).ln_1p())
+    .exp()
+        / (2.0 * PI).sqrt()
+}
+
+/// Finds the number where an increasing function reaches a value, by widening a bracket
+/// around 0 until it contains the number and halving it until the floats run out. NaN if the
+/// function is NaN.
+fn increasing_inverse(f: impl Fn(f64) -> f64, value: f64) -> f64 {
+    let (mut low, mut high) = (-1.0, 1.0);
+    while f(low) > value && low.is_finite() {
+        low *= 2.0;
+    }
+    while f(high) < value && high.is_finite() {
+        high *= 2.0;
+    }
+    for _ in 0..MAX_ITERATIONS {
+        let middle = (low + high) / 2.0;
+        if middle <= low || middle >= high {
+            break;
+        }
+        let result = f(middle);
+        if result.is_nan() {
+            return f64::NAN;
+        }
+        if result == value {
+            return middle;
+        }
+        if result < value {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Probabilities of inverse distributions are #NUM! unless between 0 and 1.
+fn probability(p: f64) -> Result<f64, types::Error> {
+    if p <= 0.0 || p >= 1.0 {
+        return Err(types::Error::Num);
+    }
+    Ok(p)
+}
+
+/// Standard deviations are #NUM! unless positive.
+fn standard_deviation(sd: f64) -> Result<f64, types::Error> {
+    if sd <= 0.0 {
+        return Err(types::Error::Num);
+    }
+    Ok(sd)
+}
+
+/// Degrees of freedom are truncated to whole numbers, #NUM! if less than 1.
+fn degrees_of_freedom(degrees_of_freedom: f64) -> Result<f64, types::Error> {
+    let degrees_of_freedom = degrees_of_freedom.trunc();
+    if degrees_of_freedom < 1.0 {
+        return Err(types::Error::Num);
+    }
+    Ok(degrees_of_freedom)
+}
+
+fn is_cumulative(cumulative: f64) -> bool {
+    cumulative != 0.0
+}
+
+/// NORM.DIST(x, mean, standard_dev, cumulative): the normal distribution.
+pub fn norm_dist(args: &[f64]) -> Result<f64, types::Error> {
+    let sd = standard_deviation(args[2])?;
+    let z = (args[0] - args[1]) / sd;
+    if is_cumulative(args[3]) {
+        Ok(standard_normal_cdf(z))
+    } else {
+        Ok(standard_normal_pdf(z) / sd)
+    }
+}
+
+/// NORM.S.DIST(z, cumulative): the standard normal distribution.
+pub fn norm_s_dist(args: &[f64]) -> Result<f64, types::Error> {
+    norm_dist(&[args[0], 0.0, 1.0, args[1]])
+}
+
+/// NORM.INV(probability, mean, standard_dev): the inverse of the cumulative normal
+/// distribution.
+pub fn norm_inv(args: &[f64]) -> Result<f64, types::Error> {
+    let p = probability(args[0])?;
+    let sd = standard_deviation(args[2])?;
+    Ok(args[1] + sd * standard_normal_inverse(p))
+}
+
+/// T.DIST(x, deg_freedom, cumulative): the left-tailed t-distribution.
+pub fn t_dist(args: &[f64]) -> Result<f64, types::Error> {
+    let v = degrees_of_freedom(args[1])?;
+    if is_cumulative(args[2]) {
+        Ok(t_cdf(args[0], v))
+    } else {
+        Ok(t_pdf(args[0], v))
+    }
+}
+
+/// T.INV(probability, deg_freedom): the inverse of the left-tailed t-distribution.
+pub fn t_inv(args: &[f64]) -> Result<f64, types::Error> {
+    let p = probability(args[0])?;
+    let v = degrees_of_freedom(args[1])?;
+    Ok(increasing_inverse(|t| t_cdf(t, v), p))
+}
+
+/// CHISQ.DIST(x, deg_freedom, cumulative): the chi-squared distribution. Negative numbers
+/// and more than 10^10 degrees of freedom are #NUM!.
+pub fn chisq_dist(args: &[f64]) -> Result<f64, types::Error> {
+    let x = args[0];
+    let k = degrees_of_freedom(args[1])?;
+    if x < 0.0 || k > MAX_DEGREES_OF_FREEDOM {
+        return Err(types::Error::Num);
+    }
+    let a = k / 2.0;
+    if is_cumulative(args[2]) {
+        return Ok(regularized_gamma(a, x / 2.0).0);
+    }
+    // The density at 0 is infinite for 1 degree of freedom, which is #NUM!.
+    Ok(gamma_density(a, x / 2.0) / 2.0)
+}
+
+/// BINOM.DIST(number_s, trials, probability_s, cumulative): the binomial distribution of
+/// the number of successes, both truncated to whole numbers. Negative numbers, more
+/// successes than trials and probabilities outside of 0 to 1 are #NUM!.
+pub fn binom_dist(args: &[f64]) -> Result<f64, types::Error> {
+    let (s, n, p) = (args[0].trunc(), args[1].trunc(), args[2]);
+    if s < 0.0 || s > n || !(0.0..=1.0).contains(&p) {
+        return Err(types::Error::Num);
+    }
+    if is_cumulative(args[3]) {
+        if s == n {
+            return Ok(1.0);
+        }
+        return Ok(regularized_beta(1.0 - p, p, n - s, s + 1.0));
+    }
+    if p == 0.0 || p == 1.0 {
+        let certain = if p == 0.0 { 0.0 } else { n };
+        return Ok(if s == certain { 1.0 } else { 0.0 });
+    }
+    // C(n, s) = 1 / ((n + 1) B(s + 1, n - s + 1)).
+    Ok(beta_power_terms(p, 1.0 - p, s + 1.0, n - s + 1.0) / ((n + 1.0) * p * (1.0 - p)))
+}
+
+/// POISSON.DIST(x, mean, cumulative): the Poisson distribution of a number of events,
+/// truncated to a whole number. Negative numbers and means are #NUM!.
+pub fn poisson_dist(args: &[f64]) -> Result<f64, types::Error> {
+    let (x, mean) = (args[0].trunc(), args[1]);
+    if x < 0.0 || mean < 0.0 {
+        return Err(types::Error::Num);
+    }
+    if is_cumulative(args[2]) {
+        return Ok(regularized_gamma(x + 1.0, mean).1);
+    }
+    Ok(gamma_density(x + 1.0, mean))
+}
+
+/// EXPON.DIST(x, lambda, cumulative): the exponential distribution. Negative numbers and
+/// rates that are not positive are #NUM!.
+pub fn expon_dist(args: &[f64]) -> Result<f64, types::Error> {
+    let (x, lambda) = (args[0], args[1]);
+    if x < 0.0 || lambda <= 0.0 {
+        return Err(types::Error::Num);
+    }
+    if is_cumulative(args[2]) {
+        Ok(-(-lambda * x).exp_m1())
+    } else {
+        Ok(lambda * (-lambda * x).exp())
+    }
+}
+
+/// CONFIDENCE.NORM(alpha, standard_dev, size): the half width of the confidence interval
+/// of a mean with a normal distribution. The size is truncated to a whole number, #NUM! if
+/// less than 1.
+pub fn confidence_norm(args: &[f64]) -> Result<f64, types::Error> {
+    let alpha = probability(args[0])?;
+    let sd = standard_deviation(args[1])?;
+    let size = args[2].trunc();
+    if size < 1.0 {
+        return Err(types::Error::Num);
+    }
+    Ok(-standard_normal_inverse(alpha / 2.0) * sd / size.sqrt())
+}
+
+/// GAMMA: the gamma function. 0, negative whole numbers and overflows are #NUM!.
+pub fn gamma_function<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    from_float(gamma(to_float(number).ok()?))
+}
+
+/// GAMMALN: the natural logarithm of the gamma function. Numbers that are not positive are
+/// #NUM!.
+pub fn gammaln<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    let x = to_float(number).ok().filter(|x| *x > 0.0)?;
+    from_float(ln_gamma(x))
+}
@@ -0,0 +1,298 @@
+use super::{
+    array::{broadcast, broadcast_all},
+    number::{number_argument, number_result},
+};
+use crate::{
+    calculate::display::show_number,
+    types::{self, XlNum},
+};
+
+/// The number of digits of the results of DEC2BIN, DEC2OCT and DEC2HEX and of the arguments
+/// of BIN2DEC, OCT2DEC and HEX2DEC. Negative numbers are their two's complement with this
+/// number of digits, so that the first half of the numbers with 10 digits are positive.
+const DIGITS: u32 = 10;
+
+/// The bit functions take numbers less than 2^48.
+const MAX_BITS: u64 = 1 << 48;
+
+/// BASE takes and DECIMAL returns numbers less than 2^53.
+const MAX_BASE_NUMBER: u64 = 1 << 53;
+
+fn num_error<N>() -> types::Value<N>
+where
+    N: XlNum,
+{
+    types::Value::Error(types::Error::Num)
+}
+
+/// Converts an argument to a whole number, truncating decimal places.
+fn integer_argument<N>(value: types::Value<N>) -> Result<i64, types::Value<N>>
+where
+    N: XlNum,
+{
+    number_argument(value)?
+        .trunc()
+        .to_i64()
+        .ok_or_else(num_error)
+}
+
+/// Converts an argument with digits to text. Numbers are written like in formulas, so that
+/// `BIN2DEC(101)` is `BIN2DEC("101")`.
+fn text_argument<N>(value: types::Value<N>) -> Result<String, types::Value<N>>
+where
+    N: XlNum,
+{
+    match value {
+        types::Value::Text(text) => Ok(text),
+        types::Value::Number(number) => Ok(show_number(number)),
+        types::Value::Blank => Ok(String::new()),
+        types::Value::Error(_) => Err(value),
+        types::Value::Boolean(_) | types::Value::Date(_) | types::Value::Iterator(_) => {
+            Err(types::Value::Error(types::Error::Value))
+        }
+    }
+}
+
+/// Writes a number with the digits of the radix, in upper case.
+fn to_digits(mut number: u64, radix: u32) -> String {
+    let mut digits = Vec::new();
+    loop {
+        let digit = (number % u64::from(radix)) as u32;
+        digits.push(
+            char::from_digit(digit, radix)
+                .unwrap_or('0')
+                .to_ascii_uppercase(),
+        );
+        number /= u64::from(radix);
+        if number == 0 {
+            break;
+        }
+    }
+    digits.into_iter().rev().collect()
+}
+
+/// Reads the digits of the radix, ignoring case. An empty text is 0. #NUM! for other
+/// characters and numbers of at least the maximum.
+fn from_digits(text: &str, radix: u32, max: u64) -> Result<u64, types::Error> {
+    text.chars().try_fold(0u64, |number, digit| {
+        let digit = digit.to_digit(radix).ok_or(types::Error::Num)?;
+        number
+            .checked_mul(u64::from(radix))
+            .and_then(|number| number.checked_add(u64::from(digit)))
+            .filter(|number| *number < max)
+            .ok_or(types::Error::Num)
+    })
+}
+
+/// DEC2BIN, DEC2OCT and DEC2HEX(number, [places]): a number in the radix, padded with zeros
+/// to the number of places. Negative numbers are their two's complement with 10 digits,
+/// ignoring the places. Numbers that do not fit into 10 digits and too few or more than 10
+/// places are #NUM!. Arrays are converted element by element.
+pub fn calculate_dec2base<N>(args: Vec<types::Value<N>>, radix: u32) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.is_empty() || args.len() > 2 {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let args = match broadcast_all(args, |args| calculate_dec2base(args, radix)) {
+        Ok(value) => return value,
+        Err(args) => args,
+    };
+    let mut args = args.into_iter();
+    let mut dec2base = || {
+        let number = integer_argument(args.next().unwrap_or(types::Value::Blank))?;
+        let places = args.next().map(integer_argument).transpose()?;
+        let complement = i64::from(radix).pow(DIGITS);
+        if number < -complement / 2 || number >= complement / 2 {
+            return Err(num_error());
+        }
+        if number < 0 {
+            return Ok(to_digits((number + complement) as u64, radix));
+        }
+        let digits = to_digits(number as u64, radix);
+        match places.map(usize::try_from) {
+            None => Ok(digits),
+            Some(Ok(places)) if (digits.len()..=DIGITS as usize).contains(&places) => {
+                Ok(format!("{digits:0>places$}"))
+            }
+            Some(_) => Err(num_error()),
+        }
+    };
+    match dec2base() {
+        Ok(digits) => types::Value::Text(digits),
+        Err(error) => error,
+    }
+}
+
+/// BIN2DEC, OCT2DEC and HEX2DEC: the number of at most 10 digits of the radix, where 10
+/// digits starting with the upper half of the digits are the two's complement of a negative
+/// number. Other characters and more digits are #NUM!. Arrays are converted element by
+/// element.
+pub fn calculate_base2dec<N>(value: types::Value<N>, radix: u32) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let value = match broadcast_all(vec![value], |mut args| {
+        calculate_base2dec(args.remove(0), radix)
+    }) {
+        Ok(value) => return value,
+        Err(mut args) => args.remove(0),
+    };
+    let text = match text_argument(value) {
+        Ok(text) => text,
+        Err(error) => return error,
+    };
+    if text.chars().count() > DIGITS as usize {
+        return num_error();
+    }
+    let complement = u64::from(radix).pow(DIGITS);
+    match from_digits(&text, radix, complement) {
+        Ok(number) if number >= complement / 2 => {
+            number_result(N::from_i64(number as i64 - complement as i64))
+        }
+        Ok(number) => number_result(N::from_u64(number)),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+/// The radix of BASE and DECIMAL, #NUM! unless from 2 to 36.
+fn radix_argument<N>(value: types::Value<N>) -> Result<u32, types::Value<N>>
+where
+    N: XlNum,
+{
+    u32::try_from(integer_argument(value)?)
+        .ok()
+        .filter(|radix| (2..=36).contains(radix))
+        .ok_or_else(num_error)
+}
+
+/// BASE(number, radix, [min_length]): a number in a radix from 2 to 36, padded with zeros to
+/// the minimum length of at most 255. Negative numbers and numbers from 2^53 are #NUM!.
+/// Arrays are converted element by element.
+pub fn calculate_base<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.len() < 2 || args.len() > 3 {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let args = match broadcast_all(args, calculate_base) {
+        Ok(value) => return value,
+        Err(args) => args,
+    };
+    let mut args = args.into_iter();
+    let mut next = || args.next().unwrap_or(types::Value::Blank);
+    let mut base = || {
+        let number = u64::try_from(integer_argument(next())?)
+            .ok()
+            .filter(|number| *number < MAX_BASE_NUMBER)
+            .ok_or_else(num_error)?;
+        let radix = radix_argument(next())?;
+        let min_length = usize::try_from(integer_argument(next())?)
+            .ok()
+            .filter(|min_length| *min_length <= 255)
+            .ok_or_else(num_error)?;
+        Ok(format!("{:0>min_length$}", to_digits(number, radix)))
+    };
+    match base() {
+        Ok(digits) => types::Value::Text(digits),
+        Err(error) => error,
+    }
+}
+
+/// DECIMAL(text, radix): the number of the digits of a radix from 2 to 36, ignoring case.
+/// Other characters, more than 255 of them and numbers from 2^53 are #NUM!. Arrays are
+/// converted element by element.
+pub fn calculate_decimal<N>(text: types::Value<N>, radix: types::Value<N>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let (text, radix) = match broadcast(text, radix, calculate_decimal) {
+        Ok(value) => return value,
+        Err(args) => args,
+    };
+    let (text, radix) = match (text_argument(text), radix_argument(radix)) {
+        (Ok(text), Ok(radix)) => (text, radix),
+        (Err(error), _) | (_, Err(error)) => return error,
+    };
+    if text.chars().count() > 255 {
+        return num_error();
+    }
+    match from_digits(&text, radix, MAX_BASE_NUMBER) {
+        Ok(number) => number_result(N::from_u64(number)),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+/// The bits of a whole number from 0 to 2^48. Other numbers are #NUM!.
+fn bits<N>(number: N) -> Result<u64, types::Error>
+where
+    N: XlNum,
+{
+    if number.trunc() != number {
+        return Err(types::Error::Num);
+    }
+    number
+        .to_u64()
+        .filter(|bits| *bits < MAX_BITS)
+        .ok_or(types::Error::Num)
+}
+
+fn bits_result<N>(bits: u64) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    N::from_u64(bits).ok_or(types::Error::Num)
+}
+
+pub fn bitand<N>(lhs: N, rhs: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    bits_result(bits(lhs)? & bits(rhs)?)
+}
+
+pub fn bitor<N>(lhs: N, rhs: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    bits_result(bits(lhs)? | bits(rhs)?)
+}
+
+pub fn bitxor<N>(lhs: N, rhs: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    bits_result(bits(lhs)? ^ bits(rhs)?)
+}
+
+/// BITLSHIFT: shifts the bits to the left, or to the right for a negative shift amount.
+/// Shift amounts greater than 53 and results from 2^48 are #NUM!.
+pub fn bitlshift<N>(number: N, shift: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let bits = bits(number)?;
+    let shift = shift
+        .trunc()
+        .to_i64()
+        .filter(|shift| shift.abs() <= 53)
+        .ok_or(types::Error::Num)?;
+    if shift < 0 {
+        return bits_result(bits >> -shift);
+    }
+    let shifted = u128::from(bits) << shift;
+    if shifted >= u128::from(MAX_BITS) {
+        return Err(types::Error::Num);
+    }
+    bits_result(shifted as u64)
+}
+
+/// BITRSHIFT: shifts the bits to the right, or to the left for a negative shift amount.
+pub fn bitrshift<N>(number: N, shift: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    bitlshift(number, -shift)
+}
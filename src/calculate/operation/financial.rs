@@ -0,0 +1,370 @@
+use super::{
+    math::{error_of, float_result, to_float},
+    number::number_argument,
+    statistics::collect_numbers,
+};
+use crate::{
+    format::date_to_serial,
+    types::{self, XlNum},
+};
+
+/// Finds a root of a function with Newton's method, starting at the guess, and stops once
+/// a step is smaller than the tolerance without increasing the residual. If Newton's method
+/// diverges, falls back to bisection between the nearest rates around the guess where the
+/// function changes sign. #NUM! if neither converges.
+fn solve(
+    guess: f64,
+    max_iterations: usize,
+    tolerance: f64,
+    f: impl Fn(f64) -> f64,
+) -> Result<f64, types::Error> {
+    newton(guess, max_iterations, tolerance, &f).or_else(|_| bisect(guess, tolerance, &f))
+}
+
+fn newton(
+    guess: f64,
+    max_iterations: usize,
+    tolerance: f64,
+    f: &impl Fn(f64) -> f64,
+) -> Result<f64, types::Error> {
+    const STEP: f64 = 1e-7;
+    let mut x = guess;
+    for _ in 0..max_iterations {
+        let value = f(x);
+        if value == 0.0 {
+            return Ok(x);
+        }
+        let derivative = (f(x + STEP) - f(x - STEP)) / (2.0 * STEP);
+        if !value.is_finite() || !derivative.is_finite() || derivative == 0.0 {
+            return Err(types::Error::Num);
+        }
+        let next = x - value / derivative;
+        if (next - x).abs() < tolerance && f(next).abs() <= value.abs() {
+            return Ok(next);
+        }
+        x = next;
+    }
+    Err(types::Error::Num)
+}
+
+fn bisect(guess: f64, tolerance: f64, f: &impl Fn(f64) -> f64) -> Result<f64, types::Error> {
+    const RATES: [f64; 12] = [
+        -0.99, -0.5, -0.1, 0.0, 0.001, 0.01, 0.1, 0.5, 1.0, 2.0, 5.0, 10.0,
+    ];
+    const MAX_ITERATIONS: usize = 200;
+    let (mut low, mut high) = RATES
+        .windows(2)
+        .map(|pair| (pair[0], pair[1]))
+        .filter(|&(low, high)| {
+            let (a, b) = (f(low), f(high));
+            a.is_finite() && b.is_finite() && a.signum() != b.signum()
+        })
+        .min_by(|a, b| {
+            let distance = |(low, high): (f64, f64)| (guess - (low + high) / 2.0).abs();
+            distance(*a).total_cmp(&distance(*b))
+        })
+        .ok_or(types::Error::Num)?;
+    let mut value_low = f(low);
+    for _ in 0..MAX_ITERATIONS {
+        let middle = (low + high) / 2.0;
+        if high - low < tolerance {
+            return Ok(middle);
+        }
+        let value = f(middle);
+        if !value.is_finite() {
+            return Err(types::Error::Num);
+        }
+        if value == 0.0 {
+            return Ok(middle);
+        }
+        if value.signum() == value_low.signum() {
+            low = middle;
+            value_low = value;
+        } else {
+            high = middle;
+        }
+    }
+    Err(types::Error::Num)
+}
+
+/// Payments at the beginning of periods for a non-zero type argument, at the end otherwise.
+fn due(kind: f64) -> f64 {
+    if kind == 0.0 {
+        0.0
+    } else {
+        1.0
+    }
+}
+
+fn future_value(rate: f64, nper: f64, pmt: f64, pv: f64, kind: f64) -> f64 {
+    if rate == 0.0 {
+        -(pv + pmt * nper)
+    } else {
+        let growth = (1.0 + rate).powf(nper);
+        -(pv * growth + pmt * (1.0 + rate * due(kind)) * (growth - 1.0) / rate)
+    }
+}
+
+fn payment(rate: f64, nper: f64, pv: f64, fv: f64, kind: f64) -> f64 {
+    if rate == 0.0 {
+        -(pv + fv) / nper
+    } else {
+        let growth = (1.0 + rate).powf(nper);
+        -rate * (fv + pv * growth) / ((1.0 + rate * due(kind)) * (growth - 1.0))
+    }
+}
+
+/// FV(rate, nper, pmt, [pv], [type]): the future value of an investment.
+pub fn fv(args: &[f64]) -> Result<f64, types::Error> {
+    let &[rate, nper, pmt, pv, kind] = args else {
+        return Err(types::Error::Argument);
+    };
+    Ok(future_value(rate, nper, pmt, pv, kind))
+}
+
+/// PV(rate, nper, pmt, [fv], [type]): the present value of an investment.
+pub fn pv(args: &[f64]) -> Result<f64, types::Error> {
+    let &[rate, nper, pmt, fv, kind] = args else {
+        return Err(types::Error::Argument);
+    };
+    if rate == 0.0 {
+        return Ok(-(fv + pmt * nper));
+    }
+    let growth = (1.0 + rate).powf(nper);
+    Ok(-(fv + pmt * (1.0 + rate * due(kind)) * (growth - 1.0) / rate) / growth)
+}
+
+/// PMT(rate, nper, pv, [fv], [type]): the payment per period of a loan.
+pub fn pmt(args: &[f64]) -> Result<f64, types::Error> {
+    let &[rate, nper, pv, fv, kind] = args else {
+        return Err(types::Error::Argument);
+    };
+    Ok(payment(rate, nper, pv, fv, kind))
+}
+
+/// The interest part of the payment of a period, see `ipmt`.
+fn interest_payment(args: &[f64]) -> Result<(f64, f64), types::Error> {
+    let &[rate, per, nper, pv, fv, kind] = args else {
+        return Err(types::Error::Argument);
+    };
+    if per < 1.0 || per > nper {
+        return Err(types::Error::Num);
+    }
+    let payment = payment(rate, nper, pv, fv, kind);
+    let interest = if due(kind) == 1.0 && per == 1.0 {
+        0.0
+    } else {
+        let interest = future_value(rate, per - 1.0, payment, pv, kind) * rate;
+        interest / (1.0 + rate * due(kind))
+    };
+    Ok((payment, interest))
+}
+
+/// IPMT(rate, per, nper, pv, [fv], [type]): the interest part of the payment of a period.
+pub fn ipmt(args: &[f64]) -> Result<f64, types::Error> {
+    interest_payment(args).map(|(_, interest)| interest)
+}
+
+/// PPMT(rate, per, nper, pv, [fv], [type]): the principal part of the payment of a period.
+pub fn ppmt(args: &[f64]) -> Result<f64, types::Error> {
+    interest_payment(args).map(|(payment, interest)| payment - interest)
+}
+
+/// NPER(rate, pmt, pv, [fv], [type]): the number of periods of an investment.
+pub fn nper(args: &[f64]) -> Result<f64, types::Error> {
+    let &[rate, pmt, pv, fv, kind] = args else {
+        return Err(types::Error::Argument);
+    };
+    if rate == 0.0 {
+        return Ok(-(pv + fv) / pmt);
+    }
+    let annuity = pmt * (1.0 + rate * due(kind)) / rate;
+    Ok(((annuity - fv) / (annuity + pv)).ln() / (1.0 + rate).ln())
+}
+
+/// RATE(nper, pmt, pv, [fv], [type], [guess]): the interest rate per period, found by
+/// iteration like Excel: #NUM! if the rate does not converge to within 0.0000001.
+pub fn rate(args: &[f64]) -> Result<f64, types::Error> {
+    let &[nper, pmt, pv, fv, kind, guess] = args else {
+        return Err(types::Error::Argument);
+    };
+    // The residual in future value grows like (1 + rate)^nper, so it is discounted back to
+    // present value to keep the iteration stable for long loans.
+    solve(guess, 20, 1e-7, |rate| {
+        (fv - future_value(rate, nper, pmt, pv, kind)) / (1.0 + rate).powf(nper)
+    })
+}
+
+/// Converts the values of a list of cash flows to floats.
+fn cash_flows<N>(values: Vec<types::Value<N>>) -> Result<Vec<f64>, types::Error>
+where
+    N: XlNum,
+{
+    collect_numbers(values)?.into_iter().map(to_float).collect()
+}
+
+fn net_present_value(rate: f64, values: &[f64], first_period: i32) -> f64 {
+    values
+        .iter()
+        .zip(first_period..)
+        .map(|(value, period)| value / (1.0 + rate).powi(period))
+        .sum()
+}
+
+/// NPV(rate, value1, ...): the net present value of cash flows at the end of periods.
+pub fn calculate_npv<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.len() < 2 {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let mut args = args.into_iter();
+    let npv = || {
+        let rate = args.next().unwrap_or(types::Value::Blank);
+        let rate = to_float(number_argument(rate).map_err(error_of)?)?;
+        let values = cash_flows(args.collect())?;
+        if rate == -1.0 {
+            return Err(types::Error::Div0);
+        }
+        Ok(net_present_value(rate, &values, 1))
+    };
+    float_result(npv())
+}
+
+/// The optional guess of IRR and XIRR, 0.1 by default.
+fn guess<N>(value: Option<types::Value<N>>) -> Result<f64, types::Error>
+where
+    N: XlNum,
+{
+    match value {
+        Some(value) => to_float(number_argument(value).map_err(error_of)?),
+        None => Ok(0.1),
+    }
+}
+
+/// Cash flows need a payment and an income to have a rate of return.
+fn has_both_signs(values: &[f64]) -> bool {
+    values.iter().any(|value| *value > 0.0) && values.iter().any(|value| *value < 0.0)
+}
+
+/// IRR(values, [guess]): the internal rate of return of cash flows at regular periods, for
+/// which their net present value is 0. #NUM! without a positive and a negative cash flow or
+/// if the rate does not converge within 20 iterations.
+pub fn calculate_irr<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.is_empty() || args.len() > 2 {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let mut args = args.into_iter();
+    let mut irr = || {
+        let values = cash_flows(args.next().into_iter().collect())?;
+        let guess = guess(args.next())?;
+        if !has_both_signs(&values) {
+            return Err(types::Error::Num);
+        }
+        solve(guess, 20, 1e-7, |rate| net_present_value(rate, &values, 0))
+    };
+    float_result(irr())
+}
+
+/// Converts dates, or serial numbers of dates, to whole serial numbers.
+fn serial_dates<N>(value: types::Value<N>) -> Result<Vec<f64>, types::Error>
+where
+    N: XlNum,
+{
+    match value {
+        types::Value::Iterator(value_vec) => {
+            let mut dates = Vec::new();
+            for value in value_vec {
+                dates.extend(serial_dates(value)?);
+            }
+            Ok(dates)
+        }
+        types::Value::Date(date) => Ok(vec![date_to_serial(date.naive_local()).trunc()]),
+        types::Value::Number(number) => Ok(vec![to_float(number)?.trunc()]),
+        types::Value::Error(error) => Err(error),
+        _ => Err(types::Error::Value),
+    }
+}
+
+/// The cash flows and the years since the first date of the XNPV and XIRR arguments. #NUM! if
+/// their numbers differ or a date is before the first one.
+fn dated_cash_flows<N>(
+    values: types::Value<N>,
+    dates: types::Value<N>,
+) -> Result<Vec<(f64, f64)>, types::Error>
+where
+    N: XlNum,
+{
+    let values = cash_flows(vec![values])?;
+    let dates = serial_dates(dates)?;
+    if values.is_empty() || values.len() != dates.len() {
+        return Err(types::Error::Num);
+    }
+    let first = dates[0];
+    if dates.iter().any(|date| *date < first) {
+        return Err(types::Error::Num);
+    }
+    Ok(values
+        .into_iter()
+        .zip(dates)
+        .map(|(value, date)| (value, (date - first) / 365.0))
+        .collect())
+}
+
+fn dated_net_present_value(rate: f64, cash_flows: &[(f64, f64)]) -> f64 {
+    cash_flows
+        .iter()
+        .map(|(value, years)| value / (1.0 + rate).powf(*years))
+        .sum()
+}
+
+/// XNPV(rate, values, dates): the net present value of cash flows at the given dates.
+pub fn calculate_xnpv<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let [rate, values, dates] = match <[types::Value<N>; 3]>::try_from(args) {
+        Ok(args) => args,
+        Err(_) => return types::Value::Error(types::Error::Argument),
+    };
+    let xnpv = || {
+        let rate = to_float(number_argument(rate).map_err(error_of)?)?;
+        let cash_flows = dated_cash_flows(values, dates)?;
+        if rate <= -1.0 {
+            return Err(types::Error::Num);
+        }
+        Ok(dated_net_present_value(rate, &cash_flows))
+    };
+    float_result(xnpv())
+}
+
+/// XIRR(values, dates, [guess]): the internal rate of return of cash flows at the given
+/// dates. #NUM! without a positive and a negative cash flow or if the rate does not converge
+/// within 100 iterations.
+pub fn calculate_xirr<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.len() < 2 || args.len() > 3 {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let mut args = args.into_iter();
+    let mut xirr = || {
+        let values = args.next().unwrap_or(types::Value::Blank);
+        let dates = args.next().unwrap_or(types::Value::Blank);
+        let cash_flows = dated_cash_flows(values, dates)?;
+        let guess = guess(args.next())?;
+        let values: Vec<f64> = cash_flows.iter().map(|(value, _)| *value).collect();
+        if !has_both_signs(&values) {
+            return Err(types::Error::Num);
+        }
+        solve(guess, 100, 1e-8, |rate| {
+            dated_net_present_value(rate, &cash_flows)
+        })
+    };
+    float_result(xirr())
+}
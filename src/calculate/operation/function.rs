@@ -1,19 +1,48 @@
 use super::{
+    array::first_element,
     boolean::{calculate_boolean, calculate_isblank, calculate_negate, calculate_negation},
+    calculate_binary_operator,
+    coerce::{calculate_numbervalue, calculate_value},
+    combinatorics,
+    criteria::{
+        calculate_aggregate_if, calculate_aggregate_ifs, calculate_countif, calculate_countifs,
+    },
     date::{calculate_day, calculate_days, calculate_month, calculate_year},
+    distribution,
+    engineering::{
+        self, calculate_base, calculate_base2dec, calculate_dec2base, calculate_decimal,
+    },
+    financial::{self, calculate_irr, calculate_npv, calculate_xirr, calculate_xnpv},
+    math::{self, calculate_float_function},
+    matrix::{
+        calculate_mdeterm, calculate_minverse, calculate_mmult, calculate_sumproduct,
+        calculate_transpose,
+    },
     number::{
-        calculate_abs, calculate_average, calculate_collective_operator,
-        calculate_collective_product_operator,
+        calculate_average, calculate_binary_numeric_function, calculate_collective_operator,
+        calculate_collective_product_operator, calculate_numeric_function, number_argument,
+        number_result,
+    },
+    random::calculate_random_function,
+    round::{
+        ceiling, ceiling_math, floor, floor_math, int, modulo, mround, quotient, round, Rounding,
+    },
+    statistics::{
+        self, calculate_aggregate, calculate_count, calculate_counta, calculate_countblank,
+        calculate_large_small, calculate_percentile, calculate_quartile, calculate_rank,
     },
     string::{
-        find_position_case_sensitive, search_position_with_wildcards, value_to_string_for_find,
-        CoerceForFind,
+        calculate_text, find_position_case_sensitive, search_position_with_wildcards,
+        value_to_string_for_find, CoerceForFind,
     },
 };
 use crate::{
-    calculate::args::{
-        get_binary_function_args, get_find_args, get_number_and_string_values,
-        get_ternary_function_args, get_unary_function_arg,
+    calculate::{
+        args::{
+            get_binary_function_args, get_find_args, get_function_args, get_list_function_args,
+            get_number_and_string_values, get_ternary_function_args, get_unary_function_arg,
+        },
+        random::with_thread_random,
     },
     types::{self, Error, XlNum},
 };
@@ -25,7 +54,7 @@ where
     <N as FromStr>::Err: Debug,
 {
     let trim_length = match number {
-        types::Value::Number(x) => x.as_(),
+        types::Value::Number(x) => x.as_i64().max(0) as usize,
         _ => 0,
     };
 
@@ -47,7 +76,7 @@ where
     <N as FromStr>::Err: Debug,
 {
     let trim_length = match number {
-        types::Value::Number(x) => x.as_(),
+        types::Value::Number(x) => x.as_i64().max(0) as usize,
         _ => 0,
     };
 
@@ -104,21 +133,24 @@ where
     <N as FromStr>::Err: Debug,
 {
     match func {
-        types::Function::Abs => calculate_abs(get_unary_function_arg(exp, f)),
+        types::Function::Abs => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::abs)
+        }
         types::Function::Sum => {
-            calculate_collective_operator(types::Value::Number(N::zero()), exp, f, |n1, n2| n1 + n2)
+            calculate_collective_operator(types::Value::Number(N::zero()), exp, f, N::checked_add)
         }
         types::Function::Product => {
-            calculate_collective_product_operator(types::Value::Blank, exp, f, |n1, n2| n1 * n2)
+            calculate_collective_product_operator(types::Value::Blank, exp, f, N::checked_mul)
         }
         types::Function::Average => {
-            calculate_average(types::Value::Number(N::zero()), exp, f, |n1, n2| n1 + n2)
+            calculate_average(types::Value::Number(N::zero()), exp, f, N::checked_add)
         }
         types::Function::Or => calculate_boolean(exp, f, |n1, n2| n1 || n2, true),
         types::Function::And => calculate_boolean(exp, f, |n1, n2| n1 && n2, false),
         types::Function::Xor => calculate_boolean(exp, f, |n1, n2| n1 ^ n2, true),
         types::Function::Not => calculate_negation(get_unary_function_arg(exp, f)),
         types::Function::Negate => calculate_negate(get_unary_function_arg(exp, f)),
+        types::Function::ImplicitIntersection => first_element(get_unary_function_arg(exp, f)),
         types::Function::Days => {
             let (end, start) = get_binary_function_args(exp, f);
             calculate_days(start, end)
@@ -143,9 +175,357 @@ where
         types::Function::Search => calculate_search(get_find_args(exp, f)),
         types::Function::IsError => calculate_iserror(get_unary_function_arg(exp, f)),
         types::Function::Blank => types::Value::Blank,
+        types::Function::Power => {
+            let (base, exponent) = get_binary_function_args(exp, f);
+            calculate_binary_operator(types::Operator::Power, base, exponent)
+        }
+        types::Function::Sqrt => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), N::checked_sqrt)
+        }
+        types::Function::Ln => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), N::checked_ln)
+        }
+        types::Function::Text => {
+            let (value, format_text) = get_binary_function_args(exp, f);
+            calculate_text(value, format_text)
+        }
+        types::Function::Value => calculate_value(get_unary_function_arg(exp, f)),
+        types::Function::NumberValue => calculate_numbervalue(get_function_args(exp, f)),
+        types::Function::Round => calculate_round(exp, f, Rounding::HalfAwayFromZero),
+        types::Function::RoundUp => calculate_round(exp, f, Rounding::AwayFromZero),
+        types::Function::RoundDown | types::Function::Trunc => {
+            calculate_round(exp, f, Rounding::TowardZero)
+        }
+        types::Function::MRound => {
+            let (number, multiple) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(number, multiple, &mround)
+        }
+        types::Function::Ceiling => {
+            let (number, significance) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(number, significance, &ceiling)
+        }
+        types::Function::Floor => {
+            let (number, significance) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(number, significance, &floor)
+        }
+        types::Function::CeilingMath => calculate_rounding_math(exp, f, ceiling_math),
+        types::Function::FloorMath => calculate_rounding_math(exp, f, floor_math),
+        types::Function::Int => calculate_numeric_function(get_unary_function_arg(exp, f), int),
+        types::Function::Mod => {
+            let (number, divisor) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(number, divisor, &modulo)
+        }
+        types::Function::Quotient => {
+            let (number, divisor) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(number, divisor, &quotient)
+        }
+        types::Function::Log => {
+            if exp.values.len() > 2 {
+                return types::Value::Error(Error::Argument);
+            }
+            let mut args = get_function_args(exp, f).into_iter();
+            let number = args.next().unwrap_or(types::Value::Error(Error::Argument));
+            let base = args
+                .next()
+                .unwrap_or(types::Value::Number(N::from_i32(10).unwrap()));
+            calculate_binary_numeric_function(number, base, &math::log)
+        }
+        types::Function::Pi => number_result(math::pi()),
+        types::Function::Atan2 => {
+            let (x, y) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(x, y, &math::atan2)
+        }
+        types::Function::Exp => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::exp)
+        }
+        types::Function::Log10 => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::log10)
+        }
+        types::Function::Sign => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::sign)
+        }
+        types::Function::Sin => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::sin)
+        }
+        types::Function::Cos => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::cos)
+        }
+        types::Function::Tan => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::tan)
+        }
+        types::Function::Asin => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::asin)
+        }
+        types::Function::Acos => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::acos)
+        }
+        types::Function::Atan => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::atan)
+        }
+        types::Function::Degrees => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::degrees)
+        }
+        types::Function::Radians => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::radians)
+        }
+        types::Function::Sinh => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::sinh)
+        }
+        types::Function::Cosh => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::cosh)
+        }
+        types::Function::Tanh => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::tanh)
+        }
+        types::Function::Asinh => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::asinh)
+        }
+        types::Function::Acosh => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::acosh)
+        }
+        types::Function::Atanh => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), math::atanh)
+        }
+        types::Function::Min => {
+            calculate_aggregate(get_list_function_args(exp, f), statistics::min)
+        }
+        types::Function::Max => {
+            calculate_aggregate(get_list_function_args(exp, f), statistics::max)
+        }
+        types::Function::Count => calculate_count(get_list_function_args(exp, f)),
+        types::Function::CountA => calculate_counta(get_list_function_args(exp, f)),
+        types::Function::CountBlank => calculate_countblank(get_list_function_args(exp, f)),
+        types::Function::Median => {
+            calculate_aggregate(get_list_function_args(exp, f), statistics::median)
+        }
+        types::Function::Mode | types::Function::ModeSngl => {
+            calculate_aggregate(get_list_function_args(exp, f), statistics::mode)
+        }
+        types::Function::Stdev | types::Function::StdevS => {
+            calculate_aggregate(get_list_function_args(exp, f), statistics::stdev_s)
+        }
+        types::Function::StdevP => {
+            calculate_aggregate(get_list_function_args(exp, f), statistics::stdev_p)
+        }
+        types::Function::Var | types::Function::VarS => {
+            calculate_aggregate(get_list_function_args(exp, f), statistics::var_s)
+        }
+        types::Function::VarP => {
+            calculate_aggregate(get_list_function_args(exp, f), statistics::var_p)
+        }
+        types::Function::Large => calculate_large_small(get_function_args(exp, f), true),
+        types::Function::Small => calculate_large_small(get_function_args(exp, f), false),
+        types::Function::Rank | types::Function::RankEq => {
+            calculate_rank(get_function_args(exp, f), false)
+        }
+        types::Function::RankAvg => calculate_rank(get_function_args(exp, f), true),
+        types::Function::Percentile | types::Function::PercentileInc => {
+            calculate_percentile(get_function_args(exp, f), true)
+        }
+        types::Function::PercentileExc => calculate_percentile(get_function_args(exp, f), false),
+        types::Function::Quartile | types::Function::QuartileInc => {
+            calculate_quartile(get_function_args(exp, f), true)
+        }
+        types::Function::QuartileExc => calculate_quartile(get_function_args(exp, f), false),
+        types::Function::CountIf => calculate_countif(get_function_args(exp, f)),
+        types::Function::CountIfs => calculate_countifs(get_function_args(exp, f)),
+        types::Function::SumIf => {
+            calculate_aggregate_if(get_function_args(exp, f), statistics::sum_numbers)
+        }
+        types::Function::SumIfs => {
+            calculate_aggregate_ifs(get_function_args(exp, f), statistics::sum_numbers)
+        }
+        types::Function::AverageIf => {
+            calculate_aggregate_if(get_function_args(exp, f), statistics::average)
+        }
+        types::Function::AverageIfs => {
+            calculate_aggregate_ifs(get_function_args(exp, f), statistics::average)
+        }
+        types::Function::MaxIfs => {
+            calculate_aggregate_ifs(get_function_args(exp, f), statistics::max)
+        }
+        types::Function::MinIfs => {
+            calculate_aggregate_ifs(get_function_args(exp, f), statistics::min)
+        }
+        types::Function::SumProduct => calculate_sumproduct(get_function_args(exp, f)),
+        types::Function::MMult => {
+            let (lhs, rhs) = get_binary_function_args(exp, f);
+            calculate_mmult(lhs, rhs)
+        }
+        types::Function::MInverse => calculate_minverse(get_unary_function_arg(exp, f)),
+        types::Function::MDeterm => calculate_mdeterm(get_unary_function_arg(exp, f)),
+        types::Function::Transpose => calculate_transpose(get_unary_function_arg(exp, f)),
+        types::Function::Pmt => {
+            calculate_float_function(get_function_args(exp, f), 3, &[0.0, 0.0], financial::pmt)
+        }
+        types::Function::Ipmt => {
+            calculate_float_function(get_function_args(exp, f), 4, &[0.0, 0.0], financial::ipmt)
+        }
+        types::Function::Ppmt => {
+            calculate_float_function(get_function_args(exp, f), 4, &[0.0, 0.0], financial::ppmt)
+        }
+        types::Function::Pv => {
+            calculate_float_function(get_function_args(exp, f), 3, &[0.0, 0.0], financial::pv)
+        }
+        types::Function::Fv => {
+            calculate_float_function(get_function_args(exp, f), 3, &[0.0, 0.0], financial::fv)
+        }
+        types::Function::Nper => {
+            calculate_float_function(get_function_args(exp, f), 3, &[0.0, 0.0], financial::nper)
+        }
+        types::Function::Rate => calculate_float_function(
+            get_function_args(exp, f),
+            3,
+            &[0.0, 0.0, 0.1],
+            financial::rate,
+        ),
+        types::Function::Npv => calculate_npv(get_function_args(exp, f)),
+        types::Function::Irr => calculate_irr(get_function_args(exp, f)),
+        types::Function::Xnpv => calculate_xnpv(get_function_args(exp, f)),
+        types::Function::Xirr => calculate_xirr(get_function_args(exp, f)),
+        types::Function::Rand | types::Function::RandBetween | types::Function::RandArray => {
+            let args = get_function_args(exp, f);
+            with_thread_random(|random| calculate_random_function(func, args, random))
+        }
+        types::Function::Dec2Bin => calculate_dec2base(get_function_args(exp, f), 2),
+        types::Function::Dec2Hex => calculate_dec2base(get_function_args(exp, f), 16),
+        types::Function::Dec2Oct => calculate_dec2base(get_function_args(exp, f), 8),
+        types::Function::Bin2Dec => calculate_base2dec(get_unary_function_arg(exp, f), 2),
+        types::Function::Hex2Dec => calculate_base2dec(get_unary_function_arg(exp, f), 16),
+        types::Function::Oct2Dec => calculate_base2dec(get_unary_function_arg(exp, f), 8),
+        types::Function::Base => calculate_base(get_function_args(exp, f)),
+        types::Function::Decimal => {
+            let (text, radix) = get_binary_function_args(exp, f);
+            calculate_decimal(text, radix)
+        }
+        types::Function::BitAnd => {
+            let (lhs, rhs) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(lhs, rhs, &engineering::bitand)
+        }
+        types::Function::BitOr => {
+            let (lhs, rhs) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(lhs, rhs, &engineering::bitor)
+        }
+        types::Function::BitXor => {
+            let (lhs, rhs) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(lhs, rhs, &engineering::bitxor)
+        }
+        types::Function::BitLShift => {
+            let (lhs, rhs) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(lhs, rhs, &engineering::bitlshift)
+        }
+        types::Function::BitRShift => {
+            let (lhs, rhs) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(lhs, rhs, &engineering::bitrshift)
+        }
+        types::Function::Fact => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), combinatorics::fact)
+        }
+        types::Function::FactDouble => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), combinatorics::factdouble)
+        }
+        types::Function::Combin => {
+            let (number, chosen) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(number, chosen, &combinatorics::combin)
+        }
+        types::Function::CombinA => {
+            let (number, chosen) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(number, chosen, &combinatorics::combina)
+        }
+        types::Function::Permut => {
+            let (number, chosen) = get_binary_function_args(exp, f);
+            calculate_binary_numeric_function(number, chosen, &combinatorics::permut)
+        }
+        types::Function::Gcd => calculate_aggregate(get_function_args(exp, f), combinatorics::gcd),
+        types::Function::Lcm => calculate_aggregate(get_function_args(exp, f), combinatorics::lcm),
+        types::Function::Multinomial => {
+            calculate_aggregate(get_function_args(exp, f), combinatorics::multinomial)
+        }
+        types::Function::NormDist => {
+            calculate_float_function(get_function_args(exp, f), 4, &[], distribution::norm_dist)
+        }
+        types::Function::NormInv => {
+            calculate_float_function(get_function_args(exp, f), 3, &[], distribution::norm_inv)
+        }
+        types::Function::NormSDist => {
+            calculate_float_function(get_function_args(exp, f), 2, &[], distribution::norm_s_dist)
+        }
+        types::Function::TDist => {
+            calculate_float_function(get_function_args(exp, f), 3, &[], distribution::t_dist)
+        }
+        types::Function::TInv => {
+            calculate_float_function(get_function_args(exp, f), 2, &[], distribution::t_inv)
+        }
+        types::Function::ChisqDist => {
+            calculate_float_function(get_function_args(exp, f), 3, &[], distribution::chisq_dist)
+        }
+        types::Function::BinomDist => {
+            calculate_float_function(get_function_args(exp, f), 4, &[], distribution::binom_dist)
+        }
+        types::Function::PoissonDist => calculate_float_function(
+            get_function_args(exp, f),
+            3,
+            &[],
+            distribution::poisson_dist,
+        ),
+        types::Function::ExponDist => {
+            calculate_float_function(get_function_args(exp, f), 3, &[], distribution::expon_dist)
+        }
+        types::Function::Gamma => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), distribution::gamma_function)
+        }
+        types::Function::GammaLn => {
+            calculate_numeric_function(get_unary_function_arg(exp, f), distribution::gammaln)
+        }
+        types::Function::ConfidenceNorm => calculate_float_function(
+            get_function_args(exp, f),
+            3,
+            &[],
+            distribution::confidence_norm,
+        ),
     }
 }
 
+/// ROUND, ROUNDUP, ROUNDDOWN and TRUNC, whose number of decimal places is optional for TRUNC.
+fn calculate_round<N>(
+    exp: types::Expression<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    rounding: Rounding,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let mut args = get_function_args(exp, f).into_iter();
+    let number = args.next().unwrap_or(types::Value::Error(Error::Argument));
+    let places = args.next().unwrap_or(types::Value::Number(N::zero()));
+    calculate_binary_numeric_function(number, places, &|number, places| {
+        round(number, places, rounding)
+    })
+}
+
+/// CEILING.MATH and FLOOR.MATH, with the optional significance 1 and mode 0.
+fn calculate_rounding_math<N>(
+    exp: types::Expression<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    round_math: fn(N, N, N) -> Result<N, Error>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let mut args = get_function_args(exp, f).into_iter();
+    let number = args.next().unwrap_or(types::Value::Error(Error::Argument));
+    let significance = args.next().unwrap_or(types::Value::Number(N::one()));
+    let mode = match number_argument(args.next().unwrap_or(types::Value::Blank)) {
+        Ok(mode) => mode,
+        Err(error) => return error,
+    };
+    calculate_binary_numeric_function(number, significance, &|number, significance| {
+        round_math(number, significance, mode)
+    })
+}
+
 fn calculate_find<N>(
     (find_text, within_text, start_num): (types::Value<N>, types::Value<N>, types::Value<N>),
 ) -> types::Value<N>
@@ -171,7 +551,7 @@ where
         CoerceForFind::Propagate(v) => return v,
     };
     let start_i64: i64 = match &start_num {
-        types::Value::Number(n) => n.as_(),
+        types::Value::Number(n) => n.as_i64(),
         _ => return types::Value::Error(Error::Value),
     };
     if start_i64 <= 0 {
@@ -215,7 +595,7 @@ where
         CoerceForFind::Propagate(v) => return v,
     };
     let start_i64: i64 = match &start_num {
-        types::Value::Number(n) => n.as_(),
+        types::Value::Number(n) => n.as_i64(),
         _ => return types::Value::Error(Error::Value),
     };
     if start_i64 <= 0 {
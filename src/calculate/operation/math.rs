@@ -0,0 +1,265 @@
+use super::{array::broadcast_all, number::number_argument};
+use crate::types::{self, XlNum};
+
+/// Converts the result of a float function back to a number. NaN and infinity, e.g. of an
+/// argument outside of the domain of the function, are `None`.
+pub fn from_float<N>(result: f64) -> Option<N>
+where
+    N: XlNum,
+{
+    if result.is_finite() {
+        N::from_f64(result).filter(|result| !result.is_infinite())
+    } else {
+        None
+    }
+}
+
+/// Converts a number to a float, #NUM! if it does not fit.
+pub fn to_float<N>(number: N) -> Result<f64, types::Error>
+where
+    N: XlNum,
+{
+    number.to_f64().ok_or(types::Error::Num)
+}
+
+/// The number of a result calculated with floats, see `calculate_float_function`.
+pub fn float_result<N>(result: Result<f64, types::Error>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    match result.and_then(|result| from_float(result).ok_or(types::Error::Num)) {
+        Ok(number) => types::Value::Number(number),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+/// The error of an argument that is not a number.
+pub fn error_of<N>(value: types::Value<N>) -> types::Error
+where
+    N: XlNum,
+{
+    match value {
+        types::Value::Error(error) => error,
+        _ => types::Error::Value,
+    }
+}
+
+/// Applies a function of floats to its arguments, with the defaults of the optional arguments
+/// after the required ones, element by element if an argument is an array. Like
+/// transcendental functions, financial and statistical functions are calculated with 64 bit
+/// floats for all number types. Results that are not finite are #NUM!.
+pub fn calculate_float_function<N>(
+    args: Vec<types::Value<N>>,
+    required: usize,
+    defaults: &[f64],
+    f: fn(&[f64]) -> Result<f64, types::Error>,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if args.len() < required || args.len() > required + defaults.len() {
+        return types::Value::Error(types::Error::Argument);
+    }
+    let args = match broadcast_all(args, |args| {
+        calculate_float_function(args, required, defaults, f)
+    }) {
+        Ok(value) => return value,
+        Err(args) => args,
+    };
+    let provided = args.len();
+    let mut floats = Vec::with_capacity(required + defaults.len());
+    for value in args {
+        match number_argument(value).map_err(error_of).and_then(to_float) {
+            Ok(float) => floats.push(float),
+            Err(error) => return types::Value::Error(error),
+        }
+    }
+    floats.extend_from_slice(&defaults[provided - required..]);
+    float_result(f(&floats))
+}
+
+/// Applies a function of a float to a number. Transcendental functions are calculated with
+/// 64 bit floats for all number types.
+fn via_float<N>(number: N, f: fn(f64) -> f64) -> Option<N>
+where
+    N: XlNum,
+{
+    from_float(f(number.to_f64()?))
+}
+
+pub fn pi<N>() -> Option<N>
+where
+    N: XlNum,
+{
+    from_float(std::f64::consts::PI)
+}
+
+/// ABS: the absolute value.
+pub fn abs<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    Some(number.abs())
+}
+
+/// SIGN: 1 for positive numbers, -1 for negative numbers and 0 for zero.
+pub fn sign<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    if number.is_zero() {
+        Some(N::zero())
+    } else {
+        Some(number.signum())
+    }
+}
+
+/// EXP: an overflow is #NUM!.
+pub fn exp<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    number.checked_exp().filter(|result| !result.is_infinite())
+}
+
+/// LOG: the logarithm to a base. Numbers and bases that are not positive are #NUM!, and the
+/// base 1 #DIV/0!.
+pub fn log<N>(number: N, base: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if !number.is_positive() || !base.is_positive() {
+        return Err(types::Error::Num);
+    }
+    if base.is_one() {
+        return Err(types::Error::Div0);
+    }
+    let number = number.to_f64().ok_or(types::Error::Num)?;
+    let base = base.to_f64().ok_or(types::Error::Num)?;
+    let result = if base == 10.0 {
+        number.log10()
+    } else {
+        number.ln() / base.ln()
+    };
+    from_float(result).ok_or(types::Error::Num)
+}
+
+pub fn log10<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::log10)
+}
+
+pub fn sin<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::sin)
+}
+
+pub fn cos<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::cos)
+}
+
+pub fn tan<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::tan)
+}
+
+pub fn asin<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::asin)
+}
+
+pub fn acos<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::acos)
+}
+
+pub fn atan<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::atan)
+}
+
+/// ATAN2: the angle of the point (x, y). Note that Excel takes x first, unlike most
+/// programming languages. The origin is #DIV/0!.
+pub fn atan2<N>(x: N, y: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if x.is_zero() && y.is_zero() {
+        return Err(types::Error::Div0);
+    }
+    let x = x.to_f64().ok_or(types::Error::Num)?;
+    let y = y.to_f64().ok_or(types::Error::Num)?;
+    from_float(y.atan2(x)).ok_or(types::Error::Num)
+}
+
+pub fn degrees<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::to_degrees)
+}
+
+pub fn radians<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::to_radians)
+}
+
+pub fn sinh<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::sinh)
+}
+
+pub fn cosh<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::cosh)
+}
+
+pub fn tanh<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::tanh)
+}
+
+pub fn asinh<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::asinh)
+}
+
+pub fn acosh<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::acosh)
+}
+
+/// ATANH: -1 and 1 are #NUM!.
+pub fn atanh<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    via_float(number, f64::atanh)
+}
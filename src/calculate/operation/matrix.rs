@@ -0,0 +1,284 @@
+use super::array::{array_from_rows, array_rows};
+use crate::types::{self, XlNum};
+
+type Matrix<N> = Vec<Vec<N>>;
+
+/// The number of rows and columns of an array, or #VALUE! if its rows differ in length.
+fn dimensions<N>(rows: &[Vec<types::Value<N>>]) -> Result<(usize, usize), types::Error>
+where
+    N: XlNum,
+{
+    let width = rows.first().map_or(0, Vec::len);
+    if width == 0 || rows.iter().any(|row| row.len() != width) {
+        return Err(types::Error::Value);
+    }
+    Ok((rows.len(), width))
+}
+
+/// Converts an array to a matrix of numbers. Elements that are not numbers are #VALUE!, and
+/// errors are returned.
+fn numeric_matrix<N>(value: types::Value<N>) -> Result<Matrix<N>, types::Error>
+where
+    N: XlNum,
+{
+    let rows = array_rows(value);
+    dimensions(&rows)?;
+    rows.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|value| match value {
+                    types::Value::Number(number) => Ok(number),
+                    types::Value::Error(error) => Err(error),
+                    _ => Err(types::Error::Value),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn square_matrix<N>(value: types::Value<N>) -> Result<Matrix<N>, types::Error>
+where
+    N: XlNum,
+{
+    let matrix = numeric_matrix(value)?;
+    if matrix.iter().any(|row| row.len() != matrix.len()) {
+        return Err(types::Error::Value);
+    }
+    Ok(matrix)
+}
+
+fn matrix_value<N>(matrix: Result<Matrix<N>, types::Error>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    match matrix {
+        Ok(matrix) => array_from_rows(
+            matrix
+                .into_iter()
+                .map(|row| row.into_iter().map(types::Value::Number).collect())
+                .collect(),
+        ),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+fn add<N>(lhs: N, rhs: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    lhs.checked_add(rhs).ok_or(types::Error::Num)
+}
+
+fn multiply<N>(lhs: N, rhs: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    lhs.checked_mul(rhs).ok_or(types::Error::Num)
+}
+
+/// Subtracts a multiple of a row from another one, starting at the given column.
+fn subtract_row<N>(
+    row: &mut [N],
+    pivot_row: &[N],
+    factor: N,
+    start: usize,
+) -> Result<(), types::Error>
+where
+    N: XlNum,
+{
+    for (element, pivot_element) in row.iter_mut().zip(pivot_row).skip(start) {
+        *element = element
+            .checked_sub(multiply(factor, *pivot_element)?)
+            .ok_or(types::Error::Num)?;
+    }
+    Ok(())
+}
+
+/// Swaps the row with the largest absolute value in the column into the pivot position, and
+/// returns whether the rows were swapped. #NUM! if all values are zero, i.e. the matrix is
+/// singular.
+fn pivot<N>(matrix: &mut [Vec<N>], column: usize) -> Result<bool, types::Error>
+where
+    N: XlNum,
+{
+    let (row, value) = matrix
+        .iter()
+        .enumerate()
+        .skip(column)
+        .map(|(row, values)| (row, values[column].abs()))
+        .fold((column, N::zero()), |(max_row, max), (row, value)| {
+            if value > max {
+                (row, value)
+            } else {
+                (max_row, max)
+            }
+        });
+    if value.is_zero() {
+        return Err(types::Error::Num);
+    }
+    matrix.swap(row, column);
+    Ok(row != column)
+}
+
+/// SUMPRODUCT: the sum of the products of the corresponding elements of arrays of the same
+/// size. Elements that are not numbers count as 0.
+pub fn calculate_sumproduct<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let sumproduct = || {
+        let arrays = args.into_iter().map(array_rows).collect::<Vec<_>>();
+        let size = dimensions(arrays.first().ok_or(types::Error::Argument)?)?;
+        let mut products = Vec::new();
+        for rows in &arrays {
+            if dimensions(rows)? != size {
+                return Err(types::Error::Value);
+            }
+            let elements = rows.iter().flatten().map(|value| match value {
+                types::Value::Number(number) => Ok(*number),
+                types::Value::Error(error) => Err(*error),
+                _ => Ok(N::zero()),
+            });
+            if products.is_empty() {
+                products = elements.collect::<Result<_, _>>()?;
+            } else {
+                for (product, element) in products.iter_mut().zip(elements) {
+                    *product = multiply(*product, element?)?;
+                }
+            }
+        }
+        products.into_iter().try_fold(N::zero(), add)
+    };
+    match sumproduct() {
+        Ok(number) => types::Value::Number(number),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+/// MMULT: the matrix product. The number of columns of the first array must equal the number
+/// of rows of the second one.
+pub fn calculate_mmult<N>(lhs: types::Value<N>, rhs: types::Value<N>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let product = || {
+        let (lhs, rhs) = (numeric_matrix(lhs)?, numeric_matrix(rhs)?);
+        if lhs[0].len() != rhs.len() {
+            return Err(types::Error::Value);
+        }
+        lhs.iter()
+            .map(|row| {
+                (0..rhs[0].len())
+                    .map(|column| {
+                        row.iter()
+                            .zip(&rhs)
+                            .try_fold(N::zero(), |sum, (element, rhs_row)| {
+                                add(sum, multiply(*element, rhs_row[column])?)
+                            })
+                    })
+                    .collect()
+            })
+            .collect()
+    };
+    matrix_value(product())
+}
+
+/// MDETERM: the determinant of a square matrix, by Gaussian elimination.
+pub fn calculate_mdeterm<N>(value: types::Value<N>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let determinant = || {
+        let mut matrix = square_matrix(value)?;
+        let mut determinant = N::one();
+        for column in 0..matrix.len() {
+            match pivot(&mut matrix, column) {
+                Ok(true) => determinant = -determinant,
+                Ok(false) => (),
+                Err(_) => return Ok(N::zero()),
+            }
+            let (upper, lower) = matrix.split_at_mut(column + 1);
+            let pivot_row = &upper[column];
+            determinant = multiply(determinant, pivot_row[column])?;
+            for row in lower {
+                let factor = row[column]
+                    .checked_div(pivot_row[column])
+                    .ok_or(types::Error::Num)?;
+                subtract_row(row, pivot_row, factor, column)?;
+            }
+        }
+        Ok(determinant)
+    };
+    match determinant() {
+        Ok(number) => types::Value::Number(number),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+/// MINVERSE: the inverse of a square matrix, by Gauss-Jordan elimination. Singular matrices
+/// are #NUM!.
+pub fn calculate_minverse<N>(value: types::Value<N>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let inverse = || {
+        let matrix = square_matrix(value)?;
+        let size = matrix.len();
+        // The matrix augmented with the identity matrix, which turns into the inverse.
+        let mut augmented: Matrix<N> = matrix
+            .into_iter()
+            .enumerate()
+            .map(|(row, mut values)| {
+                values.extend((0..size).map(
+                    |column| {
+                        if row == column {
+                            N::one()
+                        } else {
+                            N::zero()
+                        }
+                    },
+                ));
+                values
+            })
+            .collect();
+        for column in 0..size {
+            pivot(&mut augmented, column)?;
+            let divisor = augmented[column][column];
+            for element in augmented[column].iter_mut() {
+                *element = element.checked_div(divisor).ok_or(types::Error::Num)?;
+            }
+            let pivot_row = augmented[column].clone();
+            for (row, values) in augmented.iter_mut().enumerate() {
+                if row != column {
+                    let factor = values[column];
+                    subtract_row(values, &pivot_row, factor, 0)?;
+                }
+            }
+        }
+        Ok(augmented
+            .into_iter()
+            .map(|values| values[size..].to_vec())
+            .collect())
+    };
+    matrix_value(inverse())
+}
+
+/// TRANSPOSE: swaps the rows and columns of an array.
+pub fn calculate_transpose<N>(value: types::Value<N>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let rows = array_rows(value);
+    let (height, width) = match dimensions(&rows) {
+        Ok(size) => size,
+        Err(error) => return types::Value::Error(error),
+    };
+    let mut columns: Vec<Vec<types::Value<N>>> =
+        (0..width).map(|_| Vec::with_capacity(height)).collect();
+    for row in rows {
+        for (column, value) in columns.iter_mut().zip(row) {
+            column.push(value);
+        }
+    }
+    array_from_rows(columns)
+}
@@ -1,14 +1,27 @@
+pub mod array;
 pub mod boolean;
+pub mod coerce;
+pub mod combinatorics;
+pub mod criteria;
 pub mod date;
+pub mod distribution;
+pub mod engineering;
+pub mod financial;
 pub mod function;
+pub mod math;
+pub mod matrix;
 pub mod number;
+pub mod random;
+pub mod round;
+pub mod statistics;
 pub mod string;
 
 use self::{
+    array::broadcast,
     boolean::calculate_comparison_operator,
     date::{add_days_to_date, compare_dates, subtract_days_from_date},
     function::calculate_function,
-    number::{calculate_divide_operator, calculate_numeric_operator, calculate_power_operator},
+    number::calculate_numeric_operator,
     string::{calculate_concat_operator, calculate_string_operator, compare_strings},
 };
 use super::args::get_binary_function_args;
@@ -24,101 +37,92 @@ where
     <N as FromStr>::Err: Debug,
 {
     match exp.op {
-        types::Operator::Plus => {
+        types::Operator::Function(func) => calculate_function(func, exp, f),
+        op => {
             let (lhs, rhs) = get_binary_function_args(exp, f);
-            match lhs {
-                types::Value::Date(d) => add_days_to_date(d, rhs),
-                _ => calculate_numeric_operator(lhs, rhs, |n1, n2| n1 + n2),
-            }
+            calculate_binary_operator(op, lhs, rhs)
         }
+    }
+}
 
-        types::Operator::Minus => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            match lhs {
-                types::Value::Date(d) => subtract_days_from_date(d, rhs),
-                _ => calculate_numeric_operator(lhs, rhs, |n1, n2| n1 - n2),
-            }
-        }
+/// Applies an operator that is not a function to its evaluated operands, element by element
+/// if one of them is an array.
+pub fn calculate_binary_operator<N>(
+    op: types::Operator,
+    lhs: types::Value<N>,
+    rhs: types::Value<N>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let (lhs, rhs) = match broadcast(lhs, rhs, |l, r| calculate_binary_operator(op, l, r)) {
+        Ok(value) => return value,
+        Err(operands) => operands,
+    };
+    match op {
+        types::Operator::Plus => match lhs {
+            types::Value::Date(d) => add_days_to_date(d, rhs),
+            _ => calculate_numeric_operator(lhs, rhs, N::checked_add),
+        },
 
-        types::Operator::Multiply => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            calculate_numeric_operator(lhs, rhs, |n1, n2| n1 * n2)
-        }
-        types::Operator::Divide => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            match rhs {
-                types::Value::Number(n) if n.is_zero() => types::Value::Error(types::Error::Div0),
-                _ => calculate_numeric_operator(lhs, rhs, calculate_divide_operator),
+        types::Operator::Minus => match lhs {
+            types::Value::Date(d) => subtract_days_from_date(d, rhs),
+            _ => calculate_numeric_operator(lhs, rhs, N::checked_sub),
+        },
+
+        types::Operator::Multiply => calculate_numeric_operator(lhs, rhs, N::checked_mul),
+        types::Operator::Divide => match rhs {
+            types::Value::Number(n) if n.is_zero() => types::Value::Error(types::Error::Div0),
+            _ => calculate_numeric_operator(lhs, rhs, N::checked_div),
+        },
+        types::Operator::Power => match (&lhs, &rhs) {
+            (types::Value::Number(base), types::Value::Number(exponent))
+                if base.is_zero() && exponent.is_negative() =>
+            {
+                types::Value::Error(types::Error::Div0)
             }
-        }
-        types::Operator::Power => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            calculate_numeric_operator(lhs, rhs, calculate_power_operator)
-        }
-        types::Operator::Concat => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            calculate_string_operator(lhs, rhs, calculate_concat_operator)
-        }
-        types::Operator::Equal => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            match (lhs.clone(), rhs.clone()) {
-                (types::Value::Date(l), types::Value::Date(r)) => {
-                    compare_dates(l, r, |d1, d2| d1 == d2)
-                }
-                (types::Value::Text(l), types::Value::Text(r)) => {
-                    compare_strings(l, r, |s1, s2| s1 == s2)
-                }
-                _ => calculate_comparison_operator(lhs, rhs, |n1, n2| (n1 - n2).abs().is_zero()),
+            _ => calculate_numeric_operator(lhs, rhs, N::checked_pow),
+        },
+        types::Operator::Concat => calculate_string_operator(lhs, rhs, calculate_concat_operator),
+        types::Operator::Equal => match (lhs.clone(), rhs.clone()) {
+            (types::Value::Date(l), types::Value::Date(r)) => {
+                compare_dates(l, r, |d1, d2| d1 == d2)
             }
-        }
-        types::Operator::NotEqual => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            match (lhs.clone(), rhs.clone()) {
-                (types::Value::Date(l), types::Value::Date(r)) => {
-                    compare_dates(l, r, |d1, d2| d1 != d2)
-                }
-                (types::Value::Text(l), types::Value::Text(r)) => {
-                    compare_strings(l, r, |s1, s2| s1 != s2)
-                }
-                _ => calculate_comparison_operator(lhs, rhs, |n1, n2| (n1 - n2).abs() > N::zero()),
+            (types::Value::Text(l), types::Value::Text(r)) => {
+                compare_strings(l, r, |s1, s2| s1 == s2)
             }
-        }
-        types::Operator::Greater => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            match (lhs.clone(), rhs.clone()) {
-                (types::Value::Date(l), types::Value::Date(r)) => {
-                    compare_dates(l, r, |d1, d2| d1 > d2)
-                }
-                _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 > n2),
+            _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 == n2),
+        },
+        types::Operator::NotEqual => match (lhs.clone(), rhs.clone()) {
+            (types::Value::Date(l), types::Value::Date(r)) => {
+                compare_dates(l, r, |d1, d2| d1 != d2)
             }
-        }
-        types::Operator::Less => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            match (lhs.clone(), rhs.clone()) {
-                (types::Value::Date(l), types::Value::Date(r)) => {
-                    compare_dates(l, r, |d1, d2| d1 < d2)
-                }
-                _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 < n2),
+            (types::Value::Text(l), types::Value::Text(r)) => {
+                compare_strings(l, r, |s1, s2| s1 != s2)
             }
-        }
-        types::Operator::GreaterOrEqual => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            match (lhs.clone(), rhs.clone()) {
-                (types::Value::Date(l), types::Value::Date(r)) => {
-                    compare_dates(l, r, |d1, d2| d1 >= d2)
-                }
-                _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 >= n2),
+            _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 != n2),
+        },
+        types::Operator::Greater => match (lhs.clone(), rhs.clone()) {
+            (types::Value::Date(l), types::Value::Date(r)) => compare_dates(l, r, |d1, d2| d1 > d2),
+            _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 > n2),
+        },
+        types::Operator::Less => match (lhs.clone(), rhs.clone()) {
+            (types::Value::Date(l), types::Value::Date(r)) => compare_dates(l, r, |d1, d2| d1 < d2),
+            _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 < n2),
+        },
+        types::Operator::GreaterOrEqual => match (lhs.clone(), rhs.clone()) {
+            (types::Value::Date(l), types::Value::Date(r)) => {
+                compare_dates(l, r, |d1, d2| d1 >= d2)
             }
-        }
-        types::Operator::LessOrEqual => {
-            let (lhs, rhs) = get_binary_function_args(exp, f);
-            match (lhs.clone(), rhs.clone()) {
-                (types::Value::Date(l), types::Value::Date(r)) => {
-                    compare_dates(l, r, |d1, d2| d1 <= d2)
-                }
-                _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 <= n2),
+            _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 >= n2),
+        },
+        types::Operator::LessOrEqual => match (lhs.clone(), rhs.clone()) {
+            (types::Value::Date(l), types::Value::Date(r)) => {
+                compare_dates(l, r, |d1, d2| d1 <= d2)
             }
-        }
-        types::Operator::Function(func) => calculate_function(func, exp, f),
+            _ => calculate_comparison_operator(lhs, rhs, |n1, n2| n1 <= n2),
+        },
+        types::Operator::Function(_) => types::Value::Error(types::Error::Argument),
     }
 }
@@ -1,59 +1,43 @@
+use super::{array::broadcast, coerce::text_to_number};
 use crate::{
     calculate::calculate_formula,
     types::{self, XlNum},
 };
 use std::{fmt::Debug, str::FromStr};
 
-pub fn calculate_divide_operator<N>(num1: N, num2: N) -> N
+/// Returns the number, or #NUM! if the operation has no result in the number type.
+pub fn number_result<N>(number: Option<N>) -> types::Value<N>
 where
     N: XlNum,
-    <N as FromStr>::Err: Debug,
-{
-    num1 / num2
-}
-
-fn is_float_int<N>(num: N) -> bool
-where
-    N: XlNum,
-    <N as FromStr>::Err: Debug,
 {
-    num.fract().is_zero()
-}
-
-pub fn calculate_power_operator<N>(num1: N, num2: N) -> N
-where
-    N: XlNum,
-    <N as FromStr>::Err: Debug,
-{
-    if is_float_int(num2) {
-        num1.powi(num2.as_())
-    } else {
-        num1.powf(num2)
+    match number {
+        Some(number) => types::Value::Number(number),
+        None => types::Value::Error(types::Error::Num),
     }
 }
 
 fn calculate_numeric_operator_rhs_text<N>(
     t: String,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
     <N as FromStr>::Err: Debug,
 {
-    match t.parse::<N>() {
-        Ok(nl) => match rhs {
+    match text_to_number(&t) {
+        Some(nl) => match rhs {
             types::Value::Boolean(_) | types::Value::Error(_) => rhs,
-            types::Value::Text(t) => match t.parse::<N>() {
-                Ok(nr) => types::Value::Number(f(nl, nr)),
-                Err(_) => types::Value::Error(types::Error::Cast),
+            types::Value::Text(t) => match text_to_number(&t) {
+                Some(nr) => number_result(f(nl, nr)),
+                None => types::Value::Error(types::Error::Cast),
             },
-            types::Value::Number(r) => types::Value::Number(f(nl, r)),
+            types::Value::Number(r) => number_result(f(nl, r)),
             types::Value::Iterator(_) | types::Value::Date(_) | types::Value::Blank => {
                 types::Value::Error(types::Error::Value)
             }
         },
-        Err(_) => types::Value::Error(types::Error::Cast),
+        None => types::Value::Error(types::Error::Cast),
     }
 }
 
@@ -61,7 +45,7 @@ fn calculate_numeric_operator_rhs_number<N>(
     l: N,
     lhs: types::Value<N>,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -69,11 +53,11 @@ where
 {
     match rhs {
         types::Value::Boolean(_) | types::Value::Error(_) => rhs,
-        types::Value::Text(t) => match t.parse::<N>() {
-            Ok(nr) => types::Value::Number(f(l, nr)),
-            Err(_) => types::Value::Error(types::Error::Cast),
+        types::Value::Text(t) => match text_to_number(&t) {
+            Some(nr) => number_result(f(l, nr)),
+            None => types::Value::Error(types::Error::Cast),
         },
-        types::Value::Number(r) => types::Value::Number(f(l, r)),
+        types::Value::Number(r) => number_result(f(l, r)),
         types::Value::Iterator(mut value_vec) => {
             if let Some(mut temp) = value_vec.pop() {
                 while let Some(top) = value_vec.pop() {
@@ -85,7 +69,7 @@ where
             }
         }
         types::Value::Date(_) => types::Value::Error(types::Error::Value),
-        types::Value::Blank => types::Value::Number(f(l, N::zero())),
+        types::Value::Blank => number_result(f(l, N::zero())),
     }
 }
 
@@ -93,7 +77,7 @@ fn calculate_numeric_operator_product_rhs_number<N>(
     l: N,
     lhs: types::Value<N>,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -101,11 +85,11 @@ where
 {
     match rhs {
         types::Value::Boolean(_) | types::Value::Error(_) => rhs,
-        types::Value::Text(t) => match t.parse::<N>() {
-            Ok(nr) => types::Value::Number(f(l, nr)),
-            Err(_) => types::Value::Error(types::Error::Cast),
+        types::Value::Text(t) => match text_to_number(&t) {
+            Some(nr) => number_result(f(l, nr)),
+            None => types::Value::Error(types::Error::Cast),
         },
-        types::Value::Number(r) => types::Value::Number(f(l, r)),
+        types::Value::Number(r) => number_result(f(l, r)),
         types::Value::Iterator(mut value_vec) => {
             if let Some(mut temp) = value_vec.pop() {
                 while let Some(top) = value_vec.pop() {
@@ -127,7 +111,7 @@ where
 fn calculate_numeric_operator_rhs_iterator<N>(
     mut lhs_vec: Vec<types::Value<N>>,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -166,7 +150,7 @@ where
 pub fn calculate_numeric_operator<N>(
     lhs: types::Value<N>,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -186,7 +170,7 @@ where
 fn calculate_numeric_product_operator<N>(
     lhs: types::Value<N>,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -208,7 +192,7 @@ fn calculate_average_operator_rhs_number<N>(
     l: N,
     lhs: types::Value<N>,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -216,11 +200,11 @@ where
 {
     match rhs {
         types::Value::Boolean(_) | types::Value::Error(_) => rhs,
-        types::Value::Text(t) => match t.parse::<N>() {
-            Ok(nr) => types::Value::Number(f(l, nr)),
-            Err(_) => types::Value::Error(types::Error::Cast),
+        types::Value::Text(t) => match text_to_number(&t) {
+            Some(nr) => number_result(f(l, nr)),
+            None => types::Value::Error(types::Error::Cast),
         },
-        types::Value::Number(r) => types::Value::Number(f(l, r)),
+        types::Value::Number(r) => number_result(f(l, r)),
         types::Value::Iterator(mut value_vec) => {
             if let Some(mut temp) = value_vec.pop() {
                 if temp == types::Value::Blank {
@@ -241,7 +225,7 @@ where
         types::Value::Date(_) => types::Value::Error(types::Error::Value),
         types::Value::Blank => {
             *element_count -= 1;
-            types::Value::Number(f(l, N::zero()))
+            number_result(f(l, N::zero()))
         }
     }
 }
@@ -250,7 +234,7 @@ fn calculate_average_operator_rhs_iterator<N>(
     element_count: &mut i64,
     mut lhs_vec: Vec<types::Value<N>>,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -276,7 +260,7 @@ fn calculate_average_operator<N>(
     element_count: &mut i64,
     lhs: types::Value<N>,
     rhs: types::Value<N>,
-    f: fn(N, N) -> N,
+    f: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -299,18 +283,70 @@ where
     }
 }
 
-pub fn calculate_abs<N>(value: types::Value<N>) -> types::Value<N>
+/// Converts an argument of a numeric function to a number. Text is converted like in
+/// arithmetic, booleans count as 1 and 0 and blanks as 0. Errors are returned as is, and
+/// other values as #VALUE!.
+pub fn number_argument<N>(value: types::Value<N>) -> Result<N, types::Value<N>>
 where
     N: XlNum,
-    <N as FromStr>::Err: Debug,
 {
     match value {
-        types::Value::Boolean(_) | types::Value::Error(_) | types::Value::Text(_) => value,
-        types::Value::Number(l) => types::Value::Number(l.abs()),
+        types::Value::Number(number) => Ok(number),
+        types::Value::Text(t) => text_to_number(&t).ok_or(types::Value::Error(types::Error::Value)),
+        types::Value::Boolean(boolean) => Ok(if boolean.into() { N::one() } else { N::zero() }),
+        types::Value::Blank => Ok(N::zero()),
+        types::Value::Error(_) => Err(value),
         types::Value::Iterator(_) | types::Value::Date(_) => {
-            types::Value::Error(types::Error::Value)
+            Err(types::Value::Error(types::Error::Value))
         }
-        types::Value::Blank => types::Value::Number(N::zero()),
+    }
+}
+
+/// Applies a function of one number, to every element of an array. Text is converted to a
+/// number and booleans count as 1 and 0.
+pub fn calculate_numeric_function<N>(
+    value: types::Value<N>,
+    f: fn(N) -> Option<N>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    match value {
+        types::Value::Iterator(value_vec) => types::Value::Iterator(
+            value_vec
+                .into_iter()
+                .map(|value| calculate_numeric_function(value, f))
+                .collect(),
+        ),
+        value => match number_argument(value) {
+            Ok(number) => number_result(f(number)),
+            Err(error) => error,
+        },
+    }
+}
+
+/// Applies a function of two numbers, element by element if one of the arguments is an
+/// array. The arguments are converted like those of `calculate_numeric_function`.
+pub fn calculate_binary_numeric_function<N>(
+    lhs: types::Value<N>,
+    rhs: types::Value<N>,
+    f: &impl Fn(N, N) -> Result<N, types::Error>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let (lhs, rhs) = match broadcast(lhs, rhs, |l, r| calculate_binary_numeric_function(l, r, f)) {
+        Ok(value) => return value,
+        Err(operands) => operands,
+    };
+    match (number_argument(lhs), number_argument(rhs)) {
+        (Ok(l), Ok(r)) => match f(l, r) {
+            Ok(number) => types::Value::Number(number),
+            Err(error) => types::Value::Error(error),
+        },
+        (Err(error), _) | (_, Err(error)) => error,
     }
 }
 
@@ -318,7 +354,7 @@ pub fn calculate_average<N>(
     mut collective_value: types::Value<N>,
     mut exp: types::Expression<N>,
     f: Option<&impl Fn(String) -> types::Value<N>>,
-    f_collective: fn(N, N) -> N,
+    f_collective: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -340,7 +376,7 @@ where
         calculate_numeric_operator(
             collective_value,
             types::Value::Number(N::from_i64(element_count).unwrap()),
-            calculate_divide_operator,
+            N::checked_div,
         )
     }
 }
@@ -349,7 +385,7 @@ pub fn calculate_collective_operator<N>(
     mut collective_value: types::Value<N>,
     mut exp: types::Expression<N>,
     f: Option<&impl Fn(String) -> types::Value<N>>,
-    f_collective: fn(N, N) -> N,
+    f_collective: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -366,7 +402,7 @@ pub fn calculate_collective_product_operator<N>(
     mut collective_value: types::Value<N>,
     mut exp: types::Expression<N>,
     f: Option<&impl Fn(String) -> types::Value<N>>,
-    f_collective: fn(N, N) -> N,
+    f_collective: fn(N, N) -> Option<N>,
 ) -> types::Value<N>
 where
     N: XlNum,
@@ -0,0 +1,188 @@
+use super::{
+    array::array_from_rows,
+    math::from_float,
+    number::number_argument,
+    round::{round_to_places, Rounding},
+};
+use crate::{
+    calculate::RandomSource,
+    types::{self, XlNum},
+};
+
+/// The rows and columns of an Excel worksheet, which bound the size of RANDARRAY.
+const MAX_ROWS: usize = 1_048_576;
+const MAX_COLUMNS: usize = 16_384;
+/// The number of elements RANDARRAY creates at most, the default `max_array_elements` of
+/// `Limits`. Larger arrays are #LIMIT! instead of running out of memory.
+const MAX_ELEMENTS: usize = 1_048_576;
+
+/// A random number that is at least 0 and less than 1. It is truncated to the significant
+/// digits of the number type, so that it is not rounded up to 1.
+fn random_fraction<N>(random: &mut impl RandomSource) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let scale = 10f64.powi(N::SIGNIFICANT_DIGITS as i32);
+    from_float((random.next_f64() * scale).floor() / scale).ok_or(types::Error::Num)
+}
+
+/// A random whole number between the bounds, both included.
+fn random_whole_number<N>(
+    bottom: N,
+    top: N,
+    random: &mut impl RandomSource,
+) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let range = top
+        .checked_sub(bottom)
+        .and_then(|range| range.checked_add(N::one()))
+        .and_then(|range| range.to_f64())
+        .ok_or(types::Error::Num)?;
+    let offset: N = from_float((random.next_f64() * range).floor()).ok_or(types::Error::Num)?;
+    bottom.checked_add(offset).ok_or(types::Error::Num)
+}
+
+/// RAND: a random number that is at least 0 and less than 1.
+fn calculate_rand<N>(args: Vec<types::Value<N>>, random: &mut impl RandomSource) -> types::Value<N>
+where
+    N: XlNum,
+{
+    if !args.is_empty() {
+        return types::Value::Error(types::Error::Argument);
+    }
+    match random_fraction(random) {
+        Ok(number) => types::Value::Number(number),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+/// RANDBETWEEN(bottom, top): a random whole number between the bounds, which are rounded
+/// towards each other. #NUM! if the bottom is greater than the top.
+fn calculate_randbetween<N>(
+    args: Vec<types::Value<N>>,
+    random: &mut impl RandomSource,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let [bottom, top] = match <[types::Value<N>; 2]>::try_from(args) {
+        Ok(args) => args,
+        Err(_) => return types::Value::Error(types::Error::Argument),
+    };
+    let (bottom, top) = match (number_argument(bottom), number_argument(top)) {
+        (Ok(bottom), Ok(top)) => (bottom, top),
+        (Err(error), _) | (_, Err(error)) => return error,
+    };
+    let mut randbetween = || {
+        let bottom = round_to_places(bottom, 0, Rounding::Up)?;
+        let top = round_to_places(top, 0, Rounding::Down)?;
+        if bottom > top {
+            return Err(types::Error::Num);
+        }
+        random_whole_number(bottom, top, random)
+    };
+    match randbetween() {
+        Ok(number) => types::Value::Number(number),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+/// An optional argument of RANDARRAY, which may be blank.
+fn optional_number<N>(value: Option<types::Value<N>>, default: N) -> Result<N, types::Value<N>>
+where
+    N: XlNum,
+{
+    match value {
+        None | Some(types::Value::Blank) => Ok(default),
+        Some(value) => number_argument(value),
+    }
+}
+
+/// The rows, columns, minimum, maximum and whether the numbers are whole of the RANDARRAY
+/// arguments. Less than one row or column, more than a worksheet has and a minimum greater than
+/// the maximum are #VALUE!, and so are bounds that are not whole numbers for whole random
+/// numbers. More than `MAX_ELEMENTS` elements are #LIMIT!.
+fn randarray_arguments<N>(
+    args: Vec<types::Value<N>>,
+) -> Result<(usize, usize, N, N, bool), types::Value<N>>
+where
+    N: XlNum,
+{
+    if args.len() > 5 {
+        return Err(types::Value::Error(types::Error::Argument));
+    }
+    let mut args = args.into_iter();
+    let mut next = |default| optional_number(args.next(), default);
+    let size = |number: N| number.trunc().to_usize().filter(|size| *size > 0);
+    let rows = size(next(N::one())?).filter(|rows| *rows <= MAX_ROWS);
+    let columns = size(next(N::one())?).filter(|columns| *columns <= MAX_COLUMNS);
+    let (min, max) = (next(N::zero())?, next(N::one())?);
+    let whole = !next(N::zero())?.is_zero();
+    match (rows, columns) {
+        (Some(rows), Some(columns))
+            if min <= max && (!whole || (min.trunc() == min && max.trunc() == max)) =>
+        {
+            match rows.checked_mul(columns) {
+                Some(elements) if elements <= MAX_ELEMENTS => Ok((rows, columns, min, max, whole)),
+                _ => Err(types::Value::Error(types::Error::Limit)),
+            }
+        }
+        _ => Err(types::Value::Error(types::Error::Value)),
+    }
+}
+
+/// RANDARRAY([rows], [columns], [min], [max], [whole_number]): an array of random numbers
+/// between the minimum and the maximum, by default a single number like RAND.
+fn calculate_randarray<N>(
+    args: Vec<types::Value<N>>,
+    random: &mut impl RandomSource,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let (rows, columns, min, max, whole) = match randarray_arguments(args) {
+        Ok(arguments) => arguments,
+        Err(error) => return error,
+    };
+    let mut element = || {
+        if whole {
+            random_whole_number(min, max, random)
+        } else {
+            let fraction: N = random_fraction(random)?;
+            max.checked_sub(min)
+                .and_then(|range| range.checked_mul(fraction))
+                .and_then(|offset| min.checked_add(offset))
+                .ok_or(types::Error::Num)
+        }
+    };
+    let mut array = Vec::with_capacity(rows);
+    for _ in 0..rows {
+        let mut row = Vec::with_capacity(columns);
+        for _ in 0..columns {
+            match element() {
+                Ok(number) => row.push(types::Value::Number(number)),
+                Err(error) => return types::Value::Error(error),
+            }
+        }
+        array.push(row);
+    }
+    array_from_rows(array)
+}
+
+/// Calculates RAND, RANDBETWEEN or RANDARRAY with the random source.
+pub fn calculate_random_function<N>(
+    func: types::Function,
+    args: Vec<types::Value<N>>,
+    random: &mut impl RandomSource,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    match func {
+        types::Function::RandBetween => calculate_randbetween(args, random),
+        types::Function::RandArray => calculate_randarray(args, random),
+        _ => calculate_rand(args, random),
+    }
+}
@@ -0,0 +1,223 @@
+use super::coerce::number_from_digits;
+use crate::{
+    format::{decimal_digits, round_digits},
+    types::{self, XlNum},
+};
+
+/// Rounding modes of the rounding functions.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Rounding {
+    HalfAwayFromZero,
+    AwayFromZero,
+    TowardZero,
+    /// Toward positive infinity.
+    Up,
+    /// Toward negative infinity.
+    Down,
+}
+
+/// More decimal places than any number type has.
+const MAX_PLACES: i64 = 1000;
+
+/// Adds one to the last digit, carrying over nines.
+fn increment(mut digits: Vec<u8>, exponent: i32) -> (Vec<u8>, i32) {
+    match digits.iter().rposition(|digit| *digit != 9) {
+        Some(position) => {
+            digits[position] += 1;
+            digits.truncate(position + 1);
+            (digits, exponent)
+        }
+        None => (vec![1], exponent + 1),
+    }
+}
+
+/// Rounds a number to the given number of decimal places, or to tens, hundreds, etc. for
+/// negative places. Like Excel, the decimal digits of the number are rounded after it is
+/// rounded to its significant digits, so that 2.675 rounds to 2.68 although the float is
+/// slightly smaller than 2.675.
+pub fn round_to_places<N>(number: N, places: i64, rounding: Rounding) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if number.is_infinite() {
+        return Err(types::Error::Div0);
+    }
+    let (negative, digits, exponent) =
+        decimal_digits(&number.to_string()).ok_or(types::Error::Num)?;
+    let (digits, exponent) = round_digits(digits, exponent, N::SIGNIFICANT_DIGITS);
+    let rounding = match (rounding, negative) {
+        (Rounding::Up, false) | (Rounding::Down, true) => Rounding::AwayFromZero,
+        (Rounding::Up, true) | (Rounding::Down, false) => Rounding::TowardZero,
+        (rounding, _) => rounding,
+    };
+    let places = places.clamp(-MAX_PLACES, MAX_PLACES) as i32;
+    // The number of digits before the rounded position.
+    let keep = exponent + places + 1;
+    let (digits, exponent) = if keep >= digits.len() as i32 {
+        (digits, exponent)
+    } else {
+        let round_up = match rounding {
+            Rounding::HalfAwayFromZero => keep >= 0 && digits[keep as usize] >= 5,
+            Rounding::AwayFromZero => true,
+            _ => false,
+        };
+        match (keep > 0, round_up) {
+            (true, true) => increment(digits[..keep as usize].to_vec(), exponent),
+            (true, false) => (digits[..keep as usize].to_vec(), exponent),
+            (false, true) => (vec![1], -places),
+            (false, false) => (Vec::new(), 0),
+        }
+    };
+    if digits.is_empty() {
+        return Ok(N::zero());
+    }
+    let digits: String = digits.iter().map(|digit| (b'0' + digit) as char).collect();
+    let exponent = exponent - (digits.len() as i32 - 1);
+    number_from_digits(negative, &digits, "", exponent).ok_or(types::Error::Num)
+}
+
+/// ROUND, ROUNDUP, ROUNDDOWN and TRUNC.
+pub fn round<N>(number: N, places: N, rounding: Rounding) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    round_to_places(number, places.as_i64(), rounding)
+}
+
+fn divide<N>(number: N, divisor: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    number.checked_div(divisor).ok_or(types::Error::Num)
+}
+
+fn multiply<N>(number: N, factor: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    number.checked_mul(factor).ok_or(types::Error::Num)
+}
+
+/// Rounds a number to a multiple of the significance.
+fn round_to_multiple<N>(number: N, significance: N, rounding: Rounding) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let multiples = round_to_places(divide(number, significance)?, 0, rounding)?;
+    multiply(multiples, significance)
+}
+
+/// MROUND: rounds half away from zero to a multiple. A number and a multiple with different
+/// signs are #NUM!.
+pub fn mround<N>(number: N, multiple: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if multiple.is_zero() || number.is_zero() {
+        Ok(N::zero())
+    } else if number.is_negative() != multiple.is_negative() {
+        Err(types::Error::Num)
+    } else {
+        round_to_multiple(number, multiple, Rounding::HalfAwayFromZero)
+    }
+}
+
+/// CEILING: rounds up to a multiple of the significance, or away from zero if both are
+/// negative. A positive number with a negative significance is #NUM!.
+pub fn ceiling<N>(number: N, significance: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if significance.is_zero() {
+        Ok(N::zero())
+    } else if number.is_positive() && significance.is_negative() {
+        Err(types::Error::Num)
+    } else {
+        round_to_multiple(number, significance, Rounding::Up)
+    }
+}
+
+/// FLOOR: rounds down to a multiple of the significance, or toward zero if both are
+/// negative. A positive number with a negative significance is #NUM!, and a significance of
+/// zero #DIV/0!.
+pub fn floor<N>(number: N, significance: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if number.is_zero() {
+        Ok(N::zero())
+    } else if significance.is_zero() {
+        Err(types::Error::Div0)
+    } else if number.is_positive() && significance.is_negative() {
+        Err(types::Error::Num)
+    } else {
+        round_to_multiple(number, significance, Rounding::Down)
+    }
+}
+
+/// CEILING.MATH: rounds up to a multiple of the absolute significance. Negative numbers are
+/// rounded away from zero if the mode is not zero.
+pub fn ceiling_math<N>(number: N, significance: N, mode: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if significance.is_zero() {
+        return Ok(N::zero());
+    }
+    let rounding = if number.is_negative() && !mode.is_zero() {
+        Rounding::AwayFromZero
+    } else {
+        Rounding::Up
+    };
+    round_to_multiple(number, significance.abs(), rounding)
+}
+
+/// FLOOR.MATH: rounds down to a multiple of the absolute significance. Negative numbers are
+/// rounded toward zero if the mode is not zero.
+pub fn floor_math<N>(number: N, significance: N, mode: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if significance.is_zero() {
+        return Ok(N::zero());
+    }
+    let rounding = if number.is_negative() && !mode.is_zero() {
+        Rounding::TowardZero
+    } else {
+        Rounding::Down
+    };
+    round_to_multiple(number, significance.abs(), rounding)
+}
+
+/// INT: rounds down to an integer.
+pub fn int<N>(number: N) -> Option<N>
+where
+    N: XlNum,
+{
+    round_to_places(number, 0, Rounding::Down).ok()
+}
+
+/// MOD: the remainder of a division, with the sign of the divisor.
+pub fn modulo<N>(number: N, divisor: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if divisor.is_zero() {
+        return Err(types::Error::Div0);
+    }
+    let quotient = round_to_places(divide(number, divisor)?, 0, Rounding::Down)?;
+    number
+        .checked_sub(multiply(divisor, quotient)?)
+        .ok_or(types::Error::Num)
+}
+
+/// QUOTIENT: the integer part of a division.
+pub fn quotient<N>(number: N, divisor: N) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if divisor.is_zero() {
+        return Err(types::Error::Div0);
+    }
+    round_to_places(divide(number, divisor)?, 0, Rounding::TowardZero)
+}
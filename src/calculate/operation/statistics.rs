@@ -0,0 +1,513 @@
+use super::{
+    coerce::text_to_number,
+    math::error_of,
+    number::{number_argument, number_result},
+};
+use crate::{
+    format::date_to_serial,
+    types::{self, XlNum},
+};
+use chrono::{DateTime, FixedOffset};
+use std::cmp::Ordering;
+
+/// The serial number of a date, which aggregates count like a number.
+pub fn date_number<N>(date: DateTime<FixedOffset>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    N::from_f64(date_to_serial(date.naive_local())).ok_or(types::Error::Num)
+}
+
+/// Adds the numbers of a list to `numbers`, ignoring text, booleans and blanks. Returns the
+/// first error of the list.
+fn list_numbers<N>(
+    value_vec: Vec<types::Value<N>>,
+    numbers: &mut Vec<N>,
+) -> Result<(), types::Error>
+where
+    N: XlNum,
+{
+    for value in value_vec {
+        match value {
+            types::Value::Number(number) => numbers.push(number),
+            types::Value::Date(date) => numbers.push(date_number(date)?),
+            types::Value::Iterator(value_vec) => list_numbers(value_vec, numbers)?,
+            types::Value::Error(error) => return Err(error),
+            types::Value::Text(_) | types::Value::Boolean(_) | types::Value::Blank => (),
+        }
+    }
+    Ok(())
+}
+
+/// Collects the numbers of the arguments of an aggregate like Excel does. Lists, ranges and
+/// references only contribute their numbers and dates, while a single value is converted
+/// like the argument of a numeric function, so that `MAX("3",TRUE)` is 3 and `MAX("a")` is
+/// #VALUE!. References are passed as lists by `get_list_function_args`.
+/// Blank values are ignored, so that an empty cell does not count as 0. Errors are returned.
+pub fn collect_numbers<N>(args: Vec<types::Value<N>>) -> Result<Vec<N>, types::Error>
+where
+    N: XlNum,
+{
+    let mut numbers = Vec::new();
+    for value in args {
+        match value {
+            types::Value::Iterator(value_vec) => list_numbers(value_vec, &mut numbers)?,
+            types::Value::Date(date) => numbers.push(date_number(date)?),
+            types::Value::Blank => (),
+            value => numbers.push(number_argument(value).map_err(error_of)?),
+        }
+    }
+    Ok(numbers)
+}
+
+fn result_value<N>(result: Result<N, types::Error>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    match result {
+        Ok(number) => types::Value::Number(number),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
+/// Applies a function to the numbers of the arguments, see `collect_numbers`.
+pub fn calculate_aggregate<N>(
+    args: Vec<types::Value<N>>,
+    f: fn(Vec<N>) -> Result<N, types::Error>,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    result_value(collect_numbers(args).and_then(f))
+}
+
+/// Applies a function to every element of an array of numbers.
+fn with_number_argument<N>(
+    value: types::Value<N>,
+    f: &impl Fn(N) -> Result<N, types::Error>,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    match value {
+        types::Value::Iterator(value_vec) => types::Value::Iterator(
+            value_vec
+                .into_iter()
+                .map(|value| with_number_argument(value, f))
+                .collect(),
+        ),
+        value => match number_argument(value) {
+            Ok(number) => result_value(f(number)),
+            Err(error) => error,
+        },
+    }
+}
+
+fn from_count<N>(count: usize) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    N::from_usize(count).ok_or(types::Error::Num)
+}
+
+fn sum<N>(numbers: &[N]) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    numbers
+        .iter()
+        .try_fold(N::zero(), |sum, number| sum.checked_add(*number))
+        .ok_or(types::Error::Num)
+}
+
+fn sorted<N>(mut numbers: Vec<N>) -> Vec<N>
+where
+    N: XlNum,
+{
+    numbers.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    numbers
+}
+
+/// SUM of numbers.
+pub fn sum_numbers<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    sum(&numbers)
+}
+
+/// AVERAGE of numbers: #DIV/0! without numbers.
+pub fn average<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    if numbers.is_empty() {
+        return Err(types::Error::Div0);
+    }
+    sum(&numbers)?
+        .checked_div(from_count(numbers.len())?)
+        .ok_or(types::Error::Num)
+}
+
+/// MIN: 0 without numbers.
+pub fn min<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    Ok(numbers
+        .into_iter()
+        .reduce(|min, number| if number < min { number } else { min })
+        .unwrap_or_else(N::zero))
+}
+
+/// MAX: 0 without numbers.
+pub fn max<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    Ok(numbers
+        .into_iter()
+        .reduce(|max, number| if number > max { number } else { max })
+        .unwrap_or_else(N::zero))
+}
+
+/// MEDIAN: the mean of the two middle numbers for an even count. #NUM! without numbers.
+pub fn median<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let numbers = sorted(numbers);
+    let middle = numbers.len() / 2;
+    match numbers.len() {
+        0 => Err(types::Error::Num),
+        len if len % 2 == 1 => Ok(numbers[middle]),
+        _ => numbers[middle - 1]
+            .checked_add(numbers[middle])
+            .and_then(|sum| sum.checked_div(N::one() + N::one()))
+            .ok_or(types::Error::Num),
+    }
+}
+
+/// MODE: the most frequent number, the first one of the arguments if several are equally
+/// frequent. #N/A if no number repeats.
+pub fn mode<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let sorted = sorted(numbers.clone());
+    let frequency = |number: &N| {
+        sorted.partition_point(|other| other <= number)
+            - sorted.partition_point(|other| other < number)
+    };
+    let mut mode = None;
+    for number in numbers {
+        let count = frequency(&number);
+        if count > mode.map_or(1, |(_, max_count)| max_count) {
+            mode = Some((number, count));
+        }
+    }
+    mode.map(|(number, _)| number)
+        .ok_or(types::Error::NotAvailable)
+}
+
+/// The variance of a sample or of a whole population. #DIV/0! without enough numbers.
+fn variance<N>(numbers: Vec<N>, sample: bool) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let count = numbers.len();
+    if count == 0 || sample && count == 1 {
+        return Err(types::Error::Div0);
+    }
+    let mean = sum(&numbers)?
+        .checked_div(from_count(count)?)
+        .ok_or(types::Error::Num)?;
+    let squares = numbers
+        .iter()
+        .map(|number| {
+            let deviation = number.checked_sub(mean)?;
+            deviation.checked_mul(deviation)
+        })
+        .collect::<Option<Vec<N>>>()
+        .ok_or(types::Error::Num)?;
+    let degrees_of_freedom = if sample { count - 1 } else { count };
+    sum(&squares)?
+        .checked_div(from_count(degrees_of_freedom)?)
+        .ok_or(types::Error::Num)
+}
+
+/// VAR and VAR.S.
+pub fn var_s<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    variance(numbers, true)
+}
+
+pub fn var_p<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    variance(numbers, false)
+}
+
+/// STDEV and STDEV.S.
+pub fn stdev_s<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    variance(numbers, true)?
+        .checked_sqrt()
+        .ok_or(types::Error::Num)
+}
+
+pub fn stdev_p<N>(numbers: Vec<N>) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    variance(numbers, false)?
+        .checked_sqrt()
+        .ok_or(types::Error::Num)
+}
+
+/// Counts the values of the arguments. Lists are counted element by element with the first
+/// predicate, single values with the second one.
+fn count_values<N>(
+    args: Vec<types::Value<N>>,
+    in_list: fn(&types::Value<N>) -> bool,
+    single: fn(&types::Value<N>) -> bool,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    fn count_list<N>(value_vec: &[types::Value<N>], in_list: fn(&types::Value<N>) -> bool) -> usize
+    where
+        N: XlNum,
+    {
+        value_vec
+            .iter()
+            .map(|value| match value {
+                types::Value::Iterator(value_vec) => count_list(value_vec, in_list),
+                value => usize::from(in_list(value)),
+            })
+            .sum()
+    }
+    let count = args
+        .iter()
+        .map(|value| match value {
+            types::Value::Iterator(value_vec) => count_list(value_vec, in_list),
+            value => usize::from(single(value)),
+        })
+        .sum();
+    number_result(N::from_usize(count))
+}
+
+/// COUNT: counts the numbers and dates of lists and references, and single values that are
+/// numbers or can be converted to numbers. Errors are not counted.
+pub fn calculate_count<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    count_values(
+        args,
+        |value| matches!(value, types::Value::Number(_) | types::Value::Date(_)),
+        |value| match value {
+            types::Value::Number(_) | types::Value::Date(_) | types::Value::Boolean(_) => true,
+            types::Value::Text(text) => text_to_number::<N>(text).is_some(),
+            _ => false,
+        },
+    )
+}
+
+/// COUNTA: counts all values that are not blank, including errors.
+pub fn calculate_counta<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let is_not_blank = |value: &types::Value<N>| !matches!(value, types::Value::Blank);
+    count_values(args, is_not_blank, is_not_blank)
+}
+
+/// COUNTBLANK: counts blank values and empty text.
+pub fn calculate_countblank<N>(args: Vec<types::Value<N>>) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let is_blank = |value: &types::Value<N>| match value {
+        types::Value::Blank => true,
+        types::Value::Text(text) => text.is_empty(),
+        _ => false,
+    };
+    count_values(args, is_blank, is_blank)
+}
+
+/// Splits the arguments of a function into the sorted numbers of a list and one further
+/// argument, which is blank if it is optional and missing. Returns #ARG! for a wrong number of
+/// arguments.
+fn list_and_argument<N>(
+    args: Vec<types::Value<N>>,
+    optional: bool,
+) -> Result<(Vec<N>, types::Value<N>), types::Value<N>>
+where
+    N: XlNum,
+{
+    if args.is_empty() || args.len() == 1 && !optional || args.len() > 2 {
+        return Err(types::Value::Error(types::Error::Argument));
+    }
+    let mut args = args.into_iter();
+    let list = args.next().unwrap_or(types::Value::Blank);
+    let argument = args.next().unwrap_or(types::Value::Blank);
+    let numbers = collect_numbers(vec![list]).map_err(types::Value::Error)?;
+    Ok((sorted(numbers), argument))
+}
+
+/// Rounds the position argument of LARGE and SMALL up to an integer.
+fn position<N>(k: N) -> i64
+where
+    N: XlNum,
+{
+    let integer = k.trunc();
+    integer.as_i64() + i64::from(k > integer)
+}
+
+/// LARGE and SMALL: the k-th largest or smallest number of a list, for every k of an array.
+pub fn calculate_large_small<N>(args: Vec<types::Value<N>>, largest: bool) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let (numbers, k) = match list_and_argument(args, false) {
+        Ok(args) => args,
+        Err(error) => return error,
+    };
+    with_number_argument(k, &|k| {
+        let k = position(k);
+        if k < 1 || k as usize > numbers.len() {
+            return Err(types::Error::Num);
+        }
+        let index = if largest {
+            numbers.len() - k as usize
+        } else {
+            k as usize - 1
+        };
+        Ok(numbers[index])
+    })
+}
+
+/// RANK, RANK.EQ and RANK.AVG: the position of a number in a list, descending unless the
+/// order is not zero. Equal numbers have the same rank, or the average of their positions.
+/// #N/A if the list does not contain the number.
+pub fn calculate_rank<N>(args: Vec<types::Value<N>>, average: bool) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let mut args = args.into_iter();
+    let number = args.next();
+    let list_and_order = args.collect();
+    let (numbers, order) = match list_and_argument(list_and_order, true) {
+        Ok(args) => args,
+        Err(error) => return error,
+    };
+    let ascending = match number_argument(order) {
+        Ok(order) => !order.is_zero(),
+        Err(error) => return error,
+    };
+    let number = number.unwrap_or(types::Value::Error(types::Error::Argument));
+    with_number_argument(number, &|number| {
+        let before = numbers
+            .iter()
+            .filter(|other| {
+                if ascending {
+                    **other < number
+                } else {
+                    **other > number
+                }
+            })
+            .count();
+        let equal = numbers.iter().filter(|other| **other == number).count();
+        if equal == 0 {
+            return Err(types::Error::NotAvailable);
+        }
+        let rank = from_count::<N>(before + 1)?;
+        if average {
+            let ties = from_count::<N>(equal - 1)?;
+            ties.checked_div(N::one() + N::one())
+                .and_then(|half| rank.checked_add(half))
+                .ok_or(types::Error::Num)
+        } else {
+            Ok(rank)
+        }
+    })
+}
+
+/// The percentile of sorted numbers, interpolating between neighbouring numbers. Inclusive
+/// percentiles range from 0 to 1, exclusive ones from 1/(n+1) to n/(n+1).
+fn percentile_of<N>(numbers: &[N], k: N, inclusive: bool) -> Result<N, types::Error>
+where
+    N: XlNum,
+{
+    let count = numbers.len();
+    if count == 0 {
+        return Err(types::Error::Num);
+    }
+    let position = if inclusive {
+        if k.is_negative() || k > N::one() {
+            return Err(types::Error::Num);
+        }
+        k.checked_mul(from_count(count - 1)?)
+    } else {
+        if !k.is_positive() || k >= N::one() {
+            return Err(types::Error::Num);
+        }
+        k.checked_mul(from_count(count + 1)?)
+            .and_then(|position| position.checked_sub(N::one()))
+    }
+    .ok_or(types::Error::Num)?;
+    if position.is_negative() || position > from_count(count - 1)? {
+        return Err(types::Error::Num);
+    }
+    let index = position.trunc();
+    let fraction = position - index;
+    let lower = numbers[index.as_i64() as usize];
+    if fraction.is_zero() {
+        return Ok(lower);
+    }
+    let upper = numbers[index.as_i64() as usize + 1];
+    upper
+        .checked_sub(lower)
+        .and_then(|difference| difference.checked_mul(fraction))
+        .and_then(|difference| lower.checked_add(difference))
+        .ok_or(types::Error::Num)
+}
+
+/// PERCENTILE, PERCENTILE.INC and PERCENTILE.EXC, for every k of an array.
+pub fn calculate_percentile<N>(args: Vec<types::Value<N>>, inclusive: bool) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let (numbers, k) = match list_and_argument(args, false) {
+        Ok(args) => args,
+        Err(error) => return error,
+    };
+    with_number_argument(k, &|k| percentile_of(&numbers, k, inclusive))
+}
+
+/// QUARTILE, QUARTILE.INC and QUARTILE.EXC: the percentile of a quarter, from 0 for the
+/// minimum to 4 for the maximum. Exclusive quartiles range from 1 to 3.
+pub fn calculate_quartile<N>(args: Vec<types::Value<N>>, inclusive: bool) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let (numbers, quart) = match list_and_argument(args, false) {
+        Ok(args) => args,
+        Err(error) => return error,
+    };
+    with_number_argument(quart, &|quart| {
+        let quart = quart.trunc();
+        let range = if inclusive { 0..=4 } else { 1..=3 };
+        if !range.contains(&quart.as_i64()) {
+            return Err(types::Error::Num);
+        }
+        let k = quart.checked_div(from_count(4)?).ok_or(types::Error::Num)?;
+        percentile_of(&numbers, k, inclusive)
+    })
+}
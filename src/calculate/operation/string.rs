@@ -1,4 +1,9 @@
-use crate::types::{self, XlNum};
+use super::{array::broadcast, coerce::text_to_number};
+use crate::{
+    calculate::display::show_number,
+    format::format_value,
+    types::{self, XlNum},
+};
 use std::{fmt::Debug, str::FromStr};
 
 pub fn calculate_concat_operator(str1: &str, str2: &str) -> String {
@@ -16,7 +21,7 @@ where
 {
     match rhs {
         types::Value::Boolean(_) | types::Value::Error(_) => rhs,
-        types::Value::Number(r) => types::Value::Text(f(l, &r.to_string())),
+        types::Value::Number(r) => types::Value::Text(f(l, &show_number(r))),
         types::Value::Text(r) => types::Value::Text(f(l, &r)),
         types::Value::Iterator(_) | types::Value::Date(_) => {
             types::Value::Error(types::Error::Value)
@@ -36,7 +41,7 @@ where
 {
     match lhs {
         types::Value::Boolean(_) | types::Value::Error(_) => lhs,
-        types::Value::Number(l) => calculate_string_operation_rhs(&l.to_string(), rhs, f),
+        types::Value::Number(l) => calculate_string_operation_rhs(&show_number(l), rhs, f),
         types::Value::Text(l) => calculate_string_operation_rhs(&l, rhs, f),
         types::Value::Iterator(_) | types::Value::Date(_) => {
             types::Value::Error(types::Error::Value)
@@ -57,6 +62,40 @@ where
     types::Value::Boolean(f(string1, string2).into())
 }
 
+/// TEXT: formats a value with a format code, element by element for arrays. Text that
+/// converts to a number is formatted as the number.
+pub fn calculate_text<N>(value: types::Value<N>, format_text: types::Value<N>) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let (value, format_text) = match broadcast(value, format_text, calculate_text) {
+        Ok(result) => return result,
+        Err(operands) => operands,
+    };
+    let code = match format_text {
+        types::Value::Text(code) => code,
+        types::Value::Number(number) => show_number(number),
+        types::Value::Boolean(boolean) => boolean.to_string(),
+        types::Value::Blank => String::new(),
+        types::Value::Error(_) => return format_text,
+        types::Value::Date(_) | types::Value::Iterator(_) => {
+            return types::Value::Error(types::Error::Value)
+        }
+    };
+    let value = match value {
+        types::Value::Text(text) => match text_to_number(&text) {
+            Some(number) => types::Value::Number(number),
+            None => types::Value::Text(text),
+        },
+        value => value,
+    };
+    match format_value(&value, &code) {
+        Ok(text) => types::Value::Text(text),
+        Err(error) => types::Value::Error(error),
+    }
+}
+
 /// Result of coercing a formula value to string for FIND/SEARCH (Excel semantics).
 #[derive(Debug, Clone)]
 pub enum CoerceForFind<N>
@@ -77,7 +116,7 @@ where
 {
     match v {
         types::Value::Error(e) => CoerceForFind::Propagate(types::Value::Error(*e)),
-        types::Value::Number(n) => CoerceForFind::Coerced(n.to_string()),
+        types::Value::Number(n) => CoerceForFind::Coerced(show_number(*n)),
         types::Value::Text(s) => CoerceForFind::Coerced(s.clone()),
         types::Value::Boolean(b) => CoerceForFind::Coerced(b.to_string()),
         types::Value::Blank => CoerceForFind::Coerced(String::new()),
@@ -128,19 +167,12 @@ pub fn search_position_with_wildcards(
     let rest_lower: String = rest.to_lowercase();
     let pattern_orig: Vec<char> = find_text.chars().collect();
     let pattern_lower: Vec<char> = find_text.to_lowercase().chars().collect();
-    let rest_orig: Vec<char> = rest.chars().collect();
     let rest_chars: Vec<char> = rest_lower.chars().collect();
     let mut best: Option<usize> = None;
     for i in 0..=rest_chars.len() {
-        if let Some(offset) = match_pattern(
-            &pattern_lower,
-            &pattern_orig,
-            &rest_chars,
-            &rest_orig,
-            i,
-            0,
-            i,
-        ) {
+        if let Some(offset) =
+            match_pattern(&pattern_lower, &pattern_orig, &rest_chars, i, 0, i, false)
+        {
             let pos = start + offset + 1;
             best = Some(match best {
                 None => pos,
@@ -151,6 +183,15 @@ pub fn search_position_with_wildcards(
     best.map(|p| p as i64)
 }
 
+/// Whether a pattern with the wildcards ? and * and the escape ~ matches the whole text,
+/// ignoring case like `SEARCH`.
+pub fn matches_with_wildcards(pattern: &str, text: &str) -> bool {
+    let pattern_orig: Vec<char> = pattern.chars().collect();
+    let pattern_lower: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text_lower: Vec<char> = text.to_lowercase().chars().collect();
+    match_pattern(&pattern_lower, &pattern_orig, &text_lower, 0, 0, 0, true).is_some()
+}
+
 /// Match pattern (with ? * ~) against text from position. Returns Some(offset) where offset
 /// is the 0-based position in text where the matched substring starts (for leading *, this
 /// is where the remainder matches, not where * started).
@@ -158,13 +199,13 @@ fn match_pattern(
     pattern_lower: &[char],
     pattern_orig: &[char],
     text_lower: &[char],
-    text_orig: &[char],
     text_pos: usize,
     pattern_pos: usize,
     match_start: usize,
+    anchored: bool,
 ) -> Option<usize> {
     if pattern_pos >= pattern_lower.len() {
-        return Some(match_start);
+        return (!anchored || text_pos == text_lower.len()).then_some(match_start);
     }
     let mut p = pattern_pos;
     let mut t = text_pos;
@@ -175,7 +216,8 @@ fn match_pattern(
         if p + 1 < pattern_orig.len() && pattern_orig[p] == '~' {
             let next = pattern_orig[p + 1];
             if next == '?' || next == '*' || next == '~' {
-                if t >= text_orig.len() || text_orig[t] != next {
+                // The escaped characters have no case.
+                if t >= text_lower.len() || text_lower[t] != next {
                     return None;
                 }
                 p += 2;
@@ -199,10 +241,10 @@ fn match_pattern(
                         rest_pat_lower,
                         rest_pat_orig,
                         text_lower,
-                        text_orig,
                         t + skip,
                         0,
                         t + skip,
+                        anchored,
                     ) {
                         return Some(off);
                     }
@@ -218,5 +260,5 @@ fn match_pattern(
             }
         }
     }
-    Some(match_start)
+    (!anchored || t == text_lower.len()).then_some(match_start)
 }
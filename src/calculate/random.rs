@@ -0,0 +1,112 @@
+use super::{
+    reference::{resolve_reference_value, ReferenceValue},
+    walk::{walk, Visitor},
+};
+use crate::types::{self, XlNum};
+use std::{
+    cell::RefCell, collections::hash_map::RandomState, fmt::Debug, hash::BuildHasher, str::FromStr,
+};
+
+/// A source of random numbers for RAND, RANDBETWEEN and RANDARRAY.
+pub trait RandomSource {
+    /// Returns a random number that is at least 0 and less than 1.
+    fn next_f64(&mut self) -> f64;
+}
+
+impl<R> RandomSource for &mut R
+where
+    R: RandomSource + ?Sized,
+{
+    fn next_f64(&mut self) -> f64 {
+        (**self).next_f64()
+    }
+}
+
+/// The default random source, a SplitMix64 generator. The same seed always gives the same
+/// numbers, e.g. for reproducible simulations.
+#[derive(Debug, Clone)]
+pub struct DefaultRandom {
+    state: u64,
+}
+
+impl DefaultRandom {
+    pub fn with_seed(seed: u64) -> Self {
+        DefaultRandom { state: seed }
+    }
+}
+
+/// A generator with a different seed every time.
+impl Default for DefaultRandom {
+    fn default() -> Self {
+        DefaultRandom::with_seed(RandomState::new().hash_one(0))
+    }
+}
+
+impl RandomSource for DefaultRandom {
+    fn next_f64(&mut self) -> f64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        // The upper 53 bits, which a float represents exactly.
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+thread_local! {
+    static THREAD_RANDOM: RefCell<DefaultRandom> = RefCell::new(DefaultRandom::default());
+}
+
+/// Calls a function with the random source of evaluations without a random source, which is
+/// seeded differently for every thread.
+pub fn with_thread_random<T>(f: impl FnOnce(&mut DefaultRandom) -> T) -> T {
+    THREAD_RANDOM.with(|random| f(&mut random.borrow_mut()))
+}
+
+pub(super) fn is_random_function(func: types::Function) -> bool {
+    matches!(
+        func,
+        types::Function::Rand | types::Function::RandBetween | types::Function::RandArray
+    )
+}
+
+struct Randomizer<'a, F, R> {
+    f: Option<&'a F>,
+    random: &'a mut R,
+}
+
+impl<N, F, R> Visitor<N> for Randomizer<'_, F, R>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+    F: Fn(String) -> types::Value<N>,
+    R: RandomSource,
+{
+    fn reference(&mut self, name: String) -> types::Value<N> {
+        match self.f {
+            Some(f) => match resolve_reference_value(f(name)) {
+                ReferenceValue::Value(value) => value,
+                ReferenceValue::Formula(formula) => walk(formula, self),
+            },
+            None => types::Value::Error(types::Error::Reference),
+        }
+    }
+
+    fn random(&mut self) -> Option<&mut dyn RandomSource> {
+        Some(self.random)
+    }
+}
+
+pub fn calculate_randomized_formula<N>(
+    formula: types::Formula<N>,
+    f: Option<&impl Fn(String) -> types::Value<N>>,
+    random: &mut impl RandomSource,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let mut randomizer = Randomizer { f, random };
+    walk(formula, &mut randomizer)
+}
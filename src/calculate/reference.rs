@@ -1,12 +1,53 @@
-use super::calculate_formula;
+use super::{
+    calculate_formula,
+    random::RandomSource,
+    walk::{walk, Visitor},
+};
 use crate::{
     parse_formula,
     types::{self, XlNum},
 };
-use std::{fmt::Debug, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+    str::FromStr,
+};
 
 type NoCustomFunction<'a, N> = &'a fn(String, Vec<N>) -> types::Value<N>;
 
+/// A value returned by the reference closure, either final or a formula that still has
+/// to be evaluated.
+#[derive(Debug, Clone)]
+pub enum ReferenceValue<N>
+where
+    N: XlNum,
+{
+    Value(types::Value<N>),
+    Formula(types::Formula<N>),
+}
+
+pub fn resolve_reference_value<N>(value: types::Value<N>) -> ReferenceValue<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    match value {
+        types::Value::Number(x) => ReferenceValue::Value(types::Value::Number(x)),
+        types::Value::Text(s) => ReferenceValue::Formula(parse_formula::parse_string_to_formula(
+            &s,
+            None::<NoCustomFunction<N>>,
+        )),
+        types::Value::Boolean(x) => ReferenceValue::Value(types::Value::Boolean(x)),
+        types::Value::Error(types::Error::Value) => {
+            ReferenceValue::Value(types::Value::Error(types::Error::Value))
+        }
+        types::Value::Iterator(v) => ReferenceValue::Value(types::Value::Iterator(v)),
+        types::Value::Date(d) => ReferenceValue::Value(types::Value::Date(d)),
+        types::Value::Blank => ReferenceValue::Value(types::Value::Blank),
+        _ => ReferenceValue::Value(types::Value::Error(types::Error::Reference)),
+    }
+}
+
 pub fn calculate_reference<N>(
     string: String,
     f: Option<&impl Fn(String) -> types::Value<N>>,
@@ -16,19 +57,82 @@ where
     <N as FromStr>::Err: Debug,
 {
     match f {
-        Some(f) => match f(string) {
-            types::Value::Number(x) => types::Value::Number(x),
-            types::Value::Text(s) => calculate_formula(
-                parse_formula::parse_string_to_formula(&s, None::<NoCustomFunction<N>>),
-                Some(f),
-            ),
-            types::Value::Boolean(x) => types::Value::Boolean(x),
-            types::Value::Error(types::Error::Value) => types::Value::Error(types::Error::Value),
-            types::Value::Iterator(v) => types::Value::Iterator(v),
-            types::Value::Date(d) => types::Value::Date(d),
-            types::Value::Blank => types::Value::Blank,
-            _ => types::Value::Error(types::Error::Reference),
+        Some(f) => match resolve_reference_value(f(string)) {
+            ReferenceValue::Value(value) => value,
+            ReferenceValue::Formula(formula) => calculate_formula(formula, Some(f)),
         },
         None => types::Value::Error(types::Error::Reference),
     }
 }
+
+/// Takes evaluated values as they are and resolves every other reference with the closure.
+struct EvaluatedReferences<'a, N, F>
+where
+    N: XlNum,
+{
+    values: &'a BTreeMap<String, types::Value<N>>,
+    f: Option<&'a F>,
+    random: Option<&'a mut dyn RandomSource>,
+}
+
+impl<N, F> Visitor<N> for EvaluatedReferences<'_, N, F>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+    F: Fn(String) -> types::Value<N>,
+{
+    fn reference(&mut self, name: String) -> types::Value<N> {
+        if let Some(value) = self.values.get(&name) {
+            return value.clone();
+        }
+        match self.f {
+            Some(f) => match resolve_reference_value(f(name)) {
+                ReferenceValue::Value(value) => value,
+                ReferenceValue::Formula(formula) => walk(formula, self),
+            },
+            None => types::Value::Error(types::Error::Reference),
+        }
+    }
+
+    fn random(&mut self) -> Option<&mut dyn RandomSource> {
+        match &mut self.random {
+            Some(random) => Some(&mut **random),
+            None => None,
+        }
+    }
+}
+
+pub fn calculate_evaluated_formula<'a, N>(
+    formula: types::Formula<N>,
+    values: &'a BTreeMap<String, types::Value<N>>,
+    f: Option<&'a impl Fn(String) -> types::Value<N>>,
+    random: Option<&'a mut dyn RandomSource>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    walk(formula, &mut EvaluatedReferences { values, f, random })
+}
+
+pub fn collect_references<N>(formula: &types::Formula<N>, references: &mut BTreeSet<String>)
+where
+    N: XlNum,
+{
+    match formula {
+        types::Formula::Operation(exp) => {
+            for value in &exp.values {
+                collect_references(value, references);
+            }
+        }
+        types::Formula::Reference(name) => {
+            references.insert(name.clone());
+        }
+        types::Formula::Iterator(vec) => {
+            for value in vec {
+                collect_references(value, references);
+            }
+        }
+        types::Formula::Value(_) => (),
+    }
+}
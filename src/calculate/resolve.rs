@@ -0,0 +1,77 @@
+use super::{
+    cache::calculate_cached_reference_values,
+    reference::{collect_references, resolve_reference_value, ReferenceValue},
+    ReferenceCache,
+};
+use crate::types::{self, XlNum};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Debug,
+    future::Future,
+    str::FromStr,
+};
+
+/// Resolves references asynchronously, e.g. from a remote store. The values are interpreted
+/// like the values returned by the closure of `calculate_formula`: text is parsed as a formula.
+/// The future is `Send`, so that the evaluation can be spawned on a multi-threaded executor.
+pub trait AsyncResolver<N>
+where
+    N: XlNum,
+{
+    /// Returns the values of the given names in the same order. Names without a value
+    /// evaluate to #REF!.
+    fn resolve(&self, names: Vec<String>) -> impl Future<Output = Vec<types::Value<N>>> + Send;
+}
+
+/// Resolves the references of the formula and of all referenced formulas, one batch per
+/// level of indirection. Text values are parsed once here and kept as formulas.
+async fn resolve_all<N>(
+    formula: &types::Formula<N>,
+    resolver: &impl AsyncResolver<N>,
+) -> HashMap<String, ReferenceValue<N>>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let mut values = HashMap::new();
+    let mut names = BTreeSet::new();
+    collect_references(formula, &mut names);
+    while !names.is_empty() {
+        let batch: Vec<String> = names.into_iter().collect();
+        let mut resolved = resolver.resolve(batch.clone()).await.into_iter();
+        names = BTreeSet::new();
+        for name in batch {
+            let value = resolve_reference_value(
+                resolved
+                    .next()
+                    .unwrap_or(types::Value::Error(types::Error::Reference)),
+            );
+            if let ReferenceValue::Formula(formula) = &value {
+                collect_references(formula, &mut names);
+            }
+            values.insert(name, value);
+        }
+        names.retain(|name| !values.contains_key(name));
+    }
+    values
+}
+
+pub async fn calculate_resolved_formula<N>(
+    formula: types::Formula<N>,
+    resolver: &impl AsyncResolver<N>,
+) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let values = resolve_all(&formula, resolver).await;
+    let resolve = |name: String| {
+        values
+            .get(&name)
+            .cloned()
+            .unwrap_or(ReferenceValue::Value(types::Value::Error(
+                types::Error::Reference,
+            )))
+    };
+    calculate_cached_reference_values(formula, resolve, &mut ReferenceCache::new(), None)
+}
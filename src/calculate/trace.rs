@@ -0,0 +1,90 @@
+use super::{
+    display::show_formula,
+    random::RandomSource,
+    reference::{resolve_reference_value, ReferenceValue},
+    walk::{walk, Visitor},
+};
+use crate::types::{self, XlNum};
+use std::{fmt::Debug, str::FromStr};
+
+/// One evaluated sub-expression of a formula.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep<N>
+where
+    N: XlNum,
+{
+    /// Nesting of the sub-expression: 0 for the whole formula, 1 for its arguments and so on.
+    /// Formulas behind a reference are nested below the reference.
+    pub depth: usize,
+    /// The sub-expression as formula text, without the leading equal sign.
+    pub text: String,
+    pub value: types::Value<N>,
+}
+
+struct Tracer<'a, N, F>
+where
+    N: XlNum,
+{
+    f: Option<&'a F>,
+    random: Option<&'a mut dyn RandomSource>,
+    pending: Vec<String>,
+    steps: Vec<TraceStep<N>>,
+}
+
+impl<N, F> Visitor<N> for Tracer<'_, N, F>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+    F: Fn(String) -> types::Value<N>,
+{
+    fn reference(&mut self, name: String) -> types::Value<N> {
+        match self.f {
+            Some(f) => match resolve_reference_value(f(name)) {
+                ReferenceValue::Value(value) => value,
+                ReferenceValue::Formula(formula) => walk(formula, self),
+            },
+            None => types::Value::Error(types::Error::Reference),
+        }
+    }
+
+    fn enter(&mut self, formula: &types::Formula<N>) -> Option<types::Value<N>> {
+        self.pending.push(show_formula(formula));
+        None
+    }
+
+    fn leave(&mut self, value: types::Value<N>) -> types::Value<N> {
+        let text = self.pending.pop().unwrap_or_default();
+        self.steps.push(TraceStep {
+            depth: self.pending.len(),
+            text,
+            value: value.clone(),
+        });
+        value
+    }
+
+    fn random(&mut self) -> Option<&mut dyn RandomSource> {
+        match &mut self.random {
+            Some(random) => Some(&mut **random),
+            None => None,
+        }
+    }
+}
+
+pub fn calculate_traced_formula<'a, N>(
+    formula: types::Formula<N>,
+    f: Option<&'a impl Fn(String) -> types::Value<N>>,
+    random: Option<&'a mut dyn RandomSource>,
+) -> (types::Value<N>, Vec<TraceStep<N>>)
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let mut tracer = Tracer {
+        f,
+        random,
+        pending: Vec::new(),
+        steps: Vec::new(),
+    };
+    let value = walk(formula, &mut tracer);
+    (value, tracer.steps)
+}
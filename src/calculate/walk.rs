@@ -0,0 +1,78 @@
+use super::{
+    iterator::collect_iterator,
+    operation::{calculate_operation, random::calculate_random_function},
+    random::{is_random_function, RandomSource},
+};
+use crate::{
+    types::{self, XlNum},
+    NoReference,
+};
+use std::{fmt::Debug, str::FromStr};
+
+/// Hooks into an evaluation that computes the arguments of every operation before the
+/// operation itself. Since all operators and functions evaluate all of their arguments,
+/// the result is the same as the one of `calculate_formula`.
+pub trait Visitor<N>
+where
+    N: XlNum,
+{
+    /// Returns the evaluated value of a reference.
+    fn reference(&mut self, name: String) -> types::Value<N>;
+
+    /// Called before a formula is evaluated. Returning a value skips the evaluation.
+    fn enter(&mut self, _formula: &types::Formula<N>) -> Option<types::Value<N>> {
+        None
+    }
+
+    /// Called with the value of every formula passed to `enter`.
+    fn leave(&mut self, value: types::Value<N>) -> types::Value<N> {
+        value
+    }
+
+    /// The random source of RAND, RANDBETWEEN and RANDARRAY. Without one they use the
+    /// generator of the thread.
+    fn random(&mut self) -> Option<&mut dyn RandomSource> {
+        None
+    }
+}
+
+pub fn walk<N>(formula: types::Formula<N>, visitor: &mut impl Visitor<N>) -> types::Value<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let value = match visitor.enter(&formula) {
+        Some(value) => value,
+        None => match formula {
+            types::Formula::Operation(exp) => {
+                let values: Vec<types::Value<N>> = exp
+                    .values
+                    .into_iter()
+                    .map(|formula| walk(formula, visitor))
+                    .collect();
+                match (exp.op, visitor.random()) {
+                    (types::Operator::Function(func), Some(mut random))
+                        if is_random_function(func) =>
+                    {
+                        calculate_random_function(func, values, &mut random)
+                    }
+                    (op, _) => calculate_operation(
+                        types::Expression {
+                            op,
+                            values: values.into_iter().map(types::Formula::Value).collect(),
+                        },
+                        None::<NoReference<N>>,
+                    ),
+                }
+            }
+            types::Formula::Value(val) => val,
+            types::Formula::Reference(string) => visitor.reference(string),
+            types::Formula::Iterator(vec) => collect_iterator(
+                vec.into_iter()
+                    .map(|formula| walk(formula, visitor))
+                    .collect(),
+            ),
+        },
+    };
+    visitor.leave(value)
+}
@@ -0,0 +1,908 @@
+use crate::{
+    calculate::result_to_string,
+    types::{self, XlNum},
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+
+/// Splits the decimal text of a number into its sign, its significant digits without leading
+/// and trailing zeros and the exponent of the first digit, e.g. `-0.0125` into
+/// `(true, [1, 2, 5], -2)`. Zero has no digits.
+pub(crate) fn decimal_digits(text: &str) -> Option<(bool, Vec<u8>, i32)> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let digits: Vec<u8> = integer
+        .bytes()
+        .chain(fraction.bytes())
+        .map(|c| c.is_ascii_digit().then(|| c - b'0'))
+        .collect::<Option<_>>()?;
+    let Some(first) = digits.iter().position(|digit| *digit != 0) else {
+        return Some((negative, Vec::new(), 0));
+    };
+    let last = digits.iter().rposition(|digit| *digit != 0).unwrap();
+    let exponent = integer.len() as i32 - 1 - first as i32;
+    Some((negative, digits[first..=last].to_vec(), exponent))
+}
+
+/// Rounds the digits half away from zero to the given number of significant digits.
+pub(crate) fn round_digits(
+    mut digits: Vec<u8>,
+    mut exponent: i32,
+    significant: usize,
+) -> (Vec<u8>, i32) {
+    if digits.len() > significant {
+        let round_up = digits[significant] >= 5;
+        digits.truncate(significant);
+        if round_up {
+            match digits.iter().rposition(|digit| *digit != 9) {
+                Some(position) => {
+                    digits[position] += 1;
+                    digits.truncate(position + 1);
+                }
+                None => {
+                    digits = vec![1];
+                    exponent += 1;
+                }
+            }
+        }
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+    }
+    (digits, exponent)
+}
+
+/// Rounds the digits half away from zero to the given number of decimal places.
+fn round_to_decimals(digits: Vec<u8>, exponent: i32, decimals: usize) -> (Vec<u8>, i32) {
+    let keep = exponent + decimals as i32 + 1;
+    match keep {
+        _ if digits.is_empty() => (digits, 0),
+        keep if keep > 0 => round_digits(digits, exponent, keep as usize),
+        0 if digits[0] >= 5 => (vec![1], exponent + 1),
+        _ => (Vec::new(), 0),
+    }
+}
+
+/// Returns the digits at the positions from `from` down to `to`, e.g. the digits of the
+/// integer part for the positions from the exponent down to 0.
+fn digits_at(digits: &[u8], exponent: i32, from: i32, to: i32) -> Vec<u8> {
+    (to..=from)
+        .rev()
+        .map(|position| {
+            usize::try_from(exponent - position)
+                .ok()
+                .and_then(|index| digits.get(index).copied())
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Placeholder {
+    /// `0` shows insignificant zeros.
+    Zero,
+    /// `#` shows only significant digits.
+    Hash,
+    /// `?` shows spaces for insignificant zeros.
+    Question,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum DatePart {
+    ShortYear,
+    Year,
+    Month,
+    Month2,
+    MonthAbbreviation,
+    MonthName,
+    MonthLetter,
+    Day,
+    Day2,
+    WeekdayAbbreviation,
+    WeekdayName,
+    Hour,
+    Hour2,
+    Minute,
+    Minute2,
+    Second,
+    Second2,
+    /// Digits of the fraction of a second, e.g. `.00`.
+    SecondFraction(usize),
+    /// Elapsed hours, minutes or seconds like `[h]` with the minimal number of digits.
+    ElapsedHours(usize),
+    ElapsedMinutes(usize),
+    ElapsedSeconds(usize),
+    /// `AM/PM` or `A/P`, in the case of the code.
+    AmPm {
+        long: bool,
+        lowercase: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Digit(Placeholder),
+    DecimalPoint,
+    /// A comma that is not a literal. Resolved to a thousands separator or a scaling by 1000.
+    Comma,
+    Percent,
+    /// `E+` shows the sign of positive exponents, `E-` only the sign of negative exponents.
+    Exponent {
+        plus: bool,
+        lowercase: bool,
+    },
+    /// `@`, the text, or the number in the General format.
+    Text,
+    General,
+    /// A run of the same date letter like `yyyy` before it is resolved to a `DatePart`.
+    DateLetter(char, usize),
+    Date(DatePart),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    Less,
+    GreaterOrEqual,
+    LessOrEqual,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Section {
+    condition: Option<(Comparison, f64)>,
+    tokens: Vec<Token>,
+}
+
+/// A parsed Excel number format code like `#,##0.00;[Red]-#,##0.00` or `yyyy-mm-dd`.
+///
+/// A code has up to four sections separated by `;` for positive numbers, negative numbers,
+/// zero and text. Conditions like `[>100]` select the sections of numbers instead. Colors like
+/// `[Red]` are accepted but not shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumberFormat {
+    sections: Vec<Section>,
+}
+
+const COLORS: [&str; 8] = [
+    "black", "blue", "cyan", "green", "magenta", "red", "white", "yellow",
+];
+
+fn parse_condition(content: &str) -> Option<(Comparison, f64)> {
+    let (comparison, number) = [
+        ("<>", Comparison::NotEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<=", Comparison::LessOrEqual),
+        ("=", Comparison::Equal),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+    ]
+    .into_iter()
+    .find_map(|(symbol, comparison)| Some((comparison, content.strip_prefix(symbol)?)))?;
+    Some((comparison, number.trim().parse().ok()?))
+}
+
+/// Interprets the content of square brackets: a currency symbol like `[$€-407]`, a condition,
+/// a color or elapsed time like `[h]`.
+fn parse_bracket(content: &str, section: &mut Section) -> Result<(), types::Error> {
+    let lowercase = content.to_lowercase();
+    let elapsed = |letter: char| !lowercase.is_empty() && lowercase.chars().all(|c| c == letter);
+    let is_color = COLORS.contains(&lowercase.as_str())
+        || lowercase
+            .strip_prefix("color")
+            .is_some_and(|number| number.parse::<u8>().is_ok());
+    if let Some(currency) = content.strip_prefix('$') {
+        let symbol = currency.split('-').next().unwrap_or_default();
+        push_literal(&mut section.tokens, symbol);
+    } else if let Some(condition) = parse_condition(content) {
+        section.condition = Some(condition);
+    } else if elapsed('h') {
+        let part = DatePart::ElapsedHours(lowercase.len());
+        section.tokens.push(Token::Date(part));
+    } else if elapsed('m') {
+        let part = DatePart::ElapsedMinutes(lowercase.len());
+        section.tokens.push(Token::Date(part));
+    } else if elapsed('s') {
+        let part = DatePart::ElapsedSeconds(lowercase.len());
+        section.tokens.push(Token::Date(part));
+    } else if !is_color {
+        return Err(types::Error::Value);
+    }
+    Ok(())
+}
+
+fn push_literal(tokens: &mut Vec<Token>, text: &str) {
+    match tokens.last_mut() {
+        Some(Token::Literal(literal)) => literal.push_str(text),
+        _ => tokens.push(Token::Literal(text.to_string())),
+    }
+}
+
+/// Splits a format code into sections of tokens.
+fn tokenize(code: &str) -> Result<Vec<Section>, types::Error> {
+    let chars: Vec<char> = code.chars().collect();
+    let mut sections = Vec::new();
+    let mut section = Section {
+        condition: None,
+        tokens: Vec::new(),
+    };
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let rest: String = chars[i..].iter().take(7).collect::<String>().to_lowercase();
+        i += 1;
+        match c {
+            ';' => {
+                sections.push(section);
+                section = Section {
+                    condition: None,
+                    tokens: Vec::new(),
+                };
+            }
+            '"' => {
+                let length = chars[i..]
+                    .iter()
+                    .position(|c| *c == '"')
+                    .ok_or(types::Error::Value)?;
+                let literal: String = chars[i..i + length].iter().collect();
+                push_literal(&mut section.tokens, &literal);
+                i += length + 1;
+            }
+            '[' => {
+                let length = chars[i..]
+                    .iter()
+                    .position(|c| *c == ']')
+                    .ok_or(types::Error::Value)?;
+                let content: String = chars[i..i + length].iter().collect();
+                parse_bracket(&content, &mut section)?;
+                i += length + 1;
+            }
+            '\\' | '!' => {
+                let literal = chars.get(i).ok_or(types::Error::Value)?;
+                push_literal(&mut section.tokens, &literal.to_string());
+                i += 1;
+            }
+            '_' => {
+                chars.get(i).ok_or(types::Error::Value)?;
+                push_literal(&mut section.tokens, " ");
+                i += 1;
+            }
+            '*' => {
+                chars.get(i).ok_or(types::Error::Value)?;
+                i += 1;
+            }
+            // Fractions like `# ?/?` or `0/100` are not supported.
+            '/' if matches!(section.tokens.last(), Some(Token::Digit(_)))
+                && matches!(chars.get(i), Some('0'..='9' | '#' | '?')) =>
+            {
+                return Err(types::Error::Value);
+            }
+            '0' => section.tokens.push(Token::Digit(Placeholder::Zero)),
+            '#' => section.tokens.push(Token::Digit(Placeholder::Hash)),
+            '?' => section.tokens.push(Token::Digit(Placeholder::Question)),
+            '.' => section.tokens.push(Token::DecimalPoint),
+            ',' => section.tokens.push(Token::Comma),
+            '%' => section.tokens.push(Token::Percent),
+            '@' => section.tokens.push(Token::Text),
+            'E' | 'e' if matches!(chars.get(i), Some('+' | '-')) => {
+                section.tokens.push(Token::Exponent {
+                    plus: chars[i] == '+',
+                    lowercase: c == 'e',
+                });
+                i += 1;
+            }
+            _ if rest.starts_with("am/pm") => {
+                section.tokens.push(Token::Date(DatePart::AmPm {
+                    long: true,
+                    lowercase: c == 'a',
+                }));
+                i += 4;
+            }
+            _ if rest.starts_with("a/p") => {
+                section.tokens.push(Token::Date(DatePart::AmPm {
+                    long: false,
+                    lowercase: c == 'a',
+                }));
+                i += 2;
+            }
+            _ if rest.starts_with("general") => {
+                section.tokens.push(Token::General);
+                i += 6;
+            }
+            'y' | 'Y' | 'm' | 'M' | 'd' | 'D' | 'h' | 'H' | 's' | 'S' => {
+                let letter = c.to_ascii_lowercase();
+                let count = 1 + chars[i..]
+                    .iter()
+                    .take_while(|c| c.to_ascii_lowercase() == letter)
+                    .count();
+                section.tokens.push(Token::DateLetter(letter, count));
+                i += count - 1;
+            }
+            _ => push_literal(&mut section.tokens, &c.to_string()),
+        }
+    }
+    sections.push(section);
+    if sections.len() > 4 {
+        return Err(types::Error::Value);
+    }
+    Ok(sections)
+}
+
+fn is_date_token(token: &Token) -> bool {
+    matches!(token, Token::DateLetter(..) | Token::Date(_))
+}
+
+fn is_hour(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::DateLetter('h', _) | Token::Date(DatePart::ElapsedHours(_))
+    )
+}
+
+fn is_second(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::DateLetter('s', _) | Token::Date(DatePart::ElapsedSeconds(_))
+    )
+}
+
+/// Resolves the date letters of a section. `m` and `mm` are minutes after hours or before
+/// seconds and months otherwise. In a date section, `.0` after seconds is a fraction of a
+/// second and other number tokens are literals.
+fn resolve_date_section(tokens: Vec<Token>) -> Vec<Token> {
+    let date_tokens: Vec<Token> = tokens
+        .iter()
+        .filter(|t| is_date_token(t))
+        .cloned()
+        .collect();
+    let mut date_index: usize = 0;
+    let mut resolved: Vec<Token> = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let token = match token {
+            Token::DateLetter(letter, count) => {
+                let previous = date_index.checked_sub(1).map(|i| &date_tokens[i]);
+                let next = date_tokens.get(date_index + 1);
+                date_index += 1;
+                let part = match (letter, count) {
+                    ('y', 1..=2) => DatePart::ShortYear,
+                    ('y', _) => DatePart::Year,
+                    ('m', 1..=2)
+                        if previous.is_some_and(is_hour) || next.is_some_and(is_second) =>
+                    {
+                        if count == 1 {
+                            DatePart::Minute
+                        } else {
+                            DatePart::Minute2
+                        }
+                    }
+                    ('m', 1) => DatePart::Month,
+                    ('m', 2) => DatePart::Month2,
+                    ('m', 3) => DatePart::MonthAbbreviation,
+                    ('m', 5) => DatePart::MonthLetter,
+                    ('m', _) => DatePart::MonthName,
+                    ('d', 1) => DatePart::Day,
+                    ('d', 2) => DatePart::Day2,
+                    ('d', 3) => DatePart::WeekdayAbbreviation,
+                    ('d', _) => DatePart::WeekdayName,
+                    ('h', 1) => DatePart::Hour,
+                    ('h', _) => DatePart::Hour2,
+                    ('s', 1) => DatePart::Second,
+                    _ => DatePart::Second2,
+                };
+                Token::Date(part)
+            }
+            Token::Date(part) => {
+                date_index += 1;
+                Token::Date(part)
+            }
+            Token::DecimalPoint
+                if matches!(
+                    resolved.last(),
+                    Some(Token::Date(
+                        DatePart::Second | DatePart::Second2 | DatePart::ElapsedSeconds(_)
+                    ))
+                ) =>
+            {
+                let mut digits = 0;
+                while tokens
+                    .next_if_eq(&Token::Digit(Placeholder::Zero))
+                    .is_some()
+                {
+                    digits += 1;
+                }
+                if digits == 0 {
+                    Token::Literal(".".to_string())
+                } else {
+                    Token::Date(DatePart::SecondFraction(digits.min(3)))
+                }
+            }
+            Token::Digit(Placeholder::Zero) => Token::Literal("0".to_string()),
+            Token::Digit(Placeholder::Hash) => Token::Literal("#".to_string()),
+            Token::Digit(Placeholder::Question) => Token::Literal("?".to_string()),
+            Token::DecimalPoint => Token::Literal(".".to_string()),
+            Token::Comma => Token::Literal(",".to_string()),
+            Token::Percent => Token::Literal("%".to_string()),
+            token => token,
+        };
+        match token {
+            Token::Literal(literal) => push_literal(&mut resolved, &literal),
+            token => resolved.push(token),
+        }
+    }
+    resolved
+}
+
+impl NumberFormat {
+    /// Parses a format code. Returns #VALUE! for invalid codes, e.g. with more than four
+    /// sections or an unterminated quote, and for fractions like `# ?/?`.
+    pub fn parse(code: &str) -> Result<NumberFormat, types::Error> {
+        let sections = tokenize(code)?
+            .into_iter()
+            .map(|section| {
+                if section.tokens.iter().any(is_date_token) {
+                    Section {
+                        condition: section.condition,
+                        tokens: resolve_date_section(section.tokens),
+                    }
+                } else {
+                    section
+                }
+            })
+            .collect();
+        Ok(NumberFormat { sections })
+    }
+
+    /// The sections for numbers, without the text section.
+    fn number_sections(&self) -> &[Section] {
+        &self.sections[..self.sections.len().min(3)]
+    }
+
+    /// Picks the section of a number, and whether the section shows the absolute value.
+    fn number_section(&self, number: f64) -> (&Section, bool) {
+        let sections = self.number_sections();
+        if sections.iter().any(|section| section.condition.is_some()) {
+            let matching = sections.iter().find(|section| match section.condition {
+                Some((comparison, value)) => match comparison {
+                    Comparison::Equal => number == value,
+                    Comparison::NotEqual => number != value,
+                    Comparison::Greater => number > value,
+                    Comparison::Less => number < value,
+                    Comparison::GreaterOrEqual => number >= value,
+                    Comparison::LessOrEqual => number <= value,
+                },
+                None => true,
+            });
+            return (matching.unwrap_or(&sections[sections.len() - 1]), false);
+        }
+        match sections {
+            [_, negative, ..] if number < 0.0 => (negative, true),
+            [_, _, zero] if number == 0.0 => (zero, false),
+            _ => (&sections[0], false),
+        }
+    }
+
+    /// Formats a value. Numbers and dates are formatted with the number sections, text with
+    /// the text section if there is one, and booleans like text. Errors are returned as is,
+    /// and arrays result in #VALUE!.
+    pub fn format<N>(&self, value: &types::Value<N>) -> Result<String, types::Error>
+    where
+        N: XlNum,
+    {
+        match value {
+            types::Value::Number(number) => self.format_number(*number),
+            types::Value::Blank => self.format_number(N::zero()),
+            types::Value::Date(date) => {
+                let serial = date_to_serial(date.naive_local());
+                let (section, _) = self.number_section(serial);
+                if section.tokens.iter().any(is_date_token) {
+                    format_date(&section.tokens, serial)
+                } else {
+                    self.format_number(N::from_f64(serial).ok_or(types::Error::Value)?)
+                }
+            }
+            types::Value::Text(text) => Ok(self.format_text(text)),
+            types::Value::Boolean(boolean) => Ok(self.format_text(&boolean.to_string())),
+            types::Value::Error(error) => Err(*error),
+            types::Value::Iterator(_) => Err(types::Error::Value),
+        }
+    }
+
+    fn format_text(&self, text: &str) -> String {
+        let section = match self.sections.as_slice() {
+            [_, _, _, text_section] => text_section,
+            [section] if section.tokens.contains(&Token::Text) => section,
+            _ => return text.to_string(),
+        };
+        section
+            .tokens
+            .iter()
+            .map(|token| match token {
+                Token::Literal(literal) => literal.as_str(),
+                Token::Text => text,
+                _ => "",
+            })
+            .collect()
+    }
+
+    fn format_number<N>(&self, number: N) -> Result<String, types::Error>
+    where
+        N: XlNum,
+    {
+        if number.is_infinite() {
+            return Err(types::Error::Div0);
+        }
+        let value = number.to_f64().ok_or(types::Error::Num)?;
+        if value.is_nan() {
+            return Err(types::Error::Num);
+        }
+        let (section, absolute) = self.number_section(value);
+        if section.tokens.iter().any(is_date_token) {
+            return format_date(&section.tokens, value);
+        }
+        let (negative, digits, exponent) =
+            decimal_digits(&number.to_string()).ok_or(types::Error::Value)?;
+        let (digits, exponent) = round_digits(digits, exponent, N::SIGNIFICANT_DIGITS);
+        let general = || result_to_string(types::Value::Number(number.abs()));
+        let (text, shows_digits) = format_digits(&section.tokens, digits, exponent, general);
+        Ok(if negative && !absolute && shows_digits {
+            format!("-{text}")
+        } else {
+            text
+        })
+    }
+}
+
+/// Formats a value with a format code like the TEXT function of Excel.
+pub fn format_value<N>(value: &types::Value<N>, code: &str) -> Result<String, types::Error>
+where
+    N: XlNum,
+{
+    NumberFormat::parse(code)?.format(value)
+}
+
+/// Formats the digits of the absolute value of a number. Also returns whether a digit other
+/// than zero is shown, so that a negative number rounded to zero has no minus sign.
+fn format_digits(
+    tokens: &[Token],
+    digits: Vec<u8>,
+    exponent: i32,
+    general: impl Fn() -> String,
+) -> (String, bool) {
+    // Commas between digit placeholders of the integer part separate thousands, commas after
+    // the last digit placeholder scale the number by 1000.
+    let end_of_integer = tokens
+        .iter()
+        .position(|token| matches!(token, Token::DecimalPoint | Token::Exponent { .. }))
+        .unwrap_or(tokens.len());
+    let is_digit = |token: Option<&Token>| matches!(token, Some(Token::Digit(_)));
+    let mut thousands = false;
+    let mut scaling = 0;
+    let mut tokens: Vec<Token> = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(index, token)| {
+            if *token != Token::Comma {
+                return Some(token.clone());
+            }
+            let previous = tokens[..index].iter().rev().find(|t| **t != Token::Comma);
+            let next = tokens[index..].iter().find(|t| **t != Token::Comma);
+            match (is_digit(previous), is_digit(next)) {
+                (true, true) if index < end_of_integer => {
+                    thousands = true;
+                    None
+                }
+                (true, false) => {
+                    scaling += 1;
+                    None
+                }
+                _ => Some(Token::Literal(",".to_string())),
+            }
+        })
+        .collect();
+    let has_placeholders = tokens.iter().any(|token| is_digit(Some(token)));
+    let has_digits = !digits.is_empty();
+    let percent = tokens.iter().filter(|t| **t == Token::Percent).count() as i32;
+    for token in tokens.iter_mut() {
+        if *token == Token::Percent {
+            *token = Token::Literal("%".to_string());
+        }
+    }
+    let exponent = exponent + 2 * percent - 3 * scaling;
+
+    // The integer part is shown even without digit placeholders before the decimal point.
+    let point = tokens.iter().position(|t| *t == Token::DecimalPoint);
+    if let Some(point) = point {
+        if !tokens[..point].iter().any(|token| is_digit(Some(token))) {
+            tokens.insert(point, Token::Digit(Placeholder::Hash));
+        }
+    }
+    let point = tokens.iter().position(|t| *t == Token::DecimalPoint);
+    let scientific = tokens
+        .iter()
+        .position(|t| matches!(t, Token::Exponent { .. }));
+    let end_of_integer = point.or(scientific).unwrap_or(tokens.len());
+    let end_of_fraction = scientific.unwrap_or(tokens.len());
+    let (integer_tokens, fraction_tokens, exponent_tokens) = (
+        &tokens[..end_of_integer],
+        &tokens[end_of_integer..end_of_fraction],
+        &tokens[end_of_fraction..],
+    );
+    let placeholders = |tokens: &[Token]| -> Vec<Placeholder> {
+        tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::Digit(placeholder) => Some(*placeholder),
+                _ => None,
+            })
+            .collect()
+    };
+    let decimals = placeholders(fraction_tokens).len();
+
+    let (digits, exponent, shown_exponent) = match exponent_tokens.first() {
+        Some(Token::Exponent { .. }) => {
+            let integer_placeholders = placeholders(integer_tokens);
+            let width = integer_placeholders.len().max(1) as i32;
+            let shown_exponent = |exponent: i32| {
+                if integer_placeholders.iter().all(|p| *p == Placeholder::Zero) {
+                    exponent - (width - 1)
+                } else {
+                    exponent.div_euclid(width) * width
+                }
+            };
+            let mut shown = shown_exponent(exponent);
+            let (mut rounded, mut rounded_exponent) =
+                round_to_decimals(digits.clone(), exponent - shown, decimals);
+            if !rounded.is_empty() && rounded_exponent + shown != exponent {
+                shown = shown_exponent(rounded_exponent + shown);
+                (rounded, rounded_exponent) = round_to_decimals(digits, exponent - shown, decimals);
+            }
+            if rounded.is_empty() {
+                shown = 0;
+            }
+            (rounded, rounded_exponent, Some(shown))
+        }
+        _ => {
+            let (digits, exponent) = round_to_decimals(digits, exponent, decimals);
+            (digits, exponent, None)
+        }
+    };
+    // Without digit placeholders the sign belongs to the number in the General format.
+    let shows_digits = !digits.is_empty() || (!has_placeholders && has_digits);
+
+    let mut text = format_integer(integer_tokens, &digits, exponent, thousands, &general);
+    let fraction = digits_at(&digits, exponent, -1, -(decimals as i32));
+    // Optional placeholders at the end of the fraction hide zeros.
+    let shown_decimals = fraction_tokens
+        .iter()
+        .filter(|t| matches!(t, Token::Digit(_)))
+        .zip(&fraction)
+        .enumerate()
+        .filter(|(_, (token, digit))| **digit != 0 || **token == Token::Digit(Placeholder::Zero))
+        .map(|(index, _)| index + 1)
+        .last()
+        .unwrap_or(0);
+    let mut decimal = 0;
+    for token in fraction_tokens {
+        match token {
+            Token::DecimalPoint => text.push('.'),
+            Token::Digit(placeholder) => {
+                if decimal < shown_decimals {
+                    text.push((b'0' + fraction[decimal]) as char);
+                } else if *placeholder == Placeholder::Question {
+                    text.push(' ');
+                }
+                decimal += 1;
+            }
+            token => text.push_str(&format_literal(token, &general)),
+        }
+    }
+    if let Some(shown_exponent) = shown_exponent {
+        let exponent_digits = placeholders(exponent_tokens).len().max(1);
+        for token in exponent_tokens {
+            match token {
+                Token::Exponent { plus, lowercase } => {
+                    text.push(if *lowercase { 'e' } else { 'E' });
+                    if shown_exponent < 0 {
+                        text.push('-');
+                    } else if *plus {
+                        text.push('+');
+                    }
+                    text.push_str(&format!(
+                        "{:0exponent_digits$}",
+                        shown_exponent.unsigned_abs()
+                    ));
+                }
+                Token::Digit(_) => (),
+                token => text.push_str(&format_literal(token, &general)),
+            }
+        }
+    }
+    (text, shows_digits)
+}
+
+fn format_literal(token: &Token, general: &impl Fn() -> String) -> String {
+    match token {
+        Token::Literal(literal) => literal.clone(),
+        Token::General | Token::Text => general(),
+        _ => String::new(),
+    }
+}
+
+/// Formats the integer part. The first digit placeholder shows all remaining digits.
+fn format_integer(
+    tokens: &[Token],
+    digits: &[u8],
+    exponent: i32,
+    thousands: bool,
+    general: &impl Fn() -> String,
+) -> String {
+    let integer = if exponent >= 0 {
+        digits_at(digits, exponent, exponent, 0)
+    } else {
+        Vec::new()
+    };
+    let first_placeholder = tokens.iter().position(|t| matches!(t, Token::Digit(_)));
+    // Characters in reverse order, with the number of shown digits for the separators.
+    let mut reversed: Vec<char> = Vec::new();
+    let mut position = 0;
+    let mut push_digit = |reversed: &mut Vec<char>, digit: char| {
+        if thousands && position > 0 && position % 3 == 0 {
+            reversed.push(',');
+        }
+        reversed.push(digit);
+        position += 1;
+    };
+    let mut remaining = integer.iter().rev();
+    for (index, token) in tokens.iter().enumerate().rev() {
+        match token {
+            Token::Digit(placeholder) if Some(index) == first_placeholder => {
+                let mut shown = false;
+                for digit in remaining.by_ref() {
+                    push_digit(&mut reversed, (b'0' + digit) as char);
+                    shown = true;
+                }
+                if !shown {
+                    match placeholder {
+                        Placeholder::Zero => push_digit(&mut reversed, '0'),
+                        Placeholder::Question => reversed.push(' '),
+                        Placeholder::Hash => (),
+                    }
+                }
+            }
+            Token::Digit(placeholder) => match (remaining.next(), placeholder) {
+                (Some(digit), _) => push_digit(&mut reversed, (b'0' + digit) as char),
+                (None, Placeholder::Zero) => push_digit(&mut reversed, '0'),
+                (None, Placeholder::Question) => reversed.push(' '),
+                (None, Placeholder::Hash) => (),
+            },
+            token => reversed.extend(format_literal(token, general).chars().rev()),
+        }
+    }
+    reversed.iter().rev().collect()
+}
+
+fn base_date() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(1899, 12, 30)
+        .unwrap()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+}
+
+/// Converts a date to the serial number of Excel, the days since 1899-12-30. Unlike Excel,
+/// the serial numbers before March 1900 do not count the 29th of February 1900.
+pub fn date_to_serial(date: NaiveDateTime) -> f64 {
+    (date - base_date()).num_milliseconds() as f64 / 86_400_000.0
+}
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+/// The serial number of 10000-01-01, the day after the last date of Excel.
+const END_SERIAL: f64 = 2_958_466.0;
+
+/// Formats a serial number with date and time tokens. Negative serial numbers and serial
+/// numbers after 9999-12-31 are #VALUE!.
+fn format_date(tokens: &[Token], serial: f64) -> Result<String, types::Error> {
+    if !(0.0..END_SERIAL).contains(&serial) {
+        return Err(types::Error::Value);
+    }
+    // Rounds to the shown fraction of a second.
+    let fraction_digits = tokens
+        .iter()
+        .find_map(|token| match token {
+            Token::Date(DatePart::SecondFraction(digits)) => Some(*digits),
+            _ => None,
+        })
+        .unwrap_or(0);
+    let scale = 10i64.pow(fraction_digits as u32);
+    let total = (serial * 86_400.0 * scale as f64).round() as i64;
+    let (seconds, fraction) = (total / scale, total % scale);
+    let date = TimeDelta::try_seconds(seconds)
+        .and_then(|duration| base_date().checked_add_signed(duration))
+        // Rounding to the shown fraction of a second may still reach the year 10000.
+        .filter(|date| date.year() <= 9999)
+        .ok_or(types::Error::Value)?;
+    let twelve_hours = tokens
+        .iter()
+        .any(|token| matches!(token, Token::Date(DatePart::AmPm { .. })));
+    let hour = if twelve_hours {
+        (date.hour() + 11) % 12 + 1
+    } else {
+        date.hour()
+    };
+    let mut text = String::new();
+    for token in tokens {
+        match token {
+            Token::Date(part) => text.push_str(&match part {
+                DatePart::ShortYear => format!("{:02}", date.year() % 100),
+                DatePart::Year => format!("{:04}", date.year()),
+                DatePart::Month => date.month().to_string(),
+                DatePart::Month2 => format!("{:02}", date.month()),
+                DatePart::MonthAbbreviation => MONTHS[date.month0() as usize][..3].to_string(),
+                DatePart::MonthName => MONTHS[date.month0() as usize].to_string(),
+                DatePart::MonthLetter => MONTHS[date.month0() as usize][..1].to_string(),
+                DatePart::Day => date.day().to_string(),
+                DatePart::Day2 => format!("{:02}", date.day()),
+                DatePart::WeekdayAbbreviation => {
+                    WEEKDAYS[date.weekday().num_days_from_monday() as usize][..3].to_string()
+                }
+                DatePart::WeekdayName => {
+                    WEEKDAYS[date.weekday().num_days_from_monday() as usize].to_string()
+                }
+                DatePart::Hour => hour.to_string(),
+                DatePart::Hour2 => format!("{hour:02}"),
+                DatePart::Minute => date.minute().to_string(),
+                DatePart::Minute2 => format!("{:02}", date.minute()),
+                DatePart::Second => date.second().to_string(),
+                DatePart::Second2 => format!("{:02}", date.second()),
+                DatePart::SecondFraction(digits) => format!(".{fraction:0digits$}"),
+                DatePart::ElapsedHours(width) => format!("{:0width$}", seconds / 3600),
+                DatePart::ElapsedMinutes(width) => format!("{:0width$}", seconds / 60),
+                DatePart::ElapsedSeconds(width) => format!("{seconds:0width$}"),
+                DatePart::AmPm { long, lowercase } => {
+                    let am_pm = match (date.hour() < 12, long) {
+                        (true, true) => "AM",
+                        (false, true) => "PM",
+                        (true, false) => "A",
+                        (false, false) => "P",
+                    };
+                    if *lowercase {
+                        am_pm.to_lowercase()
+                    } else {
+                        am_pm.to_string()
+                    }
+                }
+            }),
+            Token::Literal(literal) => text.push_str(literal),
+            _ => (),
+        }
+    }
+    Ok(text)
+}
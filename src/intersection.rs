@@ -0,0 +1,172 @@
+use crate::types::{self, XlNum};
+
+/// Row and column of a cell, both starting at 1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CellPosition {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl CellPosition {
+    /// Parses a cell name like `B5`, ignoring the case of the column letters.
+    pub fn parse(name: &str) -> Option<CellPosition> {
+        let digits = name.find(|c: char| c.is_ascii_digit())?;
+        let (letters, digits) = name.split_at(digits);
+        if letters.is_empty() || !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        let column = letters.chars().try_fold(0usize, |column, c| {
+            let value = c.to_ascii_uppercase() as usize - 'A' as usize + 1;
+            column.checked_mul(26)?.checked_add(value)
+        })?;
+        let row = digits.parse::<usize>().ok().filter(|row| *row > 0)?;
+        Some(CellPosition { row, column })
+    }
+
+    /// Returns the name of the cell, e.g. `AA10` for row 10 and column 27.
+    pub fn name(&self) -> String {
+        let mut letters = Vec::new();
+        let mut column = self.column;
+        while column > 0 {
+            column -= 1;
+            letters.push((b'A' + (column % 26) as u8) as char);
+            column /= 26;
+        }
+        letters.iter().rev().collect::<String>() + &self.row.to_string()
+    }
+}
+
+/// Returns the first and last row and column of a range reference like `A1:A10`.
+pub(crate) fn parse_range(name: &str) -> Option<(CellPosition, CellPosition)> {
+    let (start, end) = name.split_once(':')?;
+    let (start, end) = (CellPosition::parse(start)?, CellPosition::parse(end)?);
+    Some((
+        CellPosition {
+            row: start.row.min(end.row),
+            column: start.column.min(end.column),
+        },
+        CellPosition {
+            row: start.row.max(end.row),
+            column: start.column.max(end.column),
+        },
+    ))
+}
+
+/// Picks the single cell of a range in the row and column of the given cell, or none if the
+/// range does not cover them.
+fn intersect_range(
+    (start, end): (CellPosition, CellPosition),
+    position: CellPosition,
+) -> Option<CellPosition> {
+    let pick = |first: usize, last: usize, current: usize| {
+        if first == last {
+            Some(first)
+        } else if (first..=last).contains(&current) {
+            Some(current)
+        } else {
+            None
+        }
+    };
+    Some(CellPosition {
+        row: pick(start.row, end.row, position.row)?,
+        column: pick(start.column, end.column, position.column)?,
+    })
+}
+
+fn is_range<N>(formula: &types::Formula<N>) -> bool
+where
+    N: XlNum,
+{
+    matches!(formula, types::Formula::Reference(name) if parse_range(name).is_some())
+}
+
+fn intersection<N>(formula: types::Formula<N>) -> types::Formula<N>
+where
+    N: XlNum,
+{
+    types::Formula::Operation(types::Expression {
+        op: types::Operator::Function(types::Function::ImplicitIntersection),
+        values: vec![formula],
+    })
+}
+
+/// Adds the `@` operator to the range references of a legacy formula that are used where a
+/// single value is expected: as the result of the formula, operands of operators and arguments
+/// of functions that do not take lists. Lists of values stay lists.
+pub fn add_implicit_intersection<N>(formula: types::Formula<N>) -> types::Formula<N>
+where
+    N: XlNum,
+{
+    if is_range(&formula) {
+        intersection(formula)
+    } else {
+        add_intersection_to_arguments(formula)
+    }
+}
+
+fn add_intersection_to_arguments<N>(formula: types::Formula<N>) -> types::Formula<N>
+where
+    N: XlNum,
+{
+    match formula {
+        types::Formula::Operation(exp) => {
+            let single_values = match exp.op {
+                types::Operator::Function(func) => !func.takes_lists(),
+                _ => true,
+            };
+            let values = exp
+                .values
+                .into_iter()
+                .map(|value| {
+                    if single_values {
+                        add_implicit_intersection(value)
+                    } else {
+                        add_intersection_to_arguments(value)
+                    }
+                })
+                .collect();
+            types::Formula::Operation(types::Expression { op: exp.op, values })
+        }
+        types::Formula::Iterator(vec) => {
+            types::Formula::Iterator(vec.into_iter().map(add_intersection_to_arguments).collect())
+        }
+        types::Formula::Value(_) | types::Formula::Reference(_) => formula,
+    }
+}
+
+/// Replaces `@` applied to a range reference like `A1:A10` with the reference to the cell of
+/// the range in the row or column of the evaluating cell, or with #VALUE! if the range does
+/// not cover it. `@` applied to any other list still evaluates to its first element.
+pub fn intersect_formula<N>(formula: types::Formula<N>, position: CellPosition) -> types::Formula<N>
+where
+    N: XlNum,
+{
+    match formula {
+        types::Formula::Operation(exp) => {
+            let values: Vec<types::Formula<N>> = exp
+                .values
+                .into_iter()
+                .map(|value| intersect_formula(value, position))
+                .collect();
+            if let (
+                types::Operator::Function(types::Function::ImplicitIntersection),
+                [types::Formula::Reference(name)],
+            ) = (exp.op, values.as_slice())
+            {
+                if let Some(range) = parse_range(name) {
+                    return match intersect_range(range, position) {
+                        Some(cell) => types::Formula::Reference(cell.name()),
+                        None => types::Formula::Value(types::Value::Error(types::Error::Value)),
+                    };
+                }
+            }
+            types::Formula::Operation(types::Expression { op: exp.op, values })
+        }
+        types::Formula::Iterator(vec) => types::Formula::Iterator(
+            vec.into_iter()
+                .map(|value| intersect_formula(value, position))
+                .collect(),
+        ),
+        types::Formula::Value(_) | types::Formula::Reference(_) => formula,
+    }
+}
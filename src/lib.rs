@@ -8,5 +8,20 @@ pub mod types;
 /// Parses a string using `pest` and `pest::prec_climber`.
 pub mod parse_formula;
 
+/// Simplifies parsed formulas that are evaluated repeatedly.
+pub mod optimize;
+
+/// Formats values with Excel number format codes.
+pub mod format;
+
+/// Resolves implicit intersection of range references for the evaluating cell.
+pub mod intersection;
+
+/// Infers the possible kinds of the values of formulas.
+pub mod analyze;
+
+/// Recalculates a set of named formulas in dependency order.
+pub mod workbook;
+
 pub type NoReference<'a, N> = &'a fn(String) -> types::Value<N>;
 pub type NoCustomFunction<'a, N> = &'a fn(String, Vec<N>) -> types::Value<N>;
@@ -0,0 +1,116 @@
+use crate::{
+    calculate::calculate_formula,
+    types::{self, XlNum},
+    NoReference,
+};
+use std::{fmt::Debug, str::FromStr};
+
+/// Functions whose result can change between evaluations with the same arguments. They are
+/// never folded into constants.
+pub(crate) fn is_volatile(func: types::Function) -> bool {
+    matches!(
+        func,
+        types::Function::Rand | types::Function::RandBetween | types::Function::RandArray
+    )
+}
+
+fn is_constant<N>(formula: &types::Formula<N>) -> bool
+where
+    N: XlNum,
+{
+    matches!(formula, types::Formula::Value(_))
+}
+
+fn calculate_constant<N>(formula: types::Formula<N>) -> types::Formula<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    types::Formula::Value(calculate_formula(formula, None::<NoReference<N>>))
+}
+
+/// Replaces IF with the branch that is taken for a constant condition. Conditions that are
+/// neither boolean, number nor blank make IF return an error, regardless of the branches.
+fn simplify_iff<N>(
+    condition: types::Value<N>,
+    true_value: types::Formula<N>,
+    false_value: types::Formula<N>,
+) -> types::Formula<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    match condition {
+        types::Value::Boolean(boolean) => {
+            if boolean.into() {
+                true_value
+            } else {
+                false_value
+            }
+        }
+        types::Value::Number(number) => {
+            if number.is_zero() {
+                false_value
+            } else {
+                true_value
+            }
+        }
+        types::Value::Blank => false_value,
+        _ => calculate_constant(types::Formula::Operation(types::Expression {
+            op: types::Operator::Function(types::Function::Iff),
+            values: vec![
+                types::Formula::Value(condition),
+                types::Formula::Value(types::Value::Blank),
+                types::Formula::Value(types::Value::Blank),
+            ],
+        })),
+    }
+}
+
+/// Evaluates all sub-expressions that neither contain references nor volatile functions and
+/// replaces them with their value. IF with a constant condition is replaced with the branch
+/// that is taken. Evaluating the optimized formula gives the same result as evaluating the
+/// original one.
+pub fn optimize_formula<N>(formula: types::Formula<N>) -> types::Formula<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    match formula {
+        types::Formula::Operation(exp) => {
+            let mut values: Vec<types::Formula<N>> =
+                exp.values.into_iter().map(optimize_formula).collect();
+            match exp.op {
+                types::Operator::Function(types::Function::Iff) if values.len() == 3 => {
+                    let false_value = values.pop().unwrap();
+                    let true_value = values.pop().unwrap();
+                    match values.pop().unwrap() {
+                        types::Formula::Value(condition) => {
+                            simplify_iff(condition, true_value, false_value)
+                        }
+                        condition => types::Formula::Operation(types::Expression {
+                            op: exp.op,
+                            values: vec![condition, true_value, false_value],
+                        }),
+                    }
+                }
+                types::Operator::Function(func) if is_volatile(func) => {
+                    types::Formula::Operation(types::Expression { op: exp.op, values })
+                }
+                op if values.iter().all(is_constant) => {
+                    calculate_constant(types::Formula::Operation(types::Expression { op, values }))
+                }
+                op => types::Formula::Operation(types::Expression { op, values }),
+            }
+        }
+        types::Formula::Iterator(vec) => {
+            let vec: Vec<types::Formula<N>> = vec.into_iter().map(optimize_formula).collect();
+            if vec.iter().all(is_constant) {
+                calculate_constant(types::Formula::Iterator(vec))
+            } else {
+                types::Formula::Iterator(vec)
+            }
+        }
+        types::Formula::Value(_) | types::Formula::Reference(_) => formula,
+    }
+}
@@ -60,6 +60,101 @@ where
     }
 }
 
+/// Parses a string like `parse_string_to_formula`, but results in #LIMIT! for formula strings
+/// that are longer or nested deeper than allowed, before they can overflow the stack.
+pub fn parse_string_to_formula_with_limits<N>(
+    s: &str,
+    f: Option<&impl Fn(String, Vec<N>) -> types::Value<N>>,
+    limits: &types::Limits,
+) -> types::Formula<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let limit_error = types::Formula::Value(types::Value::Error(types::Error::Limit));
+    if let Some(max_formula_length) = limits.max_formula_length {
+        if s.chars().count() > max_formula_length {
+            return limit_error;
+        }
+    }
+    if let Some(max_nesting_depth) = limits.max_nesting_depth {
+        if bracket_depth(s) > max_nesting_depth {
+            return limit_error;
+        }
+        let formula = parse_string_to_formula(s, f);
+        if formula_depth(&formula) > max_nesting_depth {
+            return limit_error;
+        }
+        formula
+    } else {
+        parse_string_to_formula(s, f)
+    }
+}
+
+/// Maximum nesting of parentheses and braces outside of string literals.
+fn bracket_depth(s: &str) -> usize {
+    let mut depth: usize = 0;
+    let mut max_depth = 0;
+    let mut quote = None;
+    for c in s.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '(') | (None, '{') => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            (None, ')') | (None, '}') => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+    }
+    max_depth
+}
+
+/// Operators count as a fraction of a nesting level, so that long chains like `1+2+...+n`
+/// stay within the limit while their depth is still bounded.
+pub(crate) const OPERATORS_PER_LEVEL: usize = 4;
+
+/// How much a formula adds to the nesting of its arguments, in `1 / OPERATORS_PER_LEVEL`
+/// levels: a whole level for functions, lists and references and a fraction for operators.
+pub(crate) fn nesting_weight<N>(formula: &types::Formula<N>) -> usize
+where
+    N: XlNum,
+{
+    match formula {
+        types::Formula::Operation(types::Expression {
+            op: types::Operator::Function(_),
+            ..
+        })
+        | types::Formula::Iterator(_)
+        | types::Formula::Reference(_) => OPERATORS_PER_LEVEL,
+        types::Formula::Operation(_) => 1,
+        types::Formula::Value(_) => 0,
+    }
+}
+
+/// Depth of nested functions and lists, computed without recursion.
+fn formula_depth<N>(formula: &types::Formula<N>) -> usize
+where
+    N: XlNum,
+{
+    let mut max_depth = 0;
+    let mut stack = vec![(formula, 0)];
+    while let Some((formula, depth)) = stack.pop() {
+        max_depth = max_depth.max(depth);
+        let depth = depth + nesting_weight(formula);
+        match formula {
+            types::Formula::Operation(exp) => {
+                stack.extend(exp.values.iter().map(|value| (value, depth)))
+            }
+            types::Formula::Iterator(vec) => stack.extend(vec.iter().map(|value| (value, depth))),
+            types::Formula::Value(_) | types::Formula::Reference(_) => (),
+        }
+    }
+    max_depth.div_ceil(OPERATORS_PER_LEVEL)
+}
+
 fn build_formula_number<N>(pair: pest::iterators::Pair<Rule>) -> types::Formula<N>
 where
     N: XlNum,
@@ -116,6 +211,7 @@ where
         Rule::abs => types::Operator::Function(types::Function::Abs),
         Rule::not => types::Operator::Function(types::Function::Not),
         Rule::negate => types::Operator::Function(types::Function::Negate),
+        Rule::intersection => types::Operator::Function(types::Function::ImplicitIntersection),
         _ => unreachable!(),
     };
     let operation = types::Expression {
@@ -133,6 +229,17 @@ where
     types::Formula::Value(types::Value::Blank)
 }
 
+fn build_formula_function_without_args<N>(operation: Rule) -> types::Formula<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    types::Formula::Operation(types::Expression {
+        op: rule_to_function_operator(operation),
+        values: Vec::new(),
+    })
+}
+
 fn build_formula_reference<N>(pair: pest::iterators::Pair<Rule>) -> types::Formula<N>
 where
     N: XlNum,
@@ -150,11 +257,20 @@ where
     N: XlNum,
     <N as FromStr>::Err: Debug,
 {
-    let mut vec = Vec::new();
-    for term in pair.into_inner() {
-        vec.push(build_formula_with_parser(term.into_inner(), f));
+    let mut rows: Vec<Vec<types::Formula<N>>> = pair
+        .into_inner()
+        .map(|row| {
+            row.into_inner()
+                .map(|term| build_formula_with_parser(term.into_inner(), f))
+                .collect()
+        })
+        .collect();
+    // A list with rows separated by ";" is a list of one list per row.
+    if rows.len() == 1 {
+        types::Formula::Iterator(rows.pop().unwrap())
+    } else {
+        types::Formula::Iterator(rows.into_iter().map(types::Formula::Iterator).collect())
     }
-    types::Formula::Iterator(vec)
 }
 
 fn build_formula_collective_operator<N>(
@@ -206,6 +322,129 @@ fn rule_to_function_operator(collective_operation: Rule) -> types::Operator {
         Rule::search => types::Operator::Function(types::Function::Search),
         Rule::iserror => types::Operator::Function(types::Function::IsError),
         Rule::blank_func => types::Operator::Function(types::Function::Blank),
+        Rule::power_function => types::Operator::Function(types::Function::Power),
+        Rule::sqrt => types::Operator::Function(types::Function::Sqrt),
+        Rule::ln => types::Operator::Function(types::Function::Ln),
+        Rule::text => types::Operator::Function(types::Function::Text),
+        Rule::value => types::Operator::Function(types::Function::Value),
+        Rule::numbervalue => types::Operator::Function(types::Function::NumberValue),
+        Rule::round => types::Operator::Function(types::Function::Round),
+        Rule::roundup => types::Operator::Function(types::Function::RoundUp),
+        Rule::rounddown => types::Operator::Function(types::Function::RoundDown),
+        Rule::mround => types::Operator::Function(types::Function::MRound),
+        Rule::ceiling => types::Operator::Function(types::Function::Ceiling),
+        Rule::ceiling_math => types::Operator::Function(types::Function::CeilingMath),
+        Rule::floor => types::Operator::Function(types::Function::Floor),
+        Rule::floor_math => types::Operator::Function(types::Function::FloorMath),
+        Rule::int_function => types::Operator::Function(types::Function::Int),
+        Rule::trunc => types::Operator::Function(types::Function::Trunc),
+        Rule::mod_function => types::Operator::Function(types::Function::Mod),
+        Rule::quotient => types::Operator::Function(types::Function::Quotient),
+        Rule::exp => types::Operator::Function(types::Function::Exp),
+        Rule::log => types::Operator::Function(types::Function::Log),
+        Rule::log10 => types::Operator::Function(types::Function::Log10),
+        Rule::pi => types::Operator::Function(types::Function::Pi),
+        Rule::sign => types::Operator::Function(types::Function::Sign),
+        Rule::sin => types::Operator::Function(types::Function::Sin),
+        Rule::cos => types::Operator::Function(types::Function::Cos),
+        Rule::tan => types::Operator::Function(types::Function::Tan),
+        Rule::asin => types::Operator::Function(types::Function::Asin),
+        Rule::acos => types::Operator::Function(types::Function::Acos),
+        Rule::atan => types::Operator::Function(types::Function::Atan),
+        Rule::atan2 => types::Operator::Function(types::Function::Atan2),
+        Rule::degrees => types::Operator::Function(types::Function::Degrees),
+        Rule::radians => types::Operator::Function(types::Function::Radians),
+        Rule::sinh => types::Operator::Function(types::Function::Sinh),
+        Rule::cosh => types::Operator::Function(types::Function::Cosh),
+        Rule::tanh => types::Operator::Function(types::Function::Tanh),
+        Rule::asinh => types::Operator::Function(types::Function::Asinh),
+        Rule::acosh => types::Operator::Function(types::Function::Acosh),
+        Rule::atanh => types::Operator::Function(types::Function::Atanh),
+        Rule::min => types::Operator::Function(types::Function::Min),
+        Rule::max => types::Operator::Function(types::Function::Max),
+        Rule::count => types::Operator::Function(types::Function::Count),
+        Rule::counta => types::Operator::Function(types::Function::CountA),
+        Rule::countblank => types::Operator::Function(types::Function::CountBlank),
+        Rule::median => types::Operator::Function(types::Function::Median),
+        Rule::mode => types::Operator::Function(types::Function::Mode),
+        Rule::mode_sngl => types::Operator::Function(types::Function::ModeSngl),
+        Rule::stdev => types::Operator::Function(types::Function::Stdev),
+        Rule::stdev_s => types::Operator::Function(types::Function::StdevS),
+        Rule::stdev_p => types::Operator::Function(types::Function::StdevP),
+        Rule::var => types::Operator::Function(types::Function::Var),
+        Rule::var_s => types::Operator::Function(types::Function::VarS),
+        Rule::var_p => types::Operator::Function(types::Function::VarP),
+        Rule::large => types::Operator::Function(types::Function::Large),
+        Rule::small => types::Operator::Function(types::Function::Small),
+        Rule::rank => types::Operator::Function(types::Function::Rank),
+        Rule::rank_eq => types::Operator::Function(types::Function::RankEq),
+        Rule::rank_avg => types::Operator::Function(types::Function::RankAvg),
+        Rule::percentile => types::Operator::Function(types::Function::Percentile),
+        Rule::percentile_inc => types::Operator::Function(types::Function::PercentileInc),
+        Rule::percentile_exc => types::Operator::Function(types::Function::PercentileExc),
+        Rule::quartile => types::Operator::Function(types::Function::Quartile),
+        Rule::quartile_inc => types::Operator::Function(types::Function::QuartileInc),
+        Rule::quartile_exc => types::Operator::Function(types::Function::QuartileExc),
+        Rule::countif => types::Operator::Function(types::Function::CountIf),
+        Rule::countifs => types::Operator::Function(types::Function::CountIfs),
+        Rule::sumif => types::Operator::Function(types::Function::SumIf),
+        Rule::sumifs => types::Operator::Function(types::Function::SumIfs),
+        Rule::averageif => types::Operator::Function(types::Function::AverageIf),
+        Rule::averageifs => types::Operator::Function(types::Function::AverageIfs),
+        Rule::maxifs => types::Operator::Function(types::Function::MaxIfs),
+        Rule::minifs => types::Operator::Function(types::Function::MinIfs),
+        Rule::sumproduct => types::Operator::Function(types::Function::SumProduct),
+        Rule::mmult => types::Operator::Function(types::Function::MMult),
+        Rule::minverse => types::Operator::Function(types::Function::MInverse),
+        Rule::mdeterm => types::Operator::Function(types::Function::MDeterm),
+        Rule::transpose => types::Operator::Function(types::Function::Transpose),
+        Rule::pmt => types::Operator::Function(types::Function::Pmt),
+        Rule::ipmt => types::Operator::Function(types::Function::Ipmt),
+        Rule::ppmt => types::Operator::Function(types::Function::Ppmt),
+        Rule::pv => types::Operator::Function(types::Function::Pv),
+        Rule::fv => types::Operator::Function(types::Function::Fv),
+        Rule::nper => types::Operator::Function(types::Function::Nper),
+        Rule::rate => types::Operator::Function(types::Function::Rate),
+        Rule::npv => types::Operator::Function(types::Function::Npv),
+        Rule::irr => types::Operator::Function(types::Function::Irr),
+        Rule::xnpv => types::Operator::Function(types::Function::Xnpv),
+        Rule::xirr => types::Operator::Function(types::Function::Xirr),
+        Rule::rand => types::Operator::Function(types::Function::Rand),
+        Rule::randbetween => types::Operator::Function(types::Function::RandBetween),
+        Rule::randarray => types::Operator::Function(types::Function::RandArray),
+        Rule::dec2bin => types::Operator::Function(types::Function::Dec2Bin),
+        Rule::dec2hex => types::Operator::Function(types::Function::Dec2Hex),
+        Rule::dec2oct => types::Operator::Function(types::Function::Dec2Oct),
+        Rule::bin2dec => types::Operator::Function(types::Function::Bin2Dec),
+        Rule::hex2dec => types::Operator::Function(types::Function::Hex2Dec),
+        Rule::oct2dec => types::Operator::Function(types::Function::Oct2Dec),
+        Rule::base => types::Operator::Function(types::Function::Base),
+        Rule::decimal => types::Operator::Function(types::Function::Decimal),
+        Rule::bitand => types::Operator::Function(types::Function::BitAnd),
+        Rule::bitor => types::Operator::Function(types::Function::BitOr),
+        Rule::bitxor => types::Operator::Function(types::Function::BitXor),
+        Rule::bitlshift => types::Operator::Function(types::Function::BitLShift),
+        Rule::bitrshift => types::Operator::Function(types::Function::BitRShift),
+        Rule::fact => types::Operator::Function(types::Function::Fact),
+        Rule::factdouble => types::Operator::Function(types::Function::FactDouble),
+        Rule::combin => types::Operator::Function(types::Function::Combin),
+        Rule::combina => types::Operator::Function(types::Function::CombinA),
+        Rule::permut => types::Operator::Function(types::Function::Permut),
+        Rule::gcd => types::Operator::Function(types::Function::Gcd),
+        Rule::lcm => types::Operator::Function(types::Function::Lcm),
+        Rule::multinomial => types::Operator::Function(types::Function::Multinomial),
+        Rule::norm_dist => types::Operator::Function(types::Function::NormDist),
+        Rule::norm_inv => types::Operator::Function(types::Function::NormInv),
+        Rule::norm_s_dist => types::Operator::Function(types::Function::NormSDist),
+        Rule::t_dist => types::Operator::Function(types::Function::TDist),
+        Rule::t_inv => types::Operator::Function(types::Function::TInv),
+        Rule::chisq_dist => types::Operator::Function(types::Function::ChisqDist),
+        Rule::binom_dist => types::Operator::Function(types::Function::BinomDist),
+        Rule::poisson_dist => types::Operator::Function(types::Function::PoissonDist),
+        Rule::expon_dist => types::Operator::Function(types::Function::ExponDist),
+        Rule::gamma => types::Operator::Function(types::Function::Gamma),
+        Rule::gammaln => types::Operator::Function(types::Function::GammaLn),
+        Rule::confidence_norm => types::Operator::Function(types::Function::ConfidenceNorm),
         _ => unreachable!(),
     }
 }
@@ -406,6 +645,7 @@ where
             Rule::reference => build_formula_reference(pair),
             Rule::iterator => build_formula_iterator(pair, f),
             Rule::negate => build_formula_unary_operator(Rule::negate, pair, f),
+            Rule::intersection => build_formula_unary_operator(Rule::intersection, pair, f),
             Rule::expr => build_formula_with_parser(pair.into_inner(), f),
             Rule::days => build_formula_collective_operator(Rule::days, pair, f),
             Rule::year => build_formula_collective_operator(Rule::year, pair, f),
@@ -420,6 +660,137 @@ where
             Rule::search => build_formula_collective_operator(Rule::search, pair, f),
             Rule::iserror => build_formula_collective_operator(Rule::iserror, pair, f),
             Rule::blank_func => build_formula_blank_func(),
+            Rule::power_function => {
+                build_formula_collective_operator(Rule::power_function, pair, f)
+            }
+            Rule::sqrt => build_formula_collective_operator(Rule::sqrt, pair, f),
+            Rule::ln => build_formula_collective_operator(Rule::ln, pair, f),
+            Rule::text => build_formula_collective_operator(Rule::text, pair, f),
+            Rule::value => build_formula_collective_operator(Rule::value, pair, f),
+            Rule::numbervalue => build_formula_collective_operator(Rule::numbervalue, pair, f),
+            Rule::round => build_formula_collective_operator(Rule::round, pair, f),
+            Rule::roundup => build_formula_collective_operator(Rule::roundup, pair, f),
+            Rule::rounddown => build_formula_collective_operator(Rule::rounddown, pair, f),
+            Rule::mround => build_formula_collective_operator(Rule::mround, pair, f),
+            Rule::ceiling => build_formula_collective_operator(Rule::ceiling, pair, f),
+            Rule::ceiling_math => build_formula_collective_operator(Rule::ceiling_math, pair, f),
+            Rule::floor => build_formula_collective_operator(Rule::floor, pair, f),
+            Rule::floor_math => build_formula_collective_operator(Rule::floor_math, pair, f),
+            Rule::int_function => build_formula_collective_operator(Rule::int_function, pair, f),
+            Rule::trunc => build_formula_collective_operator(Rule::trunc, pair, f),
+            Rule::mod_function => build_formula_collective_operator(Rule::mod_function, pair, f),
+            Rule::quotient => build_formula_collective_operator(Rule::quotient, pair, f),
+            Rule::exp => build_formula_collective_operator(Rule::exp, pair, f),
+            Rule::log => build_formula_collective_operator(Rule::log, pair, f),
+            Rule::log10 => build_formula_collective_operator(Rule::log10, pair, f),
+            Rule::pi => build_formula_function_without_args(Rule::pi),
+            Rule::sign => build_formula_collective_operator(Rule::sign, pair, f),
+            Rule::sin => build_formula_collective_operator(Rule::sin, pair, f),
+            Rule::cos => build_formula_collective_operator(Rule::cos, pair, f),
+            Rule::tan => build_formula_collective_operator(Rule::tan, pair, f),
+            Rule::asin => build_formula_collective_operator(Rule::asin, pair, f),
+            Rule::acos => build_formula_collective_operator(Rule::acos, pair, f),
+            Rule::atan => build_formula_collective_operator(Rule::atan, pair, f),
+            Rule::atan2 => build_formula_collective_operator(Rule::atan2, pair, f),
+            Rule::degrees => build_formula_collective_operator(Rule::degrees, pair, f),
+            Rule::radians => build_formula_collective_operator(Rule::radians, pair, f),
+            Rule::sinh => build_formula_collective_operator(Rule::sinh, pair, f),
+            Rule::cosh => build_formula_collective_operator(Rule::cosh, pair, f),
+            Rule::tanh => build_formula_collective_operator(Rule::tanh, pair, f),
+            Rule::asinh => build_formula_collective_operator(Rule::asinh, pair, f),
+            Rule::acosh => build_formula_collective_operator(Rule::acosh, pair, f),
+            Rule::atanh => build_formula_collective_operator(Rule::atanh, pair, f),
+            Rule::min => build_formula_collective_operator(Rule::min, pair, f),
+            Rule::max => build_formula_collective_operator(Rule::max, pair, f),
+            Rule::count => build_formula_collective_operator(Rule::count, pair, f),
+            Rule::counta => build_formula_collective_operator(Rule::counta, pair, f),
+            Rule::countblank => build_formula_collective_operator(Rule::countblank, pair, f),
+            Rule::median => build_formula_collective_operator(Rule::median, pair, f),
+            Rule::mode => build_formula_collective_operator(Rule::mode, pair, f),
+            Rule::mode_sngl => build_formula_collective_operator(Rule::mode_sngl, pair, f),
+            Rule::stdev => build_formula_collective_operator(Rule::stdev, pair, f),
+            Rule::stdev_s => build_formula_collective_operator(Rule::stdev_s, pair, f),
+            Rule::stdev_p => build_formula_collective_operator(Rule::stdev_p, pair, f),
+            Rule::var => build_formula_collective_operator(Rule::var, pair, f),
+            Rule::var_s => build_formula_collective_operator(Rule::var_s, pair, f),
+            Rule::var_p => build_formula_collective_operator(Rule::var_p, pair, f),
+            Rule::large => build_formula_collective_operator(Rule::large, pair, f),
+            Rule::small => build_formula_collective_operator(Rule::small, pair, f),
+            Rule::rank => build_formula_collective_operator(Rule::rank, pair, f),
+            Rule::rank_eq => build_formula_collective_operator(Rule::rank_eq, pair, f),
+            Rule::rank_avg => build_formula_collective_operator(Rule::rank_avg, pair, f),
+            Rule::percentile => build_formula_collective_operator(Rule::percentile, pair, f),
+            Rule::percentile_inc => {
+                build_formula_collective_operator(Rule::percentile_inc, pair, f)
+            }
+            Rule::percentile_exc => {
+                build_formula_collective_operator(Rule::percentile_exc, pair, f)
+            }
+            Rule::quartile => build_formula_collective_operator(Rule::quartile, pair, f),
+            Rule::quartile_inc => build_formula_collective_operator(Rule::quartile_inc, pair, f),
+            Rule::quartile_exc => build_formula_collective_operator(Rule::quartile_exc, pair, f),
+            Rule::countif => build_formula_collective_operator(Rule::countif, pair, f),
+            Rule::countifs => build_formula_collective_operator(Rule::countifs, pair, f),
+            Rule::sumif => build_formula_collective_operator(Rule::sumif, pair, f),
+            Rule::sumifs => build_formula_collective_operator(Rule::sumifs, pair, f),
+            Rule::averageif => build_formula_collective_operator(Rule::averageif, pair, f),
+            Rule::averageifs => build_formula_collective_operator(Rule::averageifs, pair, f),
+            Rule::maxifs => build_formula_collective_operator(Rule::maxifs, pair, f),
+            Rule::minifs => build_formula_collective_operator(Rule::minifs, pair, f),
+            Rule::sumproduct => build_formula_collective_operator(Rule::sumproduct, pair, f),
+            Rule::mmult => build_formula_collective_operator(Rule::mmult, pair, f),
+            Rule::minverse => build_formula_collective_operator(Rule::minverse, pair, f),
+            Rule::mdeterm => build_formula_collective_operator(Rule::mdeterm, pair, f),
+            Rule::transpose => build_formula_collective_operator(Rule::transpose, pair, f),
+            Rule::pmt => build_formula_collective_operator(Rule::pmt, pair, f),
+            Rule::ipmt => build_formula_collective_operator(Rule::ipmt, pair, f),
+            Rule::ppmt => build_formula_collective_operator(Rule::ppmt, pair, f),
+            Rule::pv => build_formula_collective_operator(Rule::pv, pair, f),
+            Rule::fv => build_formula_collective_operator(Rule::fv, pair, f),
+            Rule::nper => build_formula_collective_operator(Rule::nper, pair, f),
+            Rule::rate => build_formula_collective_operator(Rule::rate, pair, f),
+            Rule::npv => build_formula_collective_operator(Rule::npv, pair, f),
+            Rule::irr => build_formula_collective_operator(Rule::irr, pair, f),
+            Rule::xnpv => build_formula_collective_operator(Rule::xnpv, pair, f),
+            Rule::xirr => build_formula_collective_operator(Rule::xirr, pair, f),
+            Rule::rand => build_formula_function_without_args(Rule::rand),
+            Rule::randbetween => build_formula_collective_operator(Rule::randbetween, pair, f),
+            Rule::randarray => build_formula_collective_operator(Rule::randarray, pair, f),
+            Rule::dec2bin => build_formula_collective_operator(Rule::dec2bin, pair, f),
+            Rule::dec2hex => build_formula_collective_operator(Rule::dec2hex, pair, f),
+            Rule::dec2oct => build_formula_collective_operator(Rule::dec2oct, pair, f),
+            Rule::bin2dec => build_formula_collective_operator(Rule::bin2dec, pair, f),
+            Rule::hex2dec => build_formula_collective_operator(Rule::hex2dec, pair, f),
+            Rule::oct2dec => build_formula_collective_operator(Rule::oct2dec, pair, f),
+            Rule::base => build_formula_collective_operator(Rule::base, pair, f),
+            Rule::decimal => build_formula_collective_operator(Rule::decimal, pair, f),
+            Rule::bitand => build_formula_collective_operator(Rule::bitand, pair, f),
+            Rule::bitor => build_formula_collective_operator(Rule::bitor, pair, f),
+            Rule::bitxor => build_formula_collective_operator(Rule::bitxor, pair, f),
+            Rule::bitlshift => build_formula_collective_operator(Rule::bitlshift, pair, f),
+            Rule::bitrshift => build_formula_collective_operator(Rule::bitrshift, pair, f),
+            Rule::fact => build_formula_collective_operator(Rule::fact, pair, f),
+            Rule::factdouble => build_formula_collective_operator(Rule::factdouble, pair, f),
+            Rule::combin => build_formula_collective_operator(Rule::combin, pair, f),
+            Rule::combina => build_formula_collective_operator(Rule::combina, pair, f),
+            Rule::permut => build_formula_collective_operator(Rule::permut, pair, f),
+            Rule::gcd => build_formula_collective_operator(Rule::gcd, pair, f),
+            Rule::lcm => build_formula_collective_operator(Rule::lcm, pair, f),
+            Rule::multinomial => build_formula_collective_operator(Rule::multinomial, pair, f),
+            Rule::norm_dist => build_formula_collective_operator(Rule::norm_dist, pair, f),
+            Rule::norm_inv => build_formula_collective_operator(Rule::norm_inv, pair, f),
+            Rule::norm_s_dist => build_formula_collective_operator(Rule::norm_s_dist, pair, f),
+            Rule::t_dist => build_formula_collective_operator(Rule::t_dist, pair, f),
+            Rule::t_inv => build_formula_collective_operator(Rule::t_inv, pair, f),
+            Rule::chisq_dist => build_formula_collective_operator(Rule::chisq_dist, pair, f),
+            Rule::binom_dist => build_formula_collective_operator(Rule::binom_dist, pair, f),
+            Rule::poisson_dist => build_formula_collective_operator(Rule::poisson_dist, pair, f),
+            Rule::expon_dist => build_formula_collective_operator(Rule::expon_dist, pair, f),
+            Rule::gamma => build_formula_collective_operator(Rule::gamma, pair, f),
+            Rule::gammaln => build_formula_collective_operator(Rule::gammaln, pair, f),
+            Rule::confidence_norm => {
+                build_formula_collective_operator(Rule::confidence_norm, pair, f)
+            }
             _ => unreachable!(),
         })
         .map_infix(
@@ -1,12 +1,16 @@
 use chrono::{DateTime, FixedOffset};
-use num_traits::{AsPrimitive, Float, FromPrimitive};
+use num_traits::{FromPrimitive, Signed, ToPrimitive};
+#[cfg(feature = "decimal")]
+use rust_decimal::MathematicalOps;
 use std::{
     fmt::{Debug, Display},
     str::FromStr,
+    sync::{atomic::AtomicBool, Arc},
 };
 
-/// Defines Excel Functions.
-#[derive(Debug, Copy, Clone)]
+/// Defines Excel Functions. Displays as the name of the function in a formula.
+#[derive(Debug, Copy, Clone, strum::Display)]
+#[strum(serialize_all = "UPPERCASE")]
 pub enum Function {
     Abs,
     Sum,
@@ -16,10 +20,14 @@ pub enum Function {
     And,
     Xor,
     Not,
+    #[strum(to_string = "-")]
     Negate,
+    #[strum(to_string = "@")]
+    ImplicitIntersection,
     Days,
     Right,
     Left,
+    #[strum(to_string = "IF")]
     Iff,
     IsBlank,
     Year,
@@ -29,6 +37,214 @@ pub enum Function {
     Search,
     IsError,
     Blank,
+    Power,
+    Sqrt,
+    Ln,
+    Text,
+    Value,
+    NumberValue,
+    Round,
+    RoundUp,
+    RoundDown,
+    MRound,
+    Ceiling,
+    #[strum(to_string = "CEILING.MATH")]
+    CeilingMath,
+    Floor,
+    #[strum(to_string = "FLOOR.MATH")]
+    FloorMath,
+    Int,
+    Trunc,
+    Mod,
+    Quotient,
+    Exp,
+    Log,
+    Log10,
+    Pi,
+    Sign,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Degrees,
+    Radians,
+    Sinh,
+    Cosh,
+    Tanh,
+    Asinh,
+    Acosh,
+    Atanh,
+    Min,
+    Max,
+    Count,
+    CountA,
+    CountBlank,
+    Median,
+    Mode,
+    #[strum(to_string = "MODE.SNGL")]
+    ModeSngl,
+    Stdev,
+    #[strum(to_string = "STDEV.S")]
+    StdevS,
+    #[strum(to_string = "STDEV.P")]
+    StdevP,
+    Var,
+    #[strum(to_string = "VAR.S")]
+    VarS,
+    #[strum(to_string = "VAR.P")]
+    VarP,
+    Large,
+    Small,
+    Rank,
+    #[strum(to_string = "RANK.EQ")]
+    RankEq,
+    #[strum(to_string = "RANK.AVG")]
+    RankAvg,
+    Percentile,
+    #[strum(to_string = "PERCENTILE.INC")]
+    PercentileInc,
+    #[strum(to_string = "PERCENTILE.EXC")]
+    PercentileExc,
+    Quartile,
+    #[strum(to_string = "QUARTILE.INC")]
+    QuartileInc,
+    #[strum(to_string = "QUARTILE.EXC")]
+    QuartileExc,
+    CountIf,
+    CountIfs,
+    SumIf,
+    SumIfs,
+    AverageIf,
+    AverageIfs,
+    MaxIfs,
+    MinIfs,
+    SumProduct,
+    MMult,
+    MInverse,
+    MDeterm,
+    Transpose,
+    Pmt,
+    Ipmt,
+    Ppmt,
+    Pv,
+    Fv,
+    Nper,
+    Rate,
+    Npv,
+    Irr,
+    Xnpv,
+    Xirr,
+    Rand,
+    RandBetween,
+    RandArray,
+    Dec2Bin,
+    Dec2Hex,
+    Dec2Oct,
+    Bin2Dec,
+    Hex2Dec,
+    Oct2Dec,
+    Base,
+    Decimal,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitLShift,
+    BitRShift,
+    Fact,
+    FactDouble,
+    Combin,
+    CombinA,
+    Permut,
+    Gcd,
+    Lcm,
+    Multinomial,
+    #[strum(to_string = "NORM.DIST")]
+    NormDist,
+    #[strum(to_string = "NORM.INV")]
+    NormInv,
+    #[strum(to_string = "NORM.S.DIST")]
+    NormSDist,
+    #[strum(to_string = "T.DIST")]
+    TDist,
+    #[strum(to_string = "T.INV")]
+    TInv,
+    #[strum(to_string = "CHISQ.DIST")]
+    ChisqDist,
+    #[strum(to_string = "BINOM.DIST")]
+    BinomDist,
+    #[strum(to_string = "POISSON.DIST")]
+    PoissonDist,
+    #[strum(to_string = "EXPON.DIST")]
+    ExponDist,
+    Gamma,
+    GammaLn,
+    #[strum(to_string = "CONFIDENCE.NORM")]
+    ConfidenceNorm,
+}
+
+impl Function {
+    /// Returns true for functions that take lists as arguments, like aggregates. Range
+    /// arguments of other functions are intersected in legacy formulas.
+    pub fn takes_lists(self) -> bool {
+        matches!(
+            self,
+            Function::Sum
+                | Function::Product
+                | Function::Average
+                | Function::Or
+                | Function::And
+                | Function::Xor
+                | Function::SumProduct
+                | Function::MMult
+                | Function::MInverse
+                | Function::MDeterm
+                | Function::Transpose
+                | Function::Npv
+                | Function::Irr
+                | Function::Xnpv
+                | Function::Xirr
+                | Function::Gcd
+                | Function::Lcm
+                | Function::Multinomial
+                | Function::CountIf
+                | Function::CountIfs
+                | Function::SumIf
+                | Function::SumIfs
+                | Function::AverageIf
+                | Function::AverageIfs
+                | Function::MaxIfs
+                | Function::MinIfs
+                | Function::Min
+                | Function::Max
+                | Function::Count
+                | Function::CountA
+                | Function::CountBlank
+                | Function::Median
+                | Function::Mode
+                | Function::ModeSngl
+                | Function::Stdev
+                | Function::StdevS
+                | Function::StdevP
+                | Function::Var
+                | Function::VarS
+                | Function::VarP
+                | Function::Large
+                | Function::Small
+                | Function::Rank
+                | Function::RankEq
+                | Function::RankAvg
+                | Function::Percentile
+                | Function::PercentileInc
+                | Function::PercentileExc
+                | Function::Quartile
+                | Function::QuartileInc
+                | Function::QuartileExc
+                | Function::ImplicitIntersection
+        )
+    }
 }
 
 /// Defines Excel Operators.
@@ -64,6 +280,14 @@ pub enum Error {
     Argument,
     #[strum(to_string = "#REF!")]
     Reference,
+    #[strum(to_string = "#N/A")]
+    NotAvailable,
+    #[strum(to_string = "#NUM!")]
+    Num,
+    #[strum(to_string = "#LIMIT!")]
+    Limit,
+    #[strum(to_string = "#CANCEL!")]
+    Cancelled,
 }
 
 /// Defines boolean types.
@@ -93,20 +317,166 @@ impl From<Boolean> for bool {
     }
 }
 
+/// The number type of the values of formulas. Operations that have no result in the number
+/// type, like the square root of a negative number or an overflow, return `None`, which
+/// evaluates to #NUM!.
 pub trait XlNum:
-    Float
-    + AsPrimitive<i64>
-    + AsPrimitive<i32>
-    + AsPrimitive<usize>
-    + FromPrimitive
-    + FromStr
-    + Debug
-    + Display
+    Signed + PartialOrd + Copy + FromPrimitive + ToPrimitive + FromStr + Debug + Display
 {
+    /// Number of significant digits shown when a number is converted to text. Excel shows
+    /// 15, which is also the precision of `f64`.
+    const SIGNIFICANT_DIGITS: usize;
+    /// Converts to an integer like the `as` operator: truncating, saturating and NaN as 0.
+    fn as_i64(self) -> i64;
+    /// Removes the fractional part.
+    fn trunc(self) -> Self;
+    fn is_infinite(self) -> bool;
+    fn is_nan(self) -> bool;
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_pow(self, exponent: Self) -> Option<Self>;
+    fn checked_sqrt(self) -> Option<Self>;
+    /// Natural logarithm.
+    fn checked_ln(self) -> Option<Self>;
+    fn checked_exp(self) -> Option<Self>;
+}
+
+/// Floating point arithmetic never fails, but results in infinity or NaN instead. Only
+/// NaN is returned as `None`.
+macro_rules! impl_xl_num_for_float {
+    ($float:ty, $digits:expr) => {
+        impl XlNum for $float {
+            const SIGNIFICANT_DIGITS: usize = $digits;
+
+            fn as_i64(self) -> i64 {
+                self as i64
+            }
+
+            fn trunc(self) -> Self {
+                <$float>::trunc(self)
+            }
+
+            fn is_infinite(self) -> bool {
+                <$float>::is_infinite(self)
+            }
+
+            fn is_nan(self) -> bool {
+                <$float>::is_nan(self)
+            }
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                Some(self + rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                Some(self - rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                Some(self * rhs)
+            }
+
+            fn checked_div(self, rhs: Self) -> Option<Self> {
+                Some(self / rhs)
+            }
+
+            fn checked_pow(self, exponent: Self) -> Option<Self> {
+                let result = if exponent.fract() == 0.0 {
+                    self.powi(exponent as i32)
+                } else {
+                    self.powf(exponent)
+                };
+                Some(result).filter(|result| !result.is_nan())
+            }
+
+            fn checked_sqrt(self) -> Option<Self> {
+                Some(self.sqrt()).filter(|result| !result.is_nan())
+            }
+
+            fn checked_ln(self) -> Option<Self> {
+                Some(self).filter(|number| *number > 0.0).map(<$float>::ln)
+            }
+
+            fn checked_exp(self) -> Option<Self> {
+                Some(self.exp())
+            }
+        }
+    };
 }
 
-impl XlNum for f32 {}
-impl XlNum for f64 {}
+impl_xl_num_for_float!(f32, 7);
+impl_xl_num_for_float!(f64, 15);
+
+/// Exact decimal arithmetic with 28 significant digits. Results that do not fit, e.g. of
+/// an overflow, evaluate to #NUM!.
+#[cfg(feature = "decimal")]
+impl XlNum for rust_decimal::Decimal {
+    const SIGNIFICANT_DIGITS: usize = 15;
+
+    fn as_i64(self) -> i64 {
+        self.trunc().to_i64().unwrap_or(if self.is_sign_negative() {
+            i64::MIN
+        } else {
+            i64::MAX
+        })
+    }
+
+    fn trunc(self) -> Self {
+        rust_decimal::Decimal::trunc(&self)
+    }
+
+    fn is_infinite(self) -> bool {
+        false
+    }
+
+    fn is_nan(self) -> bool {
+        false
+    }
+
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_add(self, rhs)
+    }
+
+    fn checked_sub(self, rhs: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_sub(self, rhs)
+    }
+
+    fn checked_mul(self, rhs: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_mul(self, rhs)
+    }
+
+    fn checked_div(self, rhs: Self) -> Option<Self> {
+        rust_decimal::Decimal::checked_div(self, rhs)
+    }
+
+    fn checked_pow(self, exponent: Self) -> Option<Self> {
+        if exponent.fract().is_zero() {
+            self.checked_powi(exponent.to_i64()?)
+        } else if self.is_sign_negative() {
+            None
+        } else {
+            self.checked_powd(exponent)
+        }
+    }
+
+    fn checked_sqrt(self) -> Option<Self> {
+        rust_decimal::MathematicalOps::sqrt(&self)
+    }
+
+    fn checked_ln(self) -> Option<Self> {
+        if self.is_sign_positive() && !self.is_zero() {
+            rust_decimal::MathematicalOps::checked_ln(&self)
+        } else {
+            None
+        }
+    }
+
+    fn checked_exp(self) -> Option<Self> {
+        rust_decimal::MathematicalOps::checked_exp(&self)
+    }
+}
 
 /// The result of an evaluation.
 #[derive(Debug, Clone, PartialEq)]
@@ -144,3 +514,36 @@ where
     pub op: Operator,
     pub values: Vec<Formula<N>>,
 }
+
+/// Limits for parsing and evaluating formulas from untrusted sources. `None` means unlimited.
+/// Exceeding a limit results in #LIMIT!, setting the cancellation flag in #CANCEL!.
+#[derive(Debug, Clone)]
+pub struct Limits {
+    /// Maximum number of characters of a formula string.
+    pub max_formula_length: Option<usize>,
+    /// Maximum nesting of parentheses and braces in a formula string, and of functions and
+    /// lists in a parsed formula. Unlike Excel's limit of 64 nested functions, operators count
+    /// as a quarter of a level to bound chains of them, so the default is higher.
+    pub max_nesting_depth: Option<usize>,
+    /// Maximum number of characters of a text value.
+    pub max_text_length: Option<usize>,
+    /// Maximum number of elements of a list value, including nested lists.
+    pub max_array_elements: Option<usize>,
+    /// Maximum number of sub-expressions evaluated, including referenced formulas.
+    pub max_evaluation_steps: Option<usize>,
+    /// Stops the evaluation once set, e.g. from another thread.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_formula_length: Some(8192),
+            max_nesting_depth: Some(256),
+            max_text_length: Some(32767),
+            max_array_elements: Some(1_048_576),
+            max_evaluation_steps: None,
+            cancel: None,
+        }
+    }
+}
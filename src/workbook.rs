@@ -0,0 +1,482 @@
+use crate::{
+    calculate::{
+        calculate_evaluated_formula, calculate_formula_with_cache, formula_references,
+        RandomSource, ReferenceCache,
+    },
+    intersection::{parse_range, CellPosition},
+    types::{self, XlNum},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Debug,
+    str::FromStr,
+};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Ranges with more cells are #LIMIT! instead of an array that does not fit into memory.
+const MAX_RANGE_CELLS: usize = 1_048_576;
+
+/// Cells of a workbook grouped by the order in which they can be evaluated.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DependencyLevels {
+    /// Each level only depends on cells of earlier levels, so the cells within
+    /// a level are independent of each other.
+    pub levels: Vec<Vec<String>>,
+    /// Cells that are part of, or depend on, a circular reference.
+    pub circular: Vec<String>,
+}
+
+/// The cells of a workbook named like `A1` by row and column, built once per recalculation so
+/// that range references are resolved without parsing every cell name again.
+struct CellIndex<'a> {
+    positions: BTreeMap<(usize, usize), &'a String>,
+}
+
+impl<'a> CellIndex<'a> {
+    fn new<N>(cells: &'a BTreeMap<String, types::Formula<N>>) -> Self
+    where
+        N: XlNum,
+    {
+        let positions = cells
+            .keys()
+            .filter_map(|name| {
+                CellPosition::parse(name)
+                    .filter(|position| position.name() == *name)
+                    .map(|position| ((position.row, position.column), name))
+            })
+            .collect();
+        CellIndex { positions }
+    }
+
+    /// The names of the cells within a range, by row.
+    fn range(
+        &self,
+        (start, end): (CellPosition, CellPosition),
+    ) -> impl Iterator<Item = &'a String> + '_ {
+        self.positions
+            .range((start.row, start.column)..=(end.row, end.column))
+            .filter(move |((_, column), _)| (start.column..=end.column).contains(column))
+            .map(|(_, name)| *name)
+    }
+}
+
+/// Settings for the iterative calculation of circular references. The defaults are the ones
+/// of Excel: at most 100 iterations and a maximum change of 0.001.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterationSettings<N>
+where
+    N: XlNum,
+{
+    pub max_iterations: usize,
+    pub max_change: N,
+}
+
+impl<N> Default for IterationSettings<N>
+where
+    N: XlNum,
+{
+    fn default() -> Self {
+        IterationSettings {
+            max_iterations: 100,
+            max_change: N::from_f64(0.001).unwrap(),
+        }
+    }
+}
+
+/// The result of an iterative recalculation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IterativeResults<N>
+where
+    N: XlNum,
+{
+    pub values: BTreeMap<String, types::Value<N>>,
+    /// Number of passes over the circular cells.
+    pub iterations: usize,
+    /// Whether the circular cells changed by at most `max_change` in the last pass.
+    /// Always true if the workbook has no circular references.
+    pub converged: bool,
+}
+
+/// A set of named formulas that reference each other by name. A range reference like `A1:B10`
+/// refers to the cells of the workbook named like `A1` within the range, and evaluates to an
+/// array of their values, with blanks for the other cells of the range. Ranges without cells
+/// of the workbook are resolved with the closure like other references.
+#[derive(Debug, Clone)]
+pub struct Workbook<N>
+where
+    N: XlNum,
+{
+    cells: BTreeMap<String, types::Formula<N>>,
+}
+
+impl<N> Default for Workbook<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N> Workbook<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    pub fn new() -> Self {
+        Workbook {
+            cells: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a cell, returning the formula it replaced.
+    pub fn insert(&mut self, name: &str, formula: types::Formula<N>) -> Option<types::Formula<N>> {
+        self.cells.insert(name.to_string(), formula)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&types::Formula<N>> {
+        self.cells.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns the names of the cells of this workbook that the given cell references, directly
+    /// or within a range.
+    pub fn precedents(&self, name: &str) -> BTreeSet<String> {
+        self.indexed_precedents(name, &CellIndex::new(&self.cells))
+    }
+
+    fn indexed_precedents(&self, name: &str, index: &CellIndex) -> BTreeSet<String> {
+        match self.cells.get(name) {
+            Some(formula) => formula_references(formula)
+                .iter()
+                .flat_map(|reference| self.referenced_cells(reference, index))
+                .collect(),
+            None => BTreeSet::new(),
+        }
+    }
+
+    /// The cells of this workbook a reference refers to: the cell of that name, or the cells
+    /// within a range.
+    fn referenced_cells(&self, reference: &str, index: &CellIndex) -> Vec<String> {
+        if self.cells.contains_key(reference) {
+            return vec![reference.to_string()];
+        }
+        match parse_range(reference) {
+            Some(range) => index.range(range).cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Replaces the range references that refer to cells of this workbook with the array of
+    /// the values of the range, so that they are evaluated like other cells of the workbook.
+    fn expand_ranges(
+        &self,
+        formula: types::Formula<N>,
+        results: &BTreeMap<String, types::Value<N>>,
+        index: &CellIndex,
+    ) -> types::Formula<N> {
+        match formula {
+            types::Formula::Operation(exp) => types::Formula::Operation(types::Expression {
+                op: exp.op,
+                values: exp
+                    .values
+                    .into_iter()
+                    .map(|value| self.expand_ranges(value, results, index))
+                    .collect(),
+            }),
+            types::Formula::Iterator(vec) => types::Formula::Iterator(
+                vec.into_iter()
+                    .map(|value| self.expand_ranges(value, results, index))
+                    .collect(),
+            ),
+            types::Formula::Reference(name) if !self.cells.contains_key(&name) => {
+                match parse_range(&name) {
+                    Some(range) if index.range(range).next().is_some() => {
+                        types::Formula::Value(range_value(range, results))
+                    }
+                    _ => types::Formula::Reference(name),
+                }
+            }
+            formula => formula,
+        }
+    }
+
+    /// Groups the cells into levels of independent cells. Names within a level are sorted.
+    pub fn dependency_levels(&self) -> DependencyLevels {
+        self.indexed_dependency_levels(&CellIndex::new(&self.cells))
+    }
+
+    /// Kahn's algorithm: a cell joins the level after the last of its precedents.
+    fn indexed_dependency_levels(&self, index: &CellIndex) -> DependencyLevels {
+        let mut in_degrees: BTreeMap<&String, usize> = BTreeMap::new();
+        let mut dependents: HashMap<String, Vec<&String>> = HashMap::new();
+        for name in self.cells.keys() {
+            let precedents = self.indexed_precedents(name, index);
+            in_degrees.insert(name, precedents.len());
+            for precedent in precedents {
+                dependents.entry(precedent).or_default().push(name);
+            }
+        }
+        let mut levels = Vec::new();
+        let mut level: Vec<&String> = in_degrees
+            .iter()
+            .filter(|(_, in_degree)| **in_degree == 0)
+            .map(|(name, _)| *name)
+            .collect();
+        while !level.is_empty() {
+            let mut next_level = Vec::new();
+            for name in &level {
+                for dependent in dependents.get(*name).into_iter().flatten() {
+                    let in_degree = in_degrees.get_mut(dependent).unwrap();
+                    *in_degree -= 1;
+                    if *in_degree == 0 {
+                        next_level.push(*dependent);
+                    }
+                }
+            }
+            next_level.sort();
+            levels.push(level.into_iter().cloned().collect());
+            level = next_level;
+        }
+        DependencyLevels {
+            levels,
+            circular: in_degrees
+                .into_iter()
+                .filter(|(_, in_degree)| *in_degree > 0)
+                .map(|(name, _)| name.clone())
+                .collect(),
+        }
+    }
+
+    /// Evaluates every cell in dependency order. References to names that are not cells
+    /// of the workbook are resolved with the optional closure. Cells on a circular
+    /// reference evaluate to #REF!.
+    pub fn recalculate(
+        &self,
+        f: Option<&impl Fn(String) -> types::Value<N>>,
+    ) -> BTreeMap<String, types::Value<N>> {
+        self.recalculate_in_order(f, None)
+    }
+
+    /// Same as `recalculate`, but draws the random numbers of RAND, RANDBETWEEN and RANDARRAY
+    /// from the random source, cell by cell in dependency order and by name within a level.
+    /// With a seeded `DefaultRandom` the results are the same on every recalculation.
+    pub fn recalculate_with_random(
+        &self,
+        f: Option<&impl Fn(String) -> types::Value<N>>,
+        random: &mut impl RandomSource,
+    ) -> BTreeMap<String, types::Value<N>> {
+        self.recalculate_in_order(f, Some(random))
+    }
+
+    fn recalculate_in_order(
+        &self,
+        f: Option<&impl Fn(String) -> types::Value<N>>,
+        mut random: Option<&mut dyn RandomSource>,
+    ) -> BTreeMap<String, types::Value<N>> {
+        let index = CellIndex::new(&self.cells);
+        let dependency_levels = self.indexed_dependency_levels(&index);
+        let mut results = BTreeMap::new();
+        for level in dependency_levels.levels {
+            for name in level {
+                let random = random
+                    .as_mut()
+                    .map(|random| &mut **random as &mut dyn RandomSource);
+                let value = self.calculate_cell(&name, &results, f, &index, random);
+                results.insert(name, value);
+            }
+        }
+        insert_circular(&mut results, dependency_levels.circular);
+        results
+    }
+
+    /// Same as `recalculate`, but cells on a circular reference are calculated iteratively
+    /// like Excel does when iterative calculation is enabled: they start at 0 and are
+    /// recalculated in name order, each pass using the latest values, until no number changes
+    /// by more than `max_change` or `max_iterations` passes were made.
+    pub fn recalculate_iterative(
+        &self,
+        f: Option<&impl Fn(String) -> types::Value<N>>,
+        settings: &IterationSettings<N>,
+    ) -> IterativeResults<N> {
+        let index = CellIndex::new(&self.cells);
+        let dependency_levels = self.indexed_dependency_levels(&index);
+        let mut results = BTreeMap::new();
+        for level in dependency_levels.levels {
+            for name in level {
+                let value = self.calculate_cell(&name, &results, f, &index, None);
+                results.insert(name, value);
+            }
+        }
+        let circular = dependency_levels.circular;
+        if circular.is_empty() {
+            return IterativeResults {
+                values: results,
+                iterations: 0,
+                converged: true,
+            };
+        }
+        for name in &circular {
+            results.insert(name.clone(), types::Value::Number(N::zero()));
+        }
+        let mut iterations = 0;
+        let mut converged = false;
+        while iterations < settings.max_iterations && !converged {
+            iterations += 1;
+            converged = true;
+            for name in &circular {
+                let value = self.calculate_cell(name, &results, f, &index, None);
+                if let Some(previous) = results.insert(name.clone(), value.clone()) {
+                    converged &= !value_changed(&previous, &value, settings.max_change);
+                }
+            }
+        }
+        IterativeResults {
+            values: results,
+            iterations,
+            converged,
+        }
+    }
+
+    /// Same as `recalculate`, but every reference is resolved and parsed only once during
+    /// the recalculation pass.
+    pub fn recalculate_with_cache(
+        &self,
+        f: Option<&impl Fn(String) -> types::Value<N>>,
+    ) -> BTreeMap<String, types::Value<N>> {
+        let index = CellIndex::new(&self.cells);
+        let dependency_levels = self.indexed_dependency_levels(&index);
+        let mut results = BTreeMap::new();
+        let mut cache = ReferenceCache::new();
+        for level in dependency_levels.levels {
+            for name in level {
+                let value = match self.cells.get(&name) {
+                    Some(formula) => calculate_formula_with_cache(
+                        self.expand_ranges(formula.clone(), &results, &index),
+                        f,
+                        &mut cache,
+                    ),
+                    None => types::Value::Error(types::Error::Reference),
+                };
+                // Later cells take the evaluated value from the cache as it is.
+                cache.insert(name.clone(), value.clone());
+                results.insert(name, value);
+            }
+        }
+        insert_circular(&mut results, dependency_levels.circular);
+        results
+    }
+
+    /// Same as `recalculate`, but the cells of each dependency level are evaluated on the
+    /// rayon thread pool. The results are identical to the ones of `recalculate`. Random
+    /// numbers come from the generator of each thread, see `recalculate_with_random`.
+    #[cfg(feature = "parallel")]
+    pub fn recalculate_parallel(
+        &self,
+        f: Option<&(impl Fn(String) -> types::Value<N> + Sync)>,
+    ) -> BTreeMap<String, types::Value<N>>
+    where
+        N: Send + Sync,
+    {
+        let index = CellIndex::new(&self.cells);
+        let dependency_levels = self.indexed_dependency_levels(&index);
+        let mut results = BTreeMap::new();
+        for level in dependency_levels.levels {
+            let values: Vec<(String, types::Value<N>)> = level
+                .into_par_iter()
+                .map(|name| {
+                    let value = self.calculate_cell(&name, &results, f, &index, None);
+                    (name, value)
+                })
+                .collect();
+            results.extend(values);
+        }
+        insert_circular(&mut results, dependency_levels.circular);
+        results
+    }
+
+    fn calculate_cell<'a>(
+        &self,
+        name: &str,
+        results: &'a BTreeMap<String, types::Value<N>>,
+        f: Option<&'a impl Fn(String) -> types::Value<N>>,
+        index: &CellIndex,
+        random: Option<&'a mut dyn RandomSource>,
+    ) -> types::Value<N> {
+        match self.cells.get(name) {
+            Some(formula) => calculate_evaluated_formula(
+                self.expand_ranges(formula.clone(), results, index),
+                results,
+                f,
+                random,
+            ),
+            None => types::Value::Error(types::Error::Reference),
+        }
+    }
+}
+
+/// The values of the cells of a range by row, a single row as a list like in array literals.
+fn range_value<N>(
+    (start, end): (CellPosition, CellPosition),
+    results: &BTreeMap<String, types::Value<N>>,
+) -> types::Value<N>
+where
+    N: XlNum,
+{
+    let (height, width) = (end.row - start.row + 1, end.column - start.column + 1);
+    if height
+        .checked_mul(width)
+        .is_none_or(|cells| cells > MAX_RANGE_CELLS)
+    {
+        return types::Value::Error(types::Error::Limit);
+    }
+    let mut rows: Vec<types::Value<N>> = (start.row..=end.row)
+        .map(|row| {
+            types::Value::Iterator(
+                (start.column..=end.column)
+                    .map(|column| {
+                        let name = CellPosition { row, column }.name();
+                        results.get(&name).cloned().unwrap_or(types::Value::Blank)
+                    })
+                    .collect(),
+            )
+        })
+        .collect();
+    if rows.len() == 1 {
+        rows.pop().unwrap()
+    } else {
+        types::Value::Iterator(rows)
+    }
+}
+
+fn value_changed<N>(previous: &types::Value<N>, value: &types::Value<N>, max_change: N) -> bool
+where
+    N: XlNum,
+{
+    match (previous, value) {
+        (types::Value::Number(previous), types::Value::Number(value)) => {
+            let change = (*value - *previous).abs();
+            change.is_nan() || change > max_change
+        }
+        _ => previous != value,
+    }
+}
+
+fn insert_circular<N>(results: &mut BTreeMap<String, types::Value<N>>, circular: Vec<String>)
+where
+    N: XlNum,
+{
+    for name in circular {
+        results.insert(name, types::Value::Error(types::Error::Reference));
+    }
+}
@@ -0,0 +1,142 @@
+use xlformula_engine::{
+    analyze::{analyze_formula, Analysis, Kinds, NoTypeHints},
+    calculate, parse_formula, types, NoCustomFunction,
+};
+
+fn analyze(s: &str) -> Analysis<f64> {
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    analyze_formula(&formula, None::<NoTypeHints>)
+}
+
+fn analyze_with_hints(s: &str) -> Analysis<f64> {
+    let hints = |name: String| match name.as_str() {
+        "N" => Kinds::NUMBER,
+        "T" => Kinds::TEXT,
+        "B" => Kinds::BOOLEAN,
+        "D" => Kinds::DATE,
+        _ => Kinds::ANY,
+    };
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    analyze_formula(&formula, Some(&hints))
+}
+
+fn diagnostics(analysis: &Analysis<f64>) -> Vec<String> {
+    analysis
+        .diagnostics
+        .iter()
+        .map(|diagnostic| calculate::formula_to_string(&diagnostic.expression))
+        .collect()
+}
+
+#[test]
+fn it_infers_kinds_of_constant_formulas() {
+    assert_eq!(analyze("=1+2").types.kinds, Kinds::NUMBER);
+    assert_eq!(analyze("=\"a\"&\"b\"").types.kinds, Kinds::TEXT);
+    assert_eq!(analyze("=1<2").types.kinds, Kinds::BOOLEAN);
+    assert_eq!(analyze("={1,2}").types.kinds, Kinds::ARRAY);
+    assert_eq!(analyze("=BLANK()").types.kinds, Kinds::BLANK);
+    assert_eq!(analyze("=1/0").types.kinds, Kinds::ERROR);
+}
+
+#[test]
+fn it_infers_kinds_of_every_argument() {
+    let analysis = analyze("=IF(1<2, \"a\", 3)");
+    assert_eq!(analysis.types.kinds, Kinds::TEXT);
+    let arguments: Vec<Kinds> = analysis
+        .types
+        .arguments
+        .iter()
+        .map(|argument| argument.kinds)
+        .collect();
+    assert_eq!(arguments, vec![Kinds::BOOLEAN, Kinds::TEXT, Kinds::NUMBER]);
+    assert_eq!(
+        analysis.types.arguments[0].arguments[1].kinds,
+        Kinds::NUMBER
+    );
+}
+
+#[test]
+fn it_uses_type_hints_for_references() {
+    assert_eq!(analyze_with_hints("=N*2").types.kinds, Kinds::NUMBER);
+    assert_eq!(
+        analyze_with_hints("=T*2").types.kinds,
+        Kinds::NUMBER | Kinds::ERROR
+    );
+    assert_eq!(analyze_with_hints("=N&T").types.kinds, Kinds::TEXT);
+    assert_eq!(analyze_with_hints("=YEAR(D)").types.kinds, Kinds::NUMBER);
+    assert_eq!(
+        analyze_with_hints("=IF(B, N, T)").types.kinds,
+        Kinds::NUMBER | Kinds::TEXT
+    );
+    assert!(analyze_with_hints("=X+1")
+        .types
+        .kinds
+        .contains(Kinds::ERROR));
+    assert!(analyze("=N+1").types.kinds.contains(Kinds::ERROR));
+}
+
+#[test]
+fn it_reports_guaranteed_errors() {
+    let analysis = analyze("=LEFT(5, \"a\") + TRUE");
+    assert_eq!(analysis.types.kinds, Kinds::ERROR);
+    assert_eq!(diagnostics(&analysis), vec!["LEFT(5,\"a\")+TRUE"]);
+    assert_eq!(analysis.diagnostics[0].error, Some(types::Error::Cast));
+
+    let analysis = analyze_with_hints("=N + (LEFT(5, \"a\") + TRUE) * 2");
+    assert_eq!(analysis.types.kinds, Kinds::ERROR);
+    assert_eq!(diagnostics(&analysis), vec!["LEFT(5,\"a\")+TRUE"]);
+
+    let analysis = analyze_with_hints("=SUM(N, \"a\" * 2)");
+    assert_eq!(diagnostics(&analysis), vec!["\"a\"*2"]);
+    assert_eq!(analysis.diagnostics[0].error, Some(types::Error::Cast));
+}
+
+#[test]
+fn it_reports_only_the_innermost_error() {
+    let analysis = analyze("=(1/0 + 1) * 2");
+    assert_eq!(diagnostics(&analysis), vec!["1/0"]);
+    assert_eq!(analysis.diagnostics[0].error, Some(types::Error::Div0));
+}
+
+#[test]
+fn it_does_not_report_possible_errors() {
+    assert!(analyze_with_hints("=T*2").diagnostics.is_empty());
+    assert!(analyze("=A+B").diagnostics.is_empty());
+    assert!(analyze_with_hints("=IF(B, 1/N, 1)").diagnostics.is_empty());
+    assert!(analyze("=A*B").diagnostics.is_empty());
+    assert!(analyze_with_hints("=LEFT(T, \"a\") * N")
+        .diagnostics
+        .is_empty());
+    assert!(analyze_with_hints("=D + B").diagnostics.is_empty());
+    assert!(analyze_with_hints("=FIND(\"x\", T)").diagnostics.is_empty());
+    assert!(analyze_with_hints("=SQRT(N - 10)").diagnostics.is_empty());
+    assert!(analyze("=SQRT(RAND() - 0.5)").diagnostics.is_empty());
+}
+
+#[test]
+fn it_does_not_evaluate_volatile_functions() {
+    assert_eq!(analyze("=RAND()").types.kinds, Kinds::NUMBER);
+    assert_eq!(
+        analyze("=RANDARRAY(1000, 1000)").types.kinds,
+        Kinds::ARRAY | Kinds::ERROR
+    );
+    assert_eq!(
+        analyze("=RANDBETWEEN(5, 1)").types.kinds,
+        Kinds::NUMBER | Kinds::ERROR
+    );
+    assert!(analyze("=RANDBETWEEN(5, 1)").diagnostics.is_empty());
+}
+
+#[test]
+fn it_infers_any_kind_for_many_unknown_arguments() {
+    assert_eq!(analyze("=SUM(A, B, C, D, E)").types.kinds, Kinds::ANY);
+}
+
+#[test]
+fn it_shows_kinds() {
+    assert_eq!(
+        format!("{:?}", Kinds::NUMBER | Kinds::TEXT),
+        "NUMBER | TEXT"
+    );
+    assert_eq!(format!("{:?}", Kinds::NONE), "NONE");
+}
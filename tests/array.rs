@@ -0,0 +1,90 @@
+mod common;
+
+use common::assert_all_types;
+use xlformula_engine::{calculate, parse_formula, types, NoCustomFunction};
+
+#[test]
+fn it_keeps_the_order_of_lists() {
+    assert_all_types("={1,2,3}", "{1,2,3}");
+    assert_all_types("={\"a\",TRUE,3}", "{a,TRUE,3}");
+    assert_all_types("=-{1,2,3}", "{-1,-2,-3}");
+    assert_all_types("=-(-{1,2,3})", "{1,2,3}");
+}
+
+#[test]
+fn it_broadcasts_scalars() {
+    assert_all_types("=1+{1,2,3}", "{2,3,4}");
+    assert_all_types("={1,2,3}+1", "{2,3,4}");
+    assert_all_types("=10-{1,2,3}", "{9,8,7}");
+    assert_all_types("={1,2,3}*2", "{2,4,6}");
+    assert_all_types("=6/{1,2,0}", "{6,3,#DIV/0!}");
+    assert_all_types("={1,2,3}^2", "{1,4,9}");
+    assert_all_types("={1,2}&\"a\"", "{1a,2a}");
+    assert_all_types("=\"a\"&{\"b\",\"c\"}", "{ab,ac}");
+}
+
+#[test]
+fn it_compares_element_wise() {
+    assert_all_types("={1,2,3}=2", "{FALSE,TRUE,FALSE}");
+    assert_all_types("={1,2,3}<>2", "{TRUE,FALSE,TRUE}");
+    assert_all_types("={1,2,3}>{0,5,1}", "{TRUE,FALSE,TRUE}");
+    assert_all_types("=2<={1,2,3}", "{FALSE,TRUE,TRUE}");
+    assert_all_types("={\"a\",\"b\"}=\"a\"", "{TRUE,FALSE}");
+}
+
+#[test]
+fn it_aggregates_element_wise_results() {
+    assert_all_types("=SUM({1,2,3}+1)", "9");
+    assert_all_types("=SUM({1,2,3}*{4,5,6})", "32");
+    assert_all_types("=AND({1,2}>0)", "TRUE");
+    assert_all_types("=OR({1,2}>5)", "FALSE");
+}
+
+#[test]
+fn it_parses_rows() {
+    assert_all_types("={1,2;3,4}", "{{1,2},{3,4}}");
+    assert_all_types("={1;2;3}", "{{1},{2},{3}}");
+    assert_all_types("=SUM({1,2;3,4})", "10");
+}
+
+#[test]
+fn it_broadcasts_rows_and_columns() {
+    assert_all_types("={1,2;3,4}+{10,20;30,40}", "{{11,22},{33,44}}");
+    assert_all_types("={1,2;3,4}*10", "{{10,20},{30,40}}");
+    assert_all_types("={1,2;3,4}*{10,100}", "{{10,200},{30,400}}");
+    assert_all_types("={1,2;3,4}*{10;100}", "{{10,20},{300,400}}");
+    assert_all_types("={1;2;3}+{10,20}", "{{11,21},{12,22},{13,23}}");
+}
+
+#[test]
+fn it_pads_mismatched_shapes_with_not_available() {
+    assert_all_types("={1,2}+{1,2,3}", "{2,4,#N/A}");
+    assert_all_types("={1;2}*{1;2;3}", "{{1},{4},{#N/A}}");
+    assert_all_types("={1,2,3;4,5,6}+{1,2}", "{{2,4,#N/A},{5,7,#N/A}}");
+    assert_all_types("={1,2;3,4}+{1,2;3,4;5,6}", "{{2,4},{6,8},{#N/A,#N/A}}");
+    assert_eq!(
+        common::evaluate_formula_value::<f64>("={1}+{1,2}"),
+        types::Value::Iterator(vec![types::Value::Number(2.0), types::Value::Number(3.0)])
+    );
+    assert_eq!(
+        common::evaluate_formula_value::<f64>("={1,2}+{1,2,3}"),
+        types::Value::Iterator(vec![
+            types::Value::Number(2.0),
+            types::Value::Number(4.0),
+            types::Value::Error(types::Error::NotAvailable)
+        ])
+    );
+}
+
+#[test]
+fn it_broadcasts_referenced_lists() {
+    let data = |s: String| match s.as_str() {
+        "L" => types::Value::Iterator(vec![types::Value::Number(1.0), types::Value::Number(2.0)]),
+        _ => types::Value::Error(types::Error::Value),
+    };
+    let formula = parse_formula::parse_string_to_formula("=L*3", None::<NoCustomFunction<f64>>);
+    assert_eq!(
+        calculate::result_to_string(calculate::calculate_formula(formula, Some(&data))),
+        "{3,6}"
+    );
+}
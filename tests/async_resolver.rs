@@ -0,0 +1,103 @@
+use futures::executor::block_on;
+use std::{collections::HashMap, sync::Mutex};
+use xlformula_engine::{
+    calculate::{self, AsyncResolver},
+    parse_formula, types, NoCustomFunction,
+};
+
+/// A key-value store that records every batch of names it is asked for.
+struct Store {
+    values: HashMap<String, types::Value<f64>>,
+    requests: Mutex<Vec<Vec<String>>>,
+}
+
+impl Store {
+    fn new() -> Store {
+        let values = [
+            ("A", types::Value::Number(3.0)),
+            ("B", types::Value::Text("=A*A+1".to_string())),
+            ("C", types::Value::Text("=B+D".to_string())),
+            ("D", types::Value::Number(1.0)),
+            ("Name", types::Value::Text("Test".to_string())),
+            ("Loop", types::Value::Text("=1+Loop".to_string())),
+        ];
+        Store {
+            values: values
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl AsyncResolver<f64> for Store {
+    async fn resolve(&self, names: Vec<String>) -> Vec<types::Value<f64>> {
+        self.requests.lock().unwrap().push(names.clone());
+        names
+            .iter()
+            .map(|name| {
+                self.values
+                    .get(name)
+                    .cloned()
+                    .unwrap_or(types::Value::Error(types::Error::Value))
+            })
+            .collect()
+    }
+}
+
+fn evaluate_async(s: &str, store: &Store) -> String {
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::result_to_string(block_on(calculate::calculate_formula_async(formula, store)))
+}
+
+#[test]
+fn it_evaluates_references_from_an_async_resolver() {
+    let store = Store::new();
+    assert_eq!(evaluate_async("=A+1", &store), "4");
+    assert_eq!(evaluate_async("=B*2", &store), "20");
+    assert_eq!(evaluate_async("=C", &store), "11");
+    assert_eq!(evaluate_async("=Name", &store), "Test");
+    assert_eq!(evaluate_async("=Missing", &store), "#VALUE!");
+    assert_eq!(evaluate_async("=Loop", &store), "#REF!");
+    assert_eq!(evaluate_async("=1+2", &store), "3");
+}
+
+#[test]
+fn it_requests_all_references_of_a_formula_in_one_batch() {
+    let store = Store::new();
+    assert_eq!(evaluate_async("=SUM(A, D, A) + 1", &store), "8");
+    assert_eq!(
+        *store.requests.lock().unwrap(),
+        vec![vec!["A".to_string(), "D".to_string()]]
+    );
+}
+
+#[test]
+fn it_requests_references_of_referenced_formulas_in_later_batches() {
+    let store = Store::new();
+    assert_eq!(evaluate_async("=C+A", &store), "14");
+    assert_eq!(
+        *store.requests.lock().unwrap(),
+        vec![
+            vec!["A".to_string(), "C".to_string()],
+            vec!["B".to_string(), "D".to_string()],
+        ]
+    );
+}
+
+#[test]
+fn it_does_not_call_the_resolver_without_references() {
+    let store = Store::new();
+    assert_eq!(evaluate_async("=1+2", &store), "3");
+    assert!(store.requests.lock().unwrap().is_empty());
+}
+
+#[test]
+fn it_evaluates_the_future_on_another_thread() {
+    let store = Store::new();
+    let formula = parse_formula::parse_string_to_formula("=C+A", None::<NoCustomFunction<f64>>);
+    let future = calculate::calculate_formula_async(formula, &store);
+    let value = std::thread::scope(|scope| scope.spawn(|| block_on(future)).join().unwrap());
+    assert_eq!(calculate::result_to_string(value), "14");
+}
@@ -0,0 +1,99 @@
+use std::{fmt::Debug, str::FromStr};
+use xlformula_engine::{
+    calculate::{self, Instruction},
+    parse_formula,
+    types::{self, XlNum},
+    NoCustomFunction, NoReference,
+};
+
+fn data_function<N: XlNum>(s: String) -> types::Value<N> {
+    match s.as_str() {
+        "A" => types::Value::Number(N::from_f32(2.0).unwrap()),
+        "B" => types::Value::Text("=A*3".to_string()),
+        "T" => types::Value::Text("text".to_string()),
+        "Z" => types::Value::Blank,
+        "L" => types::Value::Iterator(vec![
+            types::Value::Number(N::one()),
+            types::Value::Blank,
+            types::Value::Number(N::from_f32(3.0).unwrap()),
+        ]),
+        _ => types::Value::Error(types::Error::Value),
+    }
+}
+
+fn assert_same_result<N>(s: &str)
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<N>>);
+    let program = calculate::compile_formula(&formula);
+    for _ in 0..2 {
+        assert_eq!(
+            program.evaluate(Some(&data_function::<N>)),
+            calculate::calculate_formula(formula.clone(), Some(&data_function::<N>)),
+            "{s}"
+        );
+    }
+    assert_eq!(
+        program.evaluate(None::<NoReference<N>>),
+        calculate::calculate_formula(formula, None::<NoReference<N>>),
+        "{s}"
+    );
+}
+
+#[test]
+fn it_compiles_formulas_to_postfix_instructions() {
+    let formula =
+        parse_formula::parse_string_to_formula("=A+SUM(1,{2,B})", None::<NoCustomFunction<f64>>);
+    let program = calculate::compile_formula(&formula);
+    let instructions: Vec<String> = program
+        .instructions()
+        .iter()
+        .map(|instruction| match instruction {
+            Instruction::Value(value) => calculate::result_to_string(value.clone()),
+            Instruction::Reference(name) => name.clone(),
+            Instruction::Iterator(len) => format!("list {len}"),
+            Instruction::Operation(op, len) => format!("{op:?} {len}"),
+        })
+        .collect();
+    assert_eq!(
+        instructions,
+        vec!["A", "1", "2", "B", "list 2", "Function(Sum) 2", "Plus 2"]
+    );
+}
+
+#[test]
+fn it_evaluates_like_calculate_formula() {
+    for s in [
+        "=1+2*3-4/5^2",
+        "=A+B*2",
+        "=(1&2)=12",
+        r#"=T&" - "&A"#,
+        "=SUM({1,2,3},A,L)",
+        "=PRODUCT(L,Z,2)",
+        "=AVERAGE(1,,A,L)",
+        "={1,2,3}+{4,5,6}",
+        "={1,2}+{1,2,3}",
+        "=-{1,2,A}",
+        "=-A",
+        "=IF(A>1,B,T)",
+        "=IF(,1)",
+        "=IF()",
+        "=AND(TRUE,A>1,OR(FALSE,0))",
+        "=XOR({1,0,1})",
+        "=NOT(Z)",
+        "=ISBLANK(Z)+ISERROR(1/0)",
+        r#"=LEFT("apple",3)&RIGHT(T)"#,
+        r#"=FIND("p","apple")*SEARCH("X",T)"#,
+        "=DAYS(Z,Z)+YEAR(Z)",
+        "=Missing+1",
+        "=1/0",
+        "Hello",
+        "1.5",
+        "=(1",
+    ] {
+        assert_same_result::<f32>(s);
+        assert_same_result::<f64>(s);
+    }
+}
@@ -0,0 +1,135 @@
+use std::{cell::RefCell, collections::HashMap};
+use xlformula_engine::{
+    calculate::{self, ReferenceCache},
+    parse_formula, types,
+    workbook::Workbook,
+    NoCustomFunction,
+};
+
+fn counting_data_function(
+    calls: &RefCell<HashMap<String, usize>>,
+) -> impl Fn(String) -> types::Value<f64> + '_ {
+    move |s: String| {
+        *calls.borrow_mut().entry(s.clone()).or_insert(0) += 1;
+        match s.as_str() {
+            "A" => types::Value::Number(3.0),
+            "B" => types::Value::Text("=A*A+1".to_string()),
+            "C" => types::Value::Text("=B+B".to_string()),
+            "Name" => types::Value::Text("Test".to_string()),
+            "Loop" => types::Value::Text("=1+Loop".to_string()),
+            _ => types::Value::Error(types::Error::Value),
+        }
+    }
+}
+
+fn evaluate_with_cache(s: &str, f: &impl Fn(String) -> types::Value<f64>) -> String {
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    let mut cache = ReferenceCache::new();
+    calculate::result_to_string(calculate::calculate_formula_with_cache(
+        formula,
+        Some(f),
+        &mut cache,
+    ))
+}
+
+fn evaluate(s: &str, f: &impl Fn(String) -> types::Value<f64>) -> String {
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::result_to_string(calculate::calculate_formula(formula, Some(f)))
+}
+
+#[test]
+fn it_resolves_each_reference_once() {
+    let calls = RefCell::new(HashMap::new());
+    let data_function = counting_data_function(&calls);
+    assert_eq!(
+        evaluate_with_cache("=IF(A>0, A*2, A/2) + SUM(A, A)", &data_function),
+        "12"
+    );
+    assert_eq!(calls.borrow()["A"], 1);
+
+    calls.borrow_mut().clear();
+    assert_eq!(evaluate_with_cache("=C+B+A", &data_function), "33");
+    assert_eq!(calls.borrow()["A"], 1);
+    assert_eq!(calls.borrow()["B"], 1);
+    assert_eq!(calls.borrow()["C"], 1);
+}
+
+#[test]
+fn it_returns_the_same_results_as_without_cache() {
+    let calls = RefCell::new(HashMap::new());
+    let data_function = counting_data_function(&calls);
+    for formula in [
+        "=IF(A>0, A*2, A/2) + SUM(A, A)",
+        "=C+B+A",
+        "={A,B,C}+{1,2,3}",
+        r#"=Name&" - "&A"#,
+        "=SUM({A,B},C,Missing)",
+        "=ISBLANK(Missing)",
+    ] {
+        assert_eq!(
+            evaluate_with_cache(formula, &data_function),
+            evaluate(formula, &data_function),
+            "{formula}"
+        );
+    }
+}
+
+#[test]
+fn it_reports_self_references() {
+    let calls = RefCell::new(HashMap::new());
+    let data_function = counting_data_function(&calls);
+    assert_eq!(evaluate_with_cache("=Loop", &data_function), "#REF!");
+}
+
+#[test]
+fn it_shares_the_cache_between_evaluations() {
+    let calls = RefCell::new(HashMap::new());
+    let data_function = counting_data_function(&calls);
+    let mut cache = ReferenceCache::new();
+    for s in ["=B+1", "=B*2", "=C"] {
+        let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+        calculate::calculate_formula_with_cache(formula, Some(&data_function), &mut cache);
+    }
+    assert_eq!(calls.borrow()["B"], 1);
+    assert_eq!(cache.get("C"), Some(&types::Value::Number(20.0)));
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn it_caches_references_during_a_recalculation_pass() {
+    let calls = RefCell::new(HashMap::new());
+    let data_function = counting_data_function(&calls);
+    let mut workbook = Workbook::new();
+    for (name, s) in [("X", "=A+B"), ("Y", "=X*A"), ("Z", "=Y+B+C")] {
+        workbook.insert(
+            name,
+            parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>),
+        );
+    }
+    let results = workbook.recalculate_with_cache(Some(&data_function));
+    assert_eq!(results, workbook.recalculate(Some(&data_function)));
+    calls.borrow_mut().clear();
+    workbook.recalculate_with_cache(Some(&data_function));
+    assert_eq!(calls.borrow()["A"], 1);
+    assert_eq!(calls.borrow()["B"], 1);
+}
+
+#[test]
+fn it_keeps_evaluated_workbook_values_in_the_cache() {
+    let mut workbook = Workbook::new();
+    for (name, s) in [("P", "=1/0"), ("Q", "=P"), ("R", r#"="=2*3""#), ("S", "=R")] {
+        workbook.insert(
+            name,
+            parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>),
+        );
+    }
+    let calls = RefCell::new(HashMap::new());
+    let data_function = counting_data_function(&calls);
+    let results = workbook.recalculate_with_cache(Some(&data_function));
+    assert_eq!(results["Q"], types::Value::Error(types::Error::Div0));
+    assert_eq!(results["S"], types::Value::Text("=2*3".to_string()));
+    assert_eq!(results, workbook.recalculate(Some(&data_function)));
+    assert!(calls.borrow().is_empty());
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::{assert_all_types, eval};
+
+#[test]
+fn it_evaluates_factorials() {
+    assert_all_types("=FACT(5)", "120");
+    assert_all_types("=FACT(1.9)", "1");
+    assert_all_types("=FACT(0)", "1");
+    assert_all_types("=FACT({3,4})", "{6,24}");
+    assert_all_types("=FACT(-1)", "#NUM!");
+    assert_eq!(eval::<f64>("=FACT(35)"), "1.03331479663861E+40");
+    assert_eq!(eval::<f32>("=FACT(35)"), "#NUM!");
+    assert_eq!(eval::<f64>("=FACT(171)"), "#NUM!");
+    assert_all_types("=FACTDOUBLE(6)", "48");
+    assert_all_types("=FACTDOUBLE(7)", "105");
+    assert_all_types("=FACTDOUBLE(0)", "1");
+    assert_all_types("=FACTDOUBLE(1)", "1");
+    assert_all_types("=FACTDOUBLE(-1)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_combinations_and_permutations() {
+    assert_all_types("=COMBIN(8,2)", "28");
+    assert_all_types("=COMBIN(10,0)", "1");
+    assert_all_types("=COMBIN(10,10)", "1");
+    assert_all_types("=COMBIN(4.9,2.9)", "6");
+    assert_eq!(eval::<f64>("=COMBIN(60,30)"), "1.18264581564861E+17");
+    assert_all_types("=COMBIN(2,3)", "#NUM!");
+    assert_all_types("=COMBIN(-1,0)", "#NUM!");
+    assert_all_types("=COMBINA(4,3)", "20");
+    assert_all_types("=COMBINA(10,3)", "220");
+    assert_all_types("=COMBINA(0,0)", "1");
+    assert_all_types("=COMBINA(3,-1)", "#NUM!");
+    assert_all_types("=PERMUT(100,3)", "970200");
+    assert_all_types("=PERMUT(3,2)", "6");
+    assert_all_types("=PERMUT(3,0)", "1");
+    assert_all_types("=PERMUT(0,0)", "#NUM!");
+    assert_all_types("=PERMUT(3,4)", "#NUM!");
+    assert_all_types("=PERMUT(\"a\",1)", "#VALUE!");
+}
+
+#[test]
+fn it_evaluates_gcd_and_lcm() {
+    assert_all_types("=GCD(5,2)", "1");
+    assert_all_types("=GCD(24,36)", "12");
+    assert_all_types("=GCD(24.9,36)", "12");
+    assert_all_types("=GCD(5,0)", "5");
+    assert_all_types("=GCD({24,36},18)", "6");
+    assert_all_types("=GCD(-1,2)", "#NUM!");
+    assert_all_types("=GCD(2^53,2)", "#NUM!");
+    assert_all_types("=LCM(5,2)", "10");
+    assert_all_types("=LCM(24,36)", "72");
+    assert_all_types("=LCM({2,3},4)", "12");
+    assert_all_types("=LCM(0,5)", "0");
+    assert_all_types("=LCM(-1,2)", "#NUM!");
+    assert_all_types("=LCM(2^52,3)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_multinomial() {
+    assert_all_types("=MULTINOMIAL(2,3,4)", "1260");
+    assert_all_types("=MULTINOMIAL({1,2},3)", "60");
+    assert_all_types("=MULTINOMIAL(5)", "1");
+    assert_all_types("=MULTINOMIAL(-1)", "#NUM!");
+    assert_all_types("=MULTINOMIAL(1,1/0)", "#DIV/0!");
+}
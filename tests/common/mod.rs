@@ -27,3 +27,20 @@ where
     let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<N>>);
     calculate::calculate_formula(formula, None::<NoReference<N>>)
 }
+
+/// Evaluate formula string with the given number type, see `evaluate_formula_string`.
+#[allow(dead_code)]
+pub fn eval<N>(s: &str) -> String
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    evaluate_formula_string::<N>(s)
+}
+
+/// Assert that the formula evaluates to the expected string with f32 and f64.
+#[allow(dead_code)]
+pub fn assert_all_types(s: &str, expected: &str) {
+    assert_eq!(eval::<f32>(s), expected, "{s}");
+    assert_eq!(eval::<f64>(s), expected, "{s}");
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::assert_all_types;
+use xlformula_engine::{calculate, parse_formula, types, NoCustomFunction};
+
+fn text(s: &str) -> types::Value<f64> {
+    types::Value::Text(s.to_string())
+}
+
+/// Evaluates a formula with a table of fruits in `A1:A6`, regions in `B1:B6` and amounts in
+/// `C1:C6`.
+fn eval_with_table(s: &str) -> String {
+    let data = |reference: String| -> types::Value<f64> {
+        let column = match reference.as_str() {
+            "A1:A6" => vec![
+                text("Apple"),
+                text("apricot"),
+                text("Banana"),
+                types::Value::Blank,
+                text("a*b"),
+                text("cherry"),
+            ],
+            "B1:B6" => vec![
+                text("north"),
+                text("south"),
+                text("north"),
+                text("north"),
+                text("south"),
+                text("north"),
+            ],
+            "C1:C6" => vec![
+                types::Value::Number(10.0),
+                types::Value::Number(20.0),
+                types::Value::Number(30.0),
+                types::Value::Number(40.0),
+                text("n/a"),
+                types::Value::Number(60.0),
+            ],
+            "C1:C5" => vec![types::Value::Number(10.0); 5],
+            _ => return types::Value::Error(types::Error::Reference),
+        };
+        types::Value::Iterator(column)
+    };
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::result_to_string(calculate::calculate_formula(formula, Some(&data)))
+}
+
+#[test]
+fn it_counts_with_number_criteria() {
+    assert_all_types("=COUNTIF({1,5,10,15},\">=10\")", "2");
+    assert_all_types("=COUNTIF({1,5,10,15},\"<5\")", "1");
+    assert_all_types("=COUNTIF({1,5,10,15},\"<>5\")", "3");
+    assert_all_types("=COUNTIF({1,5,10,15},5)", "1");
+    assert_all_types("=COUNTIF({1,5,10,15},\"=1e1\")", "1");
+    assert_all_types("=COUNTIF({1,\"5\",\"x\",TRUE},\">0\")", "1");
+    assert_all_types("=COUNTIF({1,\"x\",TRUE},\"<>1\")", "2");
+    assert_all_types("=COUNTIF({\"5\",5},\"5\")", "2");
+    assert_all_types("=COUNTIF({\"5\",5,\"x\"},\"<>5\")", "1");
+    assert_all_types("=COUNTIF({TRUE,FALSE,TRUE},TRUE)", "2");
+    assert_all_types("=COUNTIF({TRUE,FALSE,\"TRUE\"},\"=false\")", "1");
+    assert_all_types("=COUNTIF({1,5,10,15},{\">1\",\"<10\"})", "{3,2}");
+    assert_all_types("=COUNTIF({1,1/0,1/0},\"#DIV/0!\")", "2");
+}
+
+#[test]
+fn it_counts_with_text_criteria() {
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"apple\")"), "1");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"a*\")"), "3");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"=*e?\")"), "0");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"*e*\")"), "2");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"??????\")"), "2");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"a~*b\")"), "1");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"<>a*\")"), "3");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\">b\")"), "2");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"\")"), "1");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"=\")"), "1");
+    assert_eq!(eval_with_table("=COUNTIF(A1:A6,\"<>\")"), "5");
+}
+
+#[test]
+fn it_evaluates_sumif_and_averageif() {
+    assert_all_types("=SUMIF({1,5,10,15},\">4\")", "30");
+    assert_all_types("=SUMIF({\"a\",\"b\",\"a\"},\"a\",{1,2,3})", "4");
+    assert_all_types("=SUMIF({1,2},\">5\")", "0");
+    assert_all_types("=AVERAGEIF({1,5,10,15},\">4\")", "10");
+    assert_all_types("=AVERAGEIF({1,2},\">5\")", "#DIV/0!");
+    assert_all_types("=SUMIF({1,2,3},\">1\",{1,1/0,1})", "#DIV/0!");
+    assert_all_types("=SUMIF({1,2})", "#ARG!");
+    assert_eq!(eval_with_table("=SUMIF(B1:B6,\"north\",C1:C6)"), "140");
+    assert_eq!(eval_with_table("=SUMIF(B1:B6,\"south\",C1:C6)"), "20");
+    assert_eq!(eval_with_table("=AVERAGEIF(B1:B6,\"north\",C1:C6)"), "35");
+    assert_eq!(eval_with_table("=SUMIF(C1:C6,{\">20\",\"<20\"})"), "{130,10}");
+}
+
+#[test]
+fn it_evaluates_aggregates_with_several_criteria() {
+    assert_eq!(eval_with_table("=COUNTIFS(B1:B6,\"north\",C1:C6,\">20\")"), "3");
+    assert_eq!(eval_with_table("=COUNTIFS(B1:B6,\"north\",A1:A6,\"<>\")"), "3");
+    assert_eq!(
+        eval_with_table("=SUMIFS(C1:C6,B1:B6,\"north\",A1:A6,\"<>\")"),
+        "100"
+    );
+    assert_eq!(
+        eval_with_table("=AVERAGEIFS(C1:C6,B1:B6,\"north\",C1:C6,\"<50\")"),
+        "26.6666666666667"
+    );
+    assert_eq!(eval_with_table("=MAXIFS(C1:C6,B1:B6,\"north\")"), "60");
+    assert_eq!(eval_with_table("=MINIFS(C1:C6,B1:B6,\"north\")"), "10");
+    assert_eq!(eval_with_table("=MAXIFS(C1:C6,B1:B6,\"east\")"), "0");
+    assert_eq!(eval_with_table("=SUMIFS(C1:C6,B1:B6,\"*th\")"), "160");
+    assert_eq!(eval_with_table("=SUMIFS(C1:C5,B1:B6,\"north\")"), "#VALUE!");
+    assert_eq!(eval_with_table("=COUNTIFS(B1:B6,\"north\",C1:C5)"), "#ARG!");
+    assert_all_types("=SUMIFS({1,2,3},{1,2,3},\">1\",{\"a\",\"b\",\"a\"},\"a\")", "3");
+    assert_eq!(
+        eval_with_table("=COUNTIFS(B1:B6,{\"north\",\"south\"})"),
+        "{4,2}"
+    );
+    assert_eq!(
+        eval_with_table("=SUMIFS(C1:C6,B1:B6,{\"north\",\"south\"},C1:C6,\">10\")"),
+        "{130,20}"
+    );
+    assert_eq!(
+        eval_with_table("=COUNTIFS(B1:B6,{\"north\",\"south\"},C1:C6,{\">20\",\"<20\"})"),
+        "{3,0}"
+    );
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "decimal")]
+
+mod common;
+
+use rust_decimal::Decimal;
+use std::str::FromStr;
+use xlformula_engine::types;
+
+fn eval(s: &str) -> String {
+    common::evaluate_formula_string::<Decimal>(s)
+}
+
+fn decimal(s: &str) -> Decimal {
+    Decimal::from_str(s).unwrap()
+}
+
+#[test]
+fn it_calculates_exactly_with_decimals() {
+    assert_eq!(eval("=300/1.2"), "250");
+    assert_eq!(eval("=0.1+0.2"), "0.3");
+    assert_eq!(eval("=0.1+0.2=0.3"), "TRUE");
+    assert_eq!(eval("=1.1*1.1"), "1.21");
+    assert_eq!(eval("=1/3"), "0.333333333333333");
+    assert_eq!(eval("=AVERAGE(1,2)"), "1.5");
+    assert_eq!(eval("=SUM({0.1,0.2,0.3})"), "0.6");
+    assert_eq!(eval("=PRODUCT(1.5,2)"), "3");
+    assert_eq!(eval("={1,2}/4"), "{0.25,0.5}");
+    assert_eq!(
+        common::evaluate_formula_value::<Decimal>("=19.99*3"),
+        types::Value::Number(decimal("59.97"))
+    );
+}
+
+#[test]
+fn it_evaluates_other_values_with_decimals() {
+    assert_eq!(eval("=1.50"), "1.5");
+    assert_eq!(eval("=\"2.5\"*2"), "5");
+    assert_eq!(eval("=LEFT(\"abc\",2)"), "ab");
+    assert_eq!(eval("=TEXT(1/3,\"0.000\")"), "0.333");
+    assert_eq!(eval("=\"$1,000.10\"*3"), "3000.3");
+    assert_eq!(eval("=ROUND(2.675,2)+MOD(-3,2)+CEILING(0.21,0.1)"), "3.98");
+    assert_eq!(eval("=IF(0.5>0.25,\"yes\",\"no\")"), "yes");
+    assert_eq!(eval("=1/0"), "#DIV/0!");
+}
+
+#[test]
+fn it_returns_num_errors_instead_of_overflowing() {
+    assert_eq!(eval("=79228162514264337593543950335*2"), "#NUM!");
+    assert_eq!(eval("=79228162514264337593543950335+1"), "#NUM!");
+    assert_eq!(eval("=SUM(79228162514264337593543950335,1)"), "#NUM!");
+    assert_eq!(eval("=10^30"), "#NUM!");
+}
+
+#[test]
+fn it_calculates_powers_roots_and_logarithms_with_decimals() {
+    assert_eq!(eval("=2^10"), "1024");
+    assert_eq!(eval("=1.5^2"), "2.25");
+    assert_eq!(eval("=2^-2"), "0.25");
+    assert_eq!(eval("=0^-1"), "#DIV/0!");
+    assert_eq!(eval("=(-8)^0.5"), "#NUM!");
+    assert_eq!(eval("=POWER(1.1,2)"), "1.21");
+    assert_eq!(eval("=SQRT(2.25)"), "1.5");
+    assert_eq!(eval("=SQRT(-1)"), "#NUM!");
+    assert_eq!(eval("=LN(1)"), "0");
+    assert_eq!(eval("=LN(0)"), "#NUM!");
+    assert_eq!(eval("=LN(-1)"), "#NUM!");
+    let ln = match common::evaluate_formula_value::<Decimal>("=LN(10)") {
+        types::Value::Number(number) => number,
+        value => panic!("{value:?}"),
+    };
+    assert!((ln - decimal("2.302585092994045684")).abs() < decimal("0.000000000000001"));
+}
+
+#[test]
+fn it_calculates_trigonometric_functions_with_decimals() {
+    assert_eq!(eval("=SIGN(-0.5)"), "-1");
+    assert_eq!(eval("=LOG(1000)"), "3");
+    assert_eq!(eval("=EXP(0)"), "1");
+    assert_eq!(eval("=SIN(0)"), "0");
+    assert_eq!(eval("=DEGREES(PI())"), "180");
+    assert_eq!(eval("=ASIN(2)"), "#NUM!");
+    assert_eq!(eval("=ATAN2(0,0)"), "#DIV/0!");
+}
+
+#[test]
+fn it_calculates_statistics_with_decimals() {
+    assert_eq!(eval("=MAX(0.1,0.3,0.2)"), "0.3");
+    assert_eq!(eval("=MEDIAN({0.1,0.2,0.4,0.3})"), "0.25");
+    assert_eq!(eval("=VAR.P(0.1,0.3)"), "0.01");
+    assert_eq!(eval("=STDEV.P(0.1,0.3)"), "0.1");
+    assert_eq!(eval("=PERCENTILE({0.1,0.2,0.3,0.4},0.3)"), "0.19");
+}
+
+#[test]
+fn it_calculates_conditional_aggregates_with_decimals() {
+    assert_eq!(eval("=SUMIF({0.1,0.2,0.3},\">0.1\")"), "0.5");
+    assert_eq!(eval("=COUNTIF({0.1,0.2,0.3},\"<=0.2\")"), "2");
+    assert_eq!(eval("=AVERAGEIFS({0.1,0.2,0.3},{1,2,3},\"<>2\")"), "0.2");
+}
+
+#[test]
+fn it_calculates_matrices_with_decimals() {
+    assert_eq!(eval("=SUMPRODUCT({0.1,0.2},{3,4})"), "1.1");
+    assert_eq!(eval("=MDETERM({1,2;3,4})"), "-2");
+    assert_eq!(eval("=MINVERSE({4,7;2,6})"), "{{0.6,-0.7},{-0.2,0.4}}");
+}
+
+#[test]
+fn it_calculates_financial_functions_with_decimals() {
+    assert_eq!(eval("=PMT(0,10,1000)"), "-100");
+    assert_eq!(eval("=ROUND(PMT(0.08/12,10,10000),2)"), "-1037.03");
+    assert_eq!(eval("=ROUND(NPV(0.1,-10000,3000,4200,6800),2)"), "1188.44");
+    assert_eq!(
+        eval("=ROUND(IRR({-70000,12000,15000,18000,21000,26000}),6)"),
+        "0.086631"
+    );
+}
+
+#[test]
+fn it_calculates_random_numbers_with_decimals() {
+    assert_eq!(eval("=RANDBETWEEN(2.5,3.5)"), "3");
+    assert_eq!(eval("=RANDARRAY(1,2,0.5,0.5)"), "{0.5,0.5}");
+    assert_eq!(eval("=ROUNDDOWN(RAND(),0)"), "0");
+}
+
+#[test]
+fn it_converts_bases_with_decimals() {
+    assert_eq!(eval("=DEC2HEX(-54)"), "FFFFFFFFCA");
+    assert_eq!(eval("=HEX2DEC(\"3DA408B9\")"), "1034160313");
+    assert_eq!(eval("=BASE(15.9,2,8)"), "00001111");
+    assert_eq!(eval("=BITLSHIFT(3,4)"), "48");
+    assert_eq!(eval("=BITAND(1.5,1)"), "#NUM!");
+}
+
+#[test]
+fn it_calculates_combinatorics_with_decimals() {
+    assert_eq!(eval("=FACT(15)"), "1307674368000");
+    assert_eq!(eval("=FACT(28)"), "#NUM!");
+    assert_eq!(eval("=COMBIN(60,30)"), "1.18264581564861E+17");
+    assert_eq!(eval("=MULTINOMIAL(2,3,4)"), "1260");
+    assert_eq!(eval("=LCM(24,36)"), "72");
+}
+
+#[test]
+fn it_calculates_distributions_with_decimals() {
+    assert_eq!(eval("=NORM.DIST(42,40,1.5,TRUE)"), "0.908788780274132");
+    assert_eq!(eval("=ROUND(T.INV(0.75,2),9)"), "0.816496581");
+    assert_eq!(eval("=BINOM.DIST(6,10,0.5,FALSE)"), "0.205078125");
+    assert_eq!(eval("=GAMMA(5)"), "24");
+    assert_eq!(eval("=GAMMA(0)"), "#NUM!");
+}
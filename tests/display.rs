@@ -0,0 +1,40 @@
+mod common;
+
+use common::{assert_all_types, eval};
+
+#[test]
+fn it_shows_numbers_with_significant_digits() {
+    assert_all_types("=0.1+0.2", "0.3");
+    assert_all_types("=300/1.2", "250");
+    assert_all_types("=1.5-0.5", "1");
+    assert_all_types("=-0.25", "-0.25");
+    assert_all_types("=0*-1", "0");
+    assert_eq!(eval::<f64>("=1/3"), "0.333333333333333");
+    assert_eq!(eval::<f32>("=1/3"), "0.3333333");
+    assert_eq!(eval::<f64>("=2/3"), "0.666666666666667");
+    assert_eq!(eval::<f64>("=123456789012345"), "123456789012345");
+    assert_eq!(eval::<f64>("=1234567.8901234567"), "1234567.89012346");
+    assert_eq!(eval::<f64>("=0.1*3"), "0.3");
+}
+
+#[test]
+fn it_shows_large_and_small_numbers_in_scientific_notation() {
+    assert_eq!(eval::<f64>("=10^15"), "1E+15");
+    assert_eq!(eval::<f64>("=1234567890123456789"), "1.23456789012346E+18");
+    assert_eq!(eval::<f64>("=-1.5*10^20"), "-1.5E+20");
+    assert_eq!(eval::<f64>("=99999999999999999"), "1E+17");
+    assert_eq!(eval::<f64>("=10^-9"), "0.000000001");
+    assert_eq!(eval::<f64>("=1.25*10^-10"), "1.25E-10");
+    assert_eq!(eval::<f64>("=10^-100"), "1E-100");
+    assert_eq!(eval::<f32>("=12345678"), "1.234568E+07");
+}
+
+#[test]
+fn it_converts_numbers_to_text_with_significant_digits() {
+    assert_eq!(eval::<f64>("=(0.1+0.2)&\"\""), "0.3");
+    assert_eq!(eval::<f64>("=\"x=\"&1/3"), "x=0.333333333333333");
+    assert_eq!(eval::<f64>("=10^20&\"\""), "1E+20");
+    assert_eq!(eval::<f64>("=LEFT((0.1+0.2)&\"\",5)"), "0.3");
+    assert_eq!(eval::<f64>("=FIND(\"E\",10^20)"), "2");
+    assert_all_types("={0.1,0.2}+0.2", "{0.3,0.4}");
+}
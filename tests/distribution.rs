@@ -0,0 +1,172 @@
+mod common;
+
+use common::{assert_all_types, eval};
+
+#[test]
+fn it_evaluates_normal_distributions() {
+    assert_eq!(
+        eval::<f64>("=NORM.DIST(42,40,1.5,TRUE)"),
+        "0.908788780274132"
+    );
+    assert_eq!(
+        eval::<f64>("=NORM.DIST(42,40,1.5,FALSE)"),
+        "0.109340049783996"
+    );
+    assert_all_types("=ROUND(NORM.DIST(42,40,1.5,1),6)", "0.908789");
+    assert_all_types("=NORM.DIST(42,40,0,TRUE)", "#NUM!");
+    assert_all_types("=NORM.DIST(42,40,1.5)", "#ARG!");
+    assert_eq!(
+        eval::<f64>("=NORM.S.DIST(1.333333,TRUE)"),
+        "0.908788725604095"
+    );
+    assert_eq!(
+        eval::<f64>("=NORM.S.DIST(1.333333,FALSE)"),
+        "0.164010147569367"
+    );
+    assert_eq!(eval::<f64>("=NORM.S.DIST(-8,TRUE)"), "6.22096057427179E-16");
+    assert_all_types("=NORM.S.DIST(0,TRUE)", "0.5");
+    assert_all_types("=NORM.S.DIST(\"a\",TRUE)", "#VALUE!");
+    assert_all_types("=ROUND(NORM.DIST({0,1},0,1,TRUE),6)", "{0.5,0.841345}");
+    assert_all_types(
+        "=ROUND(NORM.S.DIST({0;1},{TRUE,0}),6)",
+        "{{0.5,0.398942},{0.841345,0.241971}}",
+    );
+}
+
+#[test]
+fn it_evaluates_the_inverse_normal_distribution() {
+    assert_eq!(
+        eval::<f64>("=ROUND(NORM.INV(0.908789,40,1.5),9)"),
+        "42.00000201"
+    );
+    assert_all_types("=NORM.INV(0.5,0,1)", "0");
+    assert_eq!(eval::<f64>("=NORM.INV(0.000001,0,1)"), "-4.7534243088229");
+    assert_eq!(
+        eval::<f64>("=ROUND(NORM.INV(NORM.S.DIST(-2.5,TRUE),0,1),12)"),
+        "-2.5"
+    );
+    assert_all_types("=NORM.INV(0,40,1.5)", "#NUM!");
+    assert_all_types("=NORM.INV(1,40,1.5)", "#NUM!");
+    assert_all_types("=NORM.INV(0.5,40,-1)", "#NUM!");
+    assert_eq!(
+        eval::<f64>("=CONFIDENCE.NORM(0.05,2.5,50)"),
+        "0.692951912174839"
+    );
+    assert_all_types("=CONFIDENCE.NORM(0.05,2.5,0.5)", "#NUM!");
+    assert_all_types("=CONFIDENCE.NORM(1,2.5,50)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_t_distributions() {
+    assert_eq!(eval::<f64>("=T.DIST(60,1,TRUE)"), "0.994695326367377");
+    assert_eq!(eval::<f64>("=T.DIST(8,3,FALSE)"), "0.000736906520946926");
+    assert_all_types("=T.DIST(0,5,TRUE)", "0.5");
+    assert_all_types("=T.DIST(1,0.5,TRUE)", "#NUM!");
+    assert_eq!(eval::<f64>("=ROUND(T.INV(0.75,2),12)"), "0.816496580928");
+    assert_eq!(eval::<f64>("=ROUND(T.INV(0.001,30),9)"), "-3.385184867");
+    assert_all_types("=T.INV(0.5,10)", "0");
+    assert_all_types("=T.INV(0.5,10^6)", "0");
+    assert_eq!(eval::<f64>("=ROUND(T.INV(0.6,10^6),12)"), "0.253347170538");
+    assert_eq!(
+        eval::<f64>("=ROUND(T.DIST(0.001,10,TRUE),14)"),
+        "0.50038910831263"
+    );
+    assert_all_types("=T.INV(0,2)", "#NUM!");
+    assert_all_types("=T.INV(0.5,0)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_chi_squared_distributions() {
+    assert_eq!(eval::<f64>("=CHISQ.DIST(0.5,1,TRUE)"), "0.520499877813047");
+    assert_eq!(eval::<f64>("=CHISQ.DIST(2,3,FALSE)"), "0.207553748710297");
+    assert_all_types("=CHISQ.DIST(0,2,FALSE)", "0.5");
+    assert_all_types("=CHISQ.DIST(0,1,FALSE)", "#NUM!");
+    assert_all_types("=CHISQ.DIST(-1,3,TRUE)", "#NUM!");
+    assert_all_types("=CHISQ.DIST(1,2^40,TRUE)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_discrete_distributions() {
+    assert_eq!(eval::<f64>("=BINOM.DIST(6,10,0.5,FALSE)"), "0.205078125");
+    assert_eq!(eval::<f64>("=BINOM.DIST(6.9,10,0.5,TRUE)"), "0.828125");
+    assert_eq!(
+        eval::<f64>("=ROUND(BINOM.DIST(3,5000,0.001,FALSE),12)"),
+        "0.140359821659"
+    );
+    assert_all_types("=BINOM.DIST(0,10,0,FALSE)", "1");
+    assert_all_types("=BINOM.DIST(10,10,1,TRUE)", "1");
+    assert_all_types("=BINOM.DIST(11,10,0.5,TRUE)", "#NUM!");
+    assert_all_types("=BINOM.DIST(1,10,1.5,TRUE)", "#NUM!");
+    assert_eq!(eval::<f64>("=POISSON.DIST(2,5,TRUE)"), "0.124652019483081");
+    assert_eq!(
+        eval::<f64>("=POISSON.DIST(2,5,FALSE)"),
+        "0.0842243374885682"
+    );
+    assert_all_types("=POISSON.DIST(0,0,FALSE)", "1");
+    assert_all_types("=POISSON.DIST(-1,5,TRUE)", "#NUM!");
+    assert_all_types("=POISSON.DIST(1,-5,TRUE)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_exponential_distributions() {
+    assert_eq!(eval::<f64>("=EXPON.DIST(0.2,10,TRUE)"), "0.864664716763387");
+    assert_eq!(eval::<f64>("=EXPON.DIST(0.2,10,FALSE)"), "1.35335283236613");
+    assert_all_types("=EXPON.DIST(0,10,TRUE)", "0");
+    assert_all_types("=EXPON.DIST(-1,10,TRUE)", "#NUM!");
+    assert_all_types("=EXPON.DIST(1,0,TRUE)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_gamma_functions() {
+    assert_eq!(eval::<f64>("=GAMMA(2.5)"), "1.32934038817914");
+    assert_eq!(eval::<f64>("=GAMMA(-3.75)"), "0.267866128861416");
+    assert_all_types("=GAMMA(5)", "24");
+    assert_all_types("=GAMMA({1,2,3})", "{1,1,2}");
+    assert_eq!(eval::<f64>("=GAMMA(171)"), "7.25741561530725E+306");
+    assert_eq!(eval::<f32>("=GAMMA(171)"), "#NUM!");
+    assert_all_types("=GAMMA(0)", "#NUM!");
+    assert_all_types("=GAMMA(-1)", "#NUM!");
+    assert_eq!(eval::<f64>("=GAMMALN(4.5)"), "2.45373657084244");
+    assert_all_types("=GAMMALN(1)", "0");
+    assert_all_types("=GAMMALN(0)", "#NUM!");
+}
+
+#[test]
+fn it_parses_function_names_with_dots_as_built_ins() {
+    assert_all_types("=norm.s.dist(0,true)", "0.5");
+    assert_all_types("=1+T.DIST(0,1,TRUE)", "1.5");
+    assert_all_types("=NORM.FOO(1)", "#REF!");
+}
+
+#[test]
+fn it_evaluates_distributions_with_large_parameters() {
+    assert_eq!(
+        eval::<f64>("=ROUND(CHISQ.DIST(10^8,10^8,TRUE),9)"),
+        "0.500018806"
+    );
+    assert_eq!(
+        eval::<f64>("=ROUND(CHISQ.DIST(10^9,10^9,TRUE),9)"),
+        "0.500005947"
+    );
+    assert_eq!(
+        eval::<f64>("=ROUND(CHISQ.DIST(198,200,TRUE),12)"),
+        "0.473304330399"
+    );
+    assert_eq!(
+        eval::<f64>("=ROUND(POISSON.DIST(10^8,10^8,TRUE),9)"),
+        "0.500026596"
+    );
+    assert_eq!(
+        eval::<f64>("=ROUND(POISSON.DIST(10^9,10^9,TRUE),9)"),
+        "0.50000841"
+    );
+    assert_eq!(
+        eval::<f64>("=ROUND(BINOM.DIST(500000,10^6,0.5,TRUE),9)"),
+        "0.500398942"
+    );
+    assert_eq!(eval::<f64>("=ROUND(T.DIST(1,10^10,TRUE),9)"), "0.841344746");
+    assert_eq!(
+        eval::<f64>("=ROUND(T.DIST(1,10^10,FALSE),12)"),
+        "0.241970724507"
+    );
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use common::{assert_all_types, eval};
+
+#[test]
+fn it_converts_decimal_numbers_to_other_bases() {
+    assert_all_types("=DEC2BIN(9,4)", "1001");
+    assert_all_types("=DEC2BIN(9.9)", "1001");
+    assert_all_types("=DEC2BIN(511)", "111111111");
+    assert_all_types("=DEC2BIN(-100)", "1110011100");
+    assert_all_types("=DEC2BIN(-512)", "1000000000");
+    assert_all_types("=DEC2HEX(100,4)", "0064");
+    assert_all_types("=DEC2HEX(28)", "1C");
+    assert_all_types("=DEC2HEX(-54)", "FFFFFFFFCA");
+    assert_all_types("=DEC2OCT(58,3)", "072");
+    assert_all_types("=DEC2OCT(-100)", "7777777634");
+    assert_all_types("=DEC2BIN(\"5\")", "101");
+    assert_all_types("=DEC2BIN(512)", "#NUM!");
+    assert_all_types("=DEC2BIN(-513)", "#NUM!");
+    assert_all_types("=DEC2BIN(9,2)", "#NUM!");
+    assert_all_types("=DEC2BIN(9,11)", "#NUM!");
+    assert_all_types("=DEC2HEX(64,-1)", "#NUM!");
+    assert_all_types("=DEC2BIN(\"a\")", "#VALUE!");
+    assert_all_types("=DEC2BIN(1,2,3)", "#ARG!");
+}
+
+#[test]
+fn it_converts_numbers_of_other_bases_to_decimal_numbers() {
+    assert_all_types("=BIN2DEC(1100100)", "100");
+    assert_all_types("=BIN2DEC(\"1111111111\")", "-1");
+    assert_all_types("=BIN2DEC(\"\")", "0");
+    assert_all_types("=HEX2DEC(\"A5\")", "165");
+    assert_all_types("=HEX2DEC(\"a5\")", "165");
+    assert_all_types("=HEX2DEC(\"FFFFFFFF5B\")", "-165");
+    assert_eq!(eval::<f64>("=HEX2DEC(\"3DA408B9\")"), "1034160313");
+    assert_all_types("=OCT2DEC(54)", "44");
+    assert_all_types("=OCT2DEC(\"7777777533\")", "-165");
+    assert_all_types("=BIN2DEC(\"2\")", "#NUM!");
+    assert_all_types("=BIN2DEC(\"11111111111\")", "#NUM!");
+    assert_all_types("=HEX2DEC(\"-1\")", "#NUM!");
+    assert_all_types("=BIN2DEC(TRUE)", "#VALUE!");
+    assert_all_types("=BIN2DEC(1/0)", "#DIV/0!");
+}
+
+#[test]
+fn it_evaluates_base_and_decimal() {
+    assert_all_types("=BASE(7,2)", "111");
+    assert_all_types("=BASE(100,16)", "64");
+    assert_all_types("=BASE(15,2,10)", "0000001111");
+    assert_all_types("=BASE(35,36)", "Z");
+    assert_all_types("=BASE(-1,2)", "#NUM!");
+    assert_all_types("=BASE(7,1)", "#NUM!");
+    assert_all_types("=BASE(7,37)", "#NUM!");
+    assert_all_types("=BASE(7,2,256)", "#NUM!");
+    assert_all_types("=DECIMAL(\"FF\",16)", "255");
+    assert_all_types("=DECIMAL(111,2)", "7");
+    assert_all_types("=DECIMAL(\"zap\",36)", "45745");
+    assert_all_types("=DECIMAL(\"2\",2)", "#NUM!");
+    assert_all_types("=DECIMAL(\"1\",0)", "#NUM!");
+}
+
+#[test]
+fn it_converts_arrays_element_by_element() {
+    assert_all_types("=DEC2BIN({1,2})", "{1,10}");
+    assert_all_types("=DEC2HEX({255;-1},{4;1})", "{{00FF},{FFFFFFFFFF}}");
+    assert_all_types("=BIN2DEC({\"101\",\"2\"})", "{5,#NUM!}");
+    assert_all_types("=BASE({7,255},{2;16})", "{{111,11111111},{7,FF}}");
+    assert_all_types("=DECIMAL(\"11\",{2,16})", "{3,17}");
+}
+
+#[test]
+fn it_evaluates_bit_functions() {
+    assert_all_types("=BITAND(13,25)", "9");
+    assert_all_types("=BITOR(23,10)", "31");
+    assert_all_types("=BITXOR(5,3)", "6");
+    assert_all_types("=BITLSHIFT(4,2)", "16");
+    assert_all_types("=BITLSHIFT(4,-1)", "2");
+    assert_all_types("=BITRSHIFT(13,2)", "3");
+    assert_all_types("=BITRSHIFT(1,-3)", "8");
+    assert_all_types("=BITAND({1,3,7},5)", "{1,1,5}");
+    assert_all_types("=BITAND(-1,1)", "#NUM!");
+    assert_all_types("=BITAND(1.5,1)", "#NUM!");
+    assert_all_types("=BITOR(2^48,1)", "#NUM!");
+    assert_all_types("=BITLSHIFT(1,54)", "#NUM!");
+    assert_all_types("=BITLSHIFT(2^47,1)", "#NUM!");
+    assert_all_types("=BITXOR(\"a\",1)", "#VALUE!");
+}
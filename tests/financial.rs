@@ -0,0 +1,126 @@
+mod common;
+
+use chrono::{DateTime, FixedOffset};
+use common::assert_all_types;
+use xlformula_engine::{calculate, parse_formula, types, NoCustomFunction};
+
+fn date(s: &str) -> DateTime<FixedOffset> {
+    DateTime::parse_from_rfc3339(s).unwrap()
+}
+
+/// Evaluates a formula with the cash flows -10000, 2750, 4250, 3250 and 2750 in `A1:A5` and
+/// their dates in `B1:B5`.
+fn eval_with_cash_flows(s: &str) -> String {
+    let data = |reference: String| -> types::Value<f64> {
+        match reference.as_str() {
+            "A1:A5" => types::Value::Iterator(
+                [-10000.0, 2750.0, 4250.0, 3250.0, 2750.0]
+                    .into_iter()
+                    .map(types::Value::Number)
+                    .collect(),
+            ),
+            "B1:B5" => types::Value::Iterator(
+                [
+                    "2008-01-01T00:00:00Z",
+                    "2008-03-01T00:00:00Z",
+                    "2008-10-30T00:00:00Z",
+                    "2009-02-15T00:00:00Z",
+                    "2009-04-01T00:00:00Z",
+                ]
+                .into_iter()
+                .map(|s| types::Value::Date(date(s)))
+                .collect(),
+            ),
+            _ => types::Value::Error(types::Error::Reference),
+        }
+    };
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::result_to_string(calculate::calculate_formula(formula, Some(&data)))
+}
+
+#[test]
+fn it_evaluates_pmt_pv_fv_and_nper() {
+    assert_all_types("=ROUND(PMT(0.08/12,10,10000),2)", "-1037.03");
+    assert_all_types("=ROUND(PMT(0.08/12,10,10000,0,1),2)", "-1030.16");
+    assert_all_types("=PMT(0,10,1000)", "-100");
+    assert_all_types("=ROUND(PV(0.08/12,12*20,500),2)", "-59777.15");
+    assert_all_types("=ROUND(FV(0.06/12,10,-200,-500,1),2)", "2581.4");
+    assert_all_types("=ROUND(FV(0.12/12,12,-1000),2)", "12682.5");
+    assert_all_types("=FV(0,10,-100)", "1000");
+    assert_all_types("=ROUND(NPER(0.01,-100,-1000,10000,1),4)", "59.6739");
+    assert_all_types("=ROUND(NPER(0.01,-100,-1000,10000),4)", "60.0821");
+    assert_all_types("=ROUND(NPER(0.01,100,1000),4)", "-9.5786");
+    assert_all_types("=NPER(0.1,-10,1000)", "#NUM!");
+    assert_all_types("=PMT(0.1,10)", "#ARG!");
+    assert_all_types("=PMT(\"a\",10,1000)", "#VALUE!");
+}
+
+#[test]
+fn it_maps_financial_functions_over_arrays() {
+    assert_all_types("=ROUND(PMT({0.1,0.2},10,100),2)", "{-16.27,-23.85}");
+    assert_all_types("=FV(0,{1;2},{-100,-200})", "{{100,200},{200,400}}");
+    assert_all_types("=PMT({0,\"a\"},10,1000)", "{-100,#VALUE!}");
+}
+
+#[test]
+fn it_evaluates_ipmt_and_ppmt() {
+    assert_all_types("=ROUND(IPMT(0.1/12,1,3*12,8000),2)", "-66.67");
+    assert_all_types("=ROUND(IPMT(0.1,3,3,8000),2)", "-292.45");
+    assert_all_types("=IPMT(0.1,1,3,8000,0,1)", "0");
+    assert_all_types("=ROUND(PPMT(0.1/12,1,2*12,2000),2)", "-75.62");
+    assert_all_types("=ROUND(PPMT(0.08,10,10,200000),2)", "-27598.05");
+    assert_all_types("=IPMT(0.1,4,3,8000)", "#NUM!");
+    assert_all_types("=PPMT(0.1,0,3,8000)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_rate() {
+    assert_all_types("=ROUND(RATE(48,-200,8000),6)", "0.007701");
+    assert_all_types("=ROUND(RATE(48,-200,8000)*12,4)", "0.0924");
+    assert_all_types("=ROUND(RATE(10,-100,0,1100,0,0.01),4)", "0.021");
+    assert_all_types("=ROUND(RATE(360,-1073.64,200000),7)", "0.0041666");
+    assert_all_types("=ROUND(RATE(360,-1073.64,200000)*12,4)", "0.05");
+    assert_all_types("=ROUND(RATE(240,-1500,200000),6)", "0.005479");
+    assert_all_types("=RATE(10,100,1000)", "#NUM!");
+}
+
+#[test]
+fn it_evaluates_npv_and_irr() {
+    assert_all_types("=ROUND(NPV(0.1,-10000,3000,4200,6800),2)", "1188.44");
+    assert_all_types("=ROUND(NPV(0.1,{-10000,3000},{4200,6800}),2)", "1188.44");
+    assert_all_types("=NPV(-1,100)", "#DIV/0!");
+    assert_all_types(
+        "=ROUND(IRR({-70000,12000,15000,18000,21000,26000}),6)",
+        "0.086631",
+    );
+    assert_all_types(
+        "=ROUND(IRR({-70000,12000,15000,18000,21000}),6)",
+        "-0.021245",
+    );
+    assert_all_types("=ROUND(IRR({-70000,12000,15000},-0.1),6)", "-0.443507");
+    assert_all_types("=IRR({100,200})", "#NUM!");
+    assert_all_types("=IRR({-100,200},1,2)", "#ARG!");
+}
+
+#[test]
+fn it_evaluates_xnpv_and_xirr() {
+    assert_eq!(
+        eval_with_cash_flows("=ROUND(XNPV(0.09,A1:A5,B1:B5),2)"),
+        "2086.65"
+    );
+    assert_eq!(
+        eval_with_cash_flows("=ROUND(XIRR(A1:A5,B1:B5),6)"),
+        "0.373363"
+    );
+    assert_all_types(
+        "=ROUND(XNPV(0.09,{-10000,2750,4250,3250,2750},{39448,39508,39751,39859,39904}),2)",
+        "2086.65",
+    );
+    assert_all_types(
+        "=ROUND(XIRR({-10000,2750,4250,3250,2750},{39448,39508,39751,39859,39904}),6)",
+        "0.373363",
+    );
+    assert_all_types("=XNPV(0.09,{-100,200},{39448,39447})", "#NUM!");
+    assert_all_types("=XNPV(0.09,{-100,200},{39448})", "#NUM!");
+    assert_all_types("=XIRR({100,200},{39448,39508})", "#NUM!");
+}
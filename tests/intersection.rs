@@ -0,0 +1,141 @@
+use xlformula_engine::{
+    calculate,
+    intersection::{add_implicit_intersection, intersect_formula, CellPosition},
+    parse_formula, types, NoCustomFunction, NoReference,
+};
+
+fn data_function(s: String) -> types::Value<f64> {
+    let column = |values: [f64; 3]| {
+        types::Value::Iterator(values.into_iter().map(types::Value::Number).collect())
+    };
+    match s.as_str() {
+        "A1" => types::Value::Number(1.0),
+        "A2" => types::Value::Number(2.0),
+        "A3" => types::Value::Number(3.0),
+        "C1" => types::Value::Number(10.0),
+        "C2" => types::Value::Number(20.0),
+        "A1:A3" => column([1.0, 2.0, 3.0]),
+        "A1:C1" => column([1.0, 5.0, 10.0]),
+        _ => types::Value::Error(types::Error::Value),
+    }
+}
+
+fn parse(s: &str) -> types::Formula<f64> {
+    parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>)
+}
+
+fn evaluate(formula: types::Formula<f64>) -> String {
+    calculate::result_to_string(calculate::calculate_formula(formula, Some(&data_function)))
+}
+
+fn evaluate_at(s: &str, cell: &str) -> String {
+    evaluate(intersect_formula(
+        parse(s),
+        CellPosition::parse(cell).unwrap(),
+    ))
+}
+
+fn evaluate_legacy_at(s: &str, cell: &str) -> String {
+    evaluate(intersect_formula(
+        add_implicit_intersection(parse(s)),
+        CellPosition::parse(cell).unwrap(),
+    ))
+}
+
+#[test]
+fn it_parses_cell_positions() {
+    assert_eq!(
+        CellPosition::parse("B5"),
+        Some(CellPosition { row: 5, column: 2 })
+    );
+    assert_eq!(
+        CellPosition::parse("aa10"),
+        Some(CellPosition {
+            row: 10,
+            column: 27
+        })
+    );
+    assert_eq!(CellPosition::parse("A0"), None);
+    assert_eq!(CellPosition::parse("5"), None);
+    assert_eq!(CellPosition::parse("A"), None);
+    assert_eq!(CellPosition::parse("A1B"), None);
+    assert_eq!(
+        CellPosition {
+            row: 10,
+            column: 27
+        }
+        .name(),
+        "AA10"
+    );
+    assert_eq!(CellPosition { row: 1, column: 26 }.name(), "Z1");
+    assert_eq!(
+        CellPosition {
+            row: 3,
+            column: 703
+        }
+        .name(),
+        "AAA3"
+    );
+}
+
+#[test]
+fn it_parses_range_references_and_the_intersection_operator() {
+    assert_eq!(evaluate(parse("=SUM(A1:A3)")), "6");
+    assert_eq!(
+        calculate::formula_to_string(&parse("=@A1:A3+1")),
+        "@A1:A3+1"
+    );
+    assert_eq!(calculate::formula_to_string(&parse("=@(A1:A3)")), "@A1:A3");
+    assert_eq!(calculate::formula_to_string(&parse("=@{1,2}")), "@{1,2}");
+    assert_eq!(
+        calculate::formula_to_string(&parse("=@SUM(A1:A3)")),
+        "@(SUM(A1:A3))"
+    );
+}
+
+#[test]
+fn it_evaluates_the_intersection_operator_to_the_first_element() {
+    assert_eq!(evaluate(parse("=@{4,5,6}")), "4");
+    assert_eq!(evaluate(parse("=@{4,5;6,7}")), "4");
+    assert_eq!(evaluate(parse("=@(7)")), "7");
+    assert_eq!(evaluate(parse("=@A1:A3")), "1");
+    assert_eq!(
+        calculate::result_to_string(calculate::calculate_formula(
+            parse("=@({1,2}*3)"),
+            None::<NoReference<f64>>
+        )),
+        "3"
+    );
+}
+
+#[test]
+fn it_intersects_ranges_with_the_evaluating_cell() {
+    assert_eq!(evaluate_at("=@A1:A3", "B2"), "2");
+    assert_eq!(evaluate_at("=@A1:A3*10", "D3"), "30");
+    assert_eq!(evaluate_at("=@A3:A1", "B1"), "1");
+    assert_eq!(evaluate_at("=@A1:C1", "C5"), "10");
+    assert_eq!(evaluate_at("=@A1:C2", "C2"), "20");
+    assert_eq!(evaluate_at("=@A1:A3", "B4"), "#VALUE!");
+    assert_eq!(evaluate_at("=@A1:C1", "D1"), "#VALUE!");
+    assert_eq!(evaluate_at("=SUM(A1:A3)", "B2"), "6");
+    assert_eq!(evaluate_at("=@{4,5,6}", "B2"), "4");
+}
+
+#[test]
+fn it_adds_implicit_intersection_to_legacy_formulas() {
+    let legacy = |s: &str| calculate::formula_to_string(&add_implicit_intersection(parse(s)));
+    assert_eq!(legacy("=A1:A3"), "@A1:A3");
+    assert_eq!(legacy("=A1:A3+1"), "@A1:A3+1");
+    assert_eq!(legacy("=ABS(A1:A3)"), "ABS(@A1:A3)");
+    assert_eq!(legacy("=SUM(A1:A3)"), "SUM(A1:A3)");
+    assert_eq!(legacy("=SUM(A1:A3*2)"), "SUM(@A1:A3*2)");
+    assert_eq!(legacy("={A1:A3}"), "{A1:A3}");
+    assert_eq!(legacy("=A1+B"), "A1+B");
+
+    assert_eq!(evaluate_legacy_at("=A1:A3*10", "B2"), "20");
+    assert_eq!(
+        evaluate_legacy_at("=IF(A1:A3>1, \"big\", \"small\")", "B3"),
+        "big"
+    );
+    assert_eq!(evaluate_legacy_at("=SUM(A1:A3)", "B2"), "6");
+}
@@ -0,0 +1,179 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use xlformula_engine::{
+    calculate, parse_formula,
+    types::{self, Limits},
+    NoCustomFunction, NoReference,
+};
+
+fn evaluate_with_limits(
+    s: &str,
+    f: Option<&impl Fn(String) -> types::Value<f64>>,
+    limits: &Limits,
+) -> String {
+    let formula = parse_formula::parse_string_to_formula_with_limits(
+        s,
+        None::<NoCustomFunction<f64>>,
+        limits,
+    );
+    calculate::result_to_string(calculate::calculate_formula_with_limits(formula, f, limits))
+}
+
+fn evaluate(s: &str, limits: &Limits) -> String {
+    evaluate_with_limits(s, None::<NoReference<f64>>, limits)
+}
+
+#[test]
+fn it_rejects_deeply_nested_formulas_before_parsing() {
+    let limits = Limits::default();
+    let s = format!("={}1{}", "(".repeat(100_000), ")".repeat(100_000));
+    assert_eq!(
+        evaluate(
+            &s,
+            &Limits {
+                max_formula_length: None,
+                ..limits.clone()
+            }
+        ),
+        "#LIMIT!"
+    );
+    let s = format!("={}1{}", "ABS(".repeat(300), ")".repeat(300));
+    assert_eq!(evaluate(&s, &limits), "#LIMIT!");
+    let s = format!("={}1{}", "ABS(".repeat(50), ")".repeat(50));
+    assert_eq!(evaluate(&s, &limits), "1");
+    // Brackets inside of strings don't count.
+    let s = format!(r#"=LEFT("{}",2)"#, "(".repeat(1000));
+    assert_eq!(evaluate(&s, &limits), "((");
+}
+
+#[test]
+fn it_rejects_long_formulas() {
+    let limits = Limits {
+        max_formula_length: Some(10),
+        ..Limits::default()
+    };
+    assert_eq!(evaluate("=1+2+3+4+5", &limits), "15");
+    assert_eq!(evaluate("=1+2+3+4+50", &limits), "#LIMIT!");
+}
+
+#[test]
+fn it_rejects_long_chains_of_operators() {
+    let limits = Limits {
+        max_formula_length: None,
+        ..Limits::default()
+    };
+    let s = format!("={}1", "1+".repeat(5000));
+    assert_eq!(evaluate(&s, &limits), "#LIMIT!");
+    let s = format!("={}1", "1+".repeat(100));
+    assert_eq!(evaluate(&s, &limits), "101");
+    let s = format!("={}1", "1+".repeat(299));
+    assert_eq!(evaluate(&s, &Limits::default()), "300");
+}
+
+#[test]
+fn it_limits_the_length_of_texts() {
+    let limits = Limits {
+        max_text_length: Some(8),
+        ..Limits::default()
+    };
+    assert_eq!(evaluate(r#"="abcd"&"efgh""#, &limits), "abcdefgh");
+    assert_eq!(evaluate(r#"="abcd"&"efgh"&"i""#, &limits), "#LIMIT!");
+    // The limit can't be hidden by functions that handle errors.
+    assert_eq!(
+        evaluate(r#"=ISERROR("abcd"&"efgh"&"i")"#, &limits),
+        "#LIMIT!"
+    );
+
+    let data_function = |s: String| match s.as_str() {
+        "A" => types::Value::Text("x".to_string()),
+        "B" => types::Value::Text("=A&A&A".to_string()),
+        "C" => types::Value::Text("=B&B&B".to_string()),
+        _ => types::Value::Error(types::Error::Value),
+    };
+    assert_eq!(
+        evaluate_with_limits("=B", Some(&data_function), &limits),
+        "xxx"
+    );
+    assert_eq!(
+        evaluate_with_limits("=C", Some(&data_function), &limits),
+        "#LIMIT!"
+    );
+}
+
+#[test]
+fn it_limits_the_size_of_arrays() {
+    let limits = Limits {
+        max_array_elements: Some(3),
+        ..Limits::default()
+    };
+    assert_eq!(evaluate("=SUM({1,2,3})", &limits), "6");
+    assert_eq!(evaluate("=SUM({1,2,3,4})", &limits), "#LIMIT!");
+}
+
+#[test]
+fn it_limits_the_number_of_evaluation_steps() {
+    let limits = Limits {
+        max_evaluation_steps: Some(5),
+        ..Limits::default()
+    };
+    assert_eq!(evaluate("=1+2+3", &limits), "6");
+    assert_eq!(evaluate("=1+2+3+4", &limits), "#LIMIT!");
+
+    // Steps of referenced formulas count as well, which also stops infinite recursion.
+    let data_function = |s: String| match s.as_str() {
+        "Loop" => types::Value::Text("=1+Loop".to_string()),
+        _ => types::Value::Error(types::Error::Value),
+    };
+    let limits = Limits {
+        max_evaluation_steps: Some(1000),
+        ..Limits::default()
+    };
+    assert_eq!(
+        evaluate_with_limits("=Loop", Some(&data_function), &limits),
+        "#LIMIT!"
+    );
+}
+
+#[test]
+fn it_stops_a_cancelled_evaluation() {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let limits = Limits {
+        cancel: Some(cancel.clone()),
+        ..Limits::default()
+    };
+    assert_eq!(evaluate("=1+2", &limits), "3");
+    let data_function = |s: String| {
+        if s == "Stop" {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        types::Value::Number(1.0)
+    };
+    assert_eq!(
+        evaluate_with_limits("=SUM(1, Stop, 3)+2", Some(&data_function), &limits),
+        "#CANCEL!"
+    );
+}
+
+#[test]
+fn it_returns_the_same_results_within_the_limits() {
+    let limits = Limits::default();
+    for s in [
+        "=1+2*3",
+        r#"="a"&1"#,
+        "=SUM({1,2,3},4)",
+        "=1/0",
+        "Hello",
+        "=(1",
+    ] {
+        let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+        assert_eq!(
+            evaluate(s, &limits),
+            calculate::result_to_string(calculate::calculate_formula(
+                formula,
+                None::<NoReference<f64>>
+            ))
+        );
+    }
+}
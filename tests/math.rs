@@ -0,0 +1,129 @@
+mod common;
+
+use common::{assert_all_types, eval};
+
+#[test]
+fn it_evaluates_power() {
+    assert_all_types("=POWER(2,10)", "1024");
+    assert_all_types("=POWER(4,0.5)", "2");
+    assert_all_types("=POWER(2,-1)", "0.5");
+    assert_all_types("=POWER(0,-1)", "#DIV/0!");
+    assert_all_types("=POWER(-8,0.5)", "#NUM!");
+    assert_all_types("=(-8)^0.5", "#NUM!");
+    assert_all_types("=POWER({2,3},2)", "{4,9}");
+    assert_all_types("=POWER(\"2\",3)", "8");
+}
+
+#[test]
+fn it_evaluates_sqrt() {
+    assert_all_types("=SQRT(16)", "4");
+    assert_all_types("=SQRT(0)", "0");
+    assert_all_types("=SQRT(-1)", "#NUM!");
+    assert_all_types("=SQRT(\"9\")", "3");
+    assert_all_types("=SQRT(\"a\")", "#VALUE!");
+    assert_all_types("=SQRT(TRUE)", "1");
+    assert_all_types("=SQRT({4,9,-1})", "{2,3,#NUM!}");
+    assert_all_types("=SQRT(1/0)", "#DIV/0!");
+}
+
+#[test]
+fn it_evaluates_ln() {
+    assert_all_types("=LN(1)", "0");
+    assert_all_types("=LN(0)", "#NUM!");
+    assert_all_types("=LN(-2)", "#NUM!");
+    assert_eq!(eval::<f64>("=LN(10)"), "2.30258509299405");
+}
+
+#[test]
+fn it_evaluates_exp_and_logarithms() {
+    assert_all_types("=EXP(0)", "1");
+    assert_eq!(eval::<f64>("=EXP(1)"), "2.71828182845905");
+    assert_all_types("=EXP(1000)", "#NUM!");
+    assert_all_types("=LOG(100)", "2");
+    assert_all_types("=LOG(8,2)", "3");
+    assert_all_types("=LOG(1000,10)", "3");
+    assert_all_types("=LOG(0)", "#NUM!");
+    assert_all_types("=LOG(10,-2)", "#NUM!");
+    assert_all_types("=LOG(10,1)", "#DIV/0!");
+    assert_all_types("=LOG10(0.001)", "-3");
+    assert_all_types("=LOG10(-1)", "#NUM!");
+    assert_all_types("=LOG10({1,10,100})", "{0,1,2}");
+    assert_all_types("=LN(EXP(2))", "2");
+}
+
+#[test]
+fn it_evaluates_abs() {
+    assert_all_types("=ABS({-1,-2})", "{1,2}");
+    assert_all_types("=ABS(\"-3\")", "3");
+    assert_all_types("=ABS(TRUE)", "1");
+    assert_all_types("=ABS(\"a\")", "#VALUE!");
+}
+
+#[test]
+fn it_evaluates_pi_and_sign() {
+    assert_eq!(eval::<f64>("=PI()"), "3.14159265358979");
+    assert_eq!(eval::<f32>("=PI()"), "3.141593");
+    assert_all_types("=SIGN(-2.5)", "-1");
+    assert_all_types("=SIGN(0)", "0");
+    assert_all_types("=SIGN(\"3\")", "1");
+    assert_all_types("=SIGN({-4,0,4})", "{-1,0,1}");
+    assert_all_types("=SIGN(\"a\")", "#VALUE!");
+}
+
+#[test]
+fn it_evaluates_trigonometric_functions() {
+    assert_all_types("=SIN(0)", "0");
+    assert_all_types("=SIN(PI()/2)", "1");
+    assert_all_types("=COS(0)", "1");
+    assert_all_types("=TAN(PI()/4)", "1");
+    assert_all_types("=ASIN(1)*2/PI()", "1");
+    assert_all_types("=ACOS(1)", "0");
+    assert_all_types("=ATAN(1)*4/PI()", "1");
+    assert_all_types("=ASIN(2)", "#NUM!");
+    assert_all_types("=ACOS(-1.5)", "#NUM!");
+    assert_all_types("=COS({0,1}*PI())", "{1,-1}");
+}
+
+#[test]
+fn it_checks_the_number_of_arguments() {
+    // Like SQRT, unary functions only parse with one argument, and are otherwise unknown
+    // custom functions.
+    assert_all_types("=SQRT(4,1)", "#REF!");
+    assert_all_types("=SIN(0,1)", "#REF!");
+    assert_all_types("=EXP(1,2)", "#REF!");
+    assert_all_types("=SINH()", "#REF!");
+    assert_all_types("=POWER(2,3,4)", "#ARG!");
+    assert_all_types("=POWER(2)", "#ARG!");
+    assert_all_types("=LOG(8,2,3)", "#ARG!");
+    assert_all_types("=ATAN2(1,1,1)", "#ARG!");
+}
+
+#[test]
+fn it_evaluates_atan2() {
+    assert_all_types("=ATAN2(1,1)*4/PI()", "1");
+    assert_all_types("=ATAN2(-1,0)/PI()", "1");
+    assert_all_types("=ATAN2(0,-1)*2/PI()", "-1");
+    assert_all_types("=ATAN2(0,0)", "#DIV/0!");
+    assert_all_types("=ATAN2({1,-1},0)/PI()", "{0,1}");
+}
+
+#[test]
+fn it_converts_angles() {
+    assert_all_types("=DEGREES(PI())", "180");
+    assert_all_types("=RADIANS(180)/PI()", "1");
+    assert_all_types("=DEGREES(RADIANS({90,-45}))", "{90,-45}");
+}
+
+#[test]
+fn it_evaluates_hyperbolic_functions() {
+    assert_all_types("=SINH(0)", "0");
+    assert_all_types("=COSH(0)", "1");
+    assert_all_types("=TANH(0)", "0");
+    assert_eq!(eval::<f64>("=SINH(1)"), "1.1752011936438");
+    assert_all_types("=ASINH(SINH(2))", "2");
+    assert_all_types("=ACOSH(1)", "0");
+    assert_all_types("=ACOSH(0.5)", "#NUM!");
+    assert_eq!(eval::<f64>("=ATANH(0.5)*2"), "1.09861228866811");
+    assert_all_types("=ATANH(1)", "#NUM!");
+    assert_all_types("=COSH(1000)", "#NUM!");
+}
@@ -0,0 +1,56 @@
+mod common;
+
+use common::assert_all_types;
+
+#[test]
+fn it_evaluates_sumproduct() {
+    assert_all_types("=SUMPRODUCT({1,2,3},{4,5,6})", "32");
+    assert_all_types("=SUMPRODUCT({1,2;3,4},{5,6;7,8})", "70");
+    assert_all_types("=SUMPRODUCT({1,2,3})", "6");
+    assert_all_types("=SUMPRODUCT({1,\"a\",TRUE},{1,1,1})", "1");
+    assert_all_types("=SUMPRODUCT({1,2,3}*{4,5,6})", "32");
+    assert_all_types("=SUMPRODUCT({1,2,3},{1,2})", "#VALUE!");
+    assert_all_types("=SUMPRODUCT({1,2},{1;2})", "#VALUE!");
+    assert_all_types("=SUMPRODUCT({1,2},{1,1/0})", "#DIV/0!");
+    assert_all_types("=SUMPRODUCT(3,4)", "12");
+}
+
+#[test]
+fn it_evaluates_mmult() {
+    assert_all_types("=MMULT({1,2;3,4},{5,6;7,8})", "{{19,22},{43,50}}");
+    assert_all_types("=MMULT({1,2,3},{4;5;6})", "{32}");
+    assert_all_types("=MMULT({4;5;6},{1,2})", "{{4,8},{5,10},{6,12}}");
+    assert_all_types("=MMULT({1,2},{1,2})", "#VALUE!");
+    assert_all_types("=MMULT({1,\"a\"},{1;2})", "#VALUE!");
+    assert_all_types("=MMULT({1,2},{1;1/0})", "#DIV/0!");
+}
+
+#[test]
+fn it_evaluates_mdeterm() {
+    assert_all_types("=MDETERM({1,2;3,4})", "-2");
+    assert_all_types("=MDETERM({2,0,0;0,3,0;0,0,4})", "24");
+    assert_all_types("=MDETERM({0,1;1,0})", "-1");
+    assert_all_types("=MDETERM({1,2;2,4})", "0");
+    assert_all_types("=MDETERM({5})", "5");
+    assert_all_types("=MDETERM({1,2,3;4,5,6})", "#VALUE!");
+    assert_all_types("=MDETERM({1,2;TRUE,4})", "#VALUE!");
+}
+
+#[test]
+fn it_evaluates_minverse() {
+    assert_all_types("=MINVERSE({4,7;2,6})", "{{0.6,-0.7},{-0.2,0.4}}");
+    assert_all_types("=MINVERSE({0,1;1,0})", "{{0,1},{1,0}}");
+    assert_all_types("=MINVERSE({2})", "{0.5}");
+    assert_all_types("=MINVERSE({1,2;2,4})", "#NUM!");
+    assert_all_types("=MINVERSE({1,2})", "#VALUE!");
+    assert_all_types("=MMULT(MINVERSE({2,1;1,1}),{2,1;1,1})", "{{1,0},{0,1}}");
+}
+
+#[test]
+fn it_evaluates_transpose() {
+    assert_all_types("=TRANSPOSE({1,2,3})", "{{1},{2},{3}}");
+    assert_all_types("=TRANSPOSE({1;2;3})", "{1,2,3}");
+    assert_all_types("=TRANSPOSE({1,2;3,4;5,6})", "{{1,3,5},{2,4,6}}");
+    assert_all_types("=TRANSPOSE({\"a\",TRUE})", "{{a},{TRUE}}");
+    assert_all_types("=TRANSPOSE(TRANSPOSE({1,2;3,4}))", "{{1,2},{3,4}}");
+}
@@ -0,0 +1,87 @@
+use xlformula_engine::{
+    calculate, optimize::optimize_formula, parse_formula, types, NoCustomFunction,
+};
+
+fn data_function(s: String) -> types::Value<f64> {
+    match s.as_str() {
+        "A" => types::Value::Number(2.0),
+        "B" => types::Value::Text("=A*3".to_string()),
+        "T" => types::Value::Text("text".to_string()),
+        "Z" => types::Value::Blank,
+        _ => types::Value::Error(types::Error::Value),
+    }
+}
+
+fn optimized_text(s: &str) -> String {
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::formula_to_string(&optimize_formula(formula))
+}
+
+#[test]
+fn it_folds_constant_sub_expressions() {
+    assert_eq!(optimized_text("=1+2*3"), "7");
+    assert_eq!(optimized_text("=A+(1+2)*3"), "A+9");
+    assert_eq!(optimized_text(r#"=A&("x"&1)"#), r#"A&"x1""#);
+    assert_eq!(optimized_text("=SUM(A, 2*3, ABS(-4))"), "SUM(A,6,4)");
+    assert_eq!(optimized_text("={1+1,A}"), "{2,A}");
+    assert_eq!(optimized_text("=1/0+A"), "#DIV/0!+A");
+}
+
+#[test]
+fn it_keeps_sub_expressions_with_references() {
+    assert_eq!(optimized_text("=A+1+2"), "A+1+2");
+    assert_eq!(optimized_text("=SUM(A,B)"), "SUM(A,B)");
+}
+
+#[test]
+fn it_keeps_volatile_functions() {
+    assert_eq!(optimized_text("=RAND()*(1+1)"), "RAND()*2");
+    assert_eq!(optimized_text("=RANDBETWEEN(1,2+3)"), "RANDBETWEEN(1,5)");
+    assert_eq!(optimized_text("=SUM(RANDARRAY(2),1)"), "SUM(RANDARRAY(2),1)");
+}
+
+#[test]
+fn it_simplifies_conditionals_with_constant_conditions() {
+    assert_eq!(optimized_text("=IF(TRUE, A, B)"), "A");
+    assert_eq!(optimized_text("=IF(1>2, A, B*2)+1"), "B*2+1");
+    assert_eq!(optimized_text("=IF(0, A, B)"), "B");
+    assert_eq!(optimized_text("=IF(5, A, B)"), "A");
+    assert_eq!(optimized_text("=IF(, A, B)"), "B");
+    assert_eq!(optimized_text("=IF(1/0, A, B)"), "#DIV/0!");
+    assert_eq!(optimized_text(r#"=IF("x", A, B)"#), "#VALUE!");
+    assert_eq!(optimized_text("=IF(A>1, 1+1, 3)"), "IF(A>1,2,3)");
+}
+
+#[test]
+fn it_gives_the_same_results_as_the_original_formula() {
+    for s in [
+        "=1+2*3",
+        "=A+(1+2)*3",
+        "=IF(TRUE, A, B)",
+        "=IF(1>2, A, B*2)+1",
+        "=IF(A>1, 1+1, 3)",
+        "=IF(1/0, A, B)",
+        r#"=IF("x", A, B)"#,
+        "=IF(, A, B)",
+        "=IF(FALSE, A)",
+        "=SUM({1,2,3}, A, 4)",
+        "={1,2,3}+{4,5,6}",
+        "=-{1,2,A}",
+        "=AVERAGE(1,,A)",
+        "=PRODUCT(Z, 2)",
+        r#"=T&" "&LEFT("apple",3)"#,
+        "=AND(TRUE,A>1,OR(FALSE,0))",
+        "=DAYS(Z,Z)+YEAR(Z)",
+        r#"=FIND("p","apple")*SEARCH("L",T&"l")"#,
+        "Hello",
+        "=(1",
+    ] {
+        let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+        let optimized = optimize_formula(formula.clone());
+        assert_eq!(
+            calculate::calculate_formula(optimized, Some(&data_function)),
+            calculate::calculate_formula(formula, Some(&data_function)),
+            "{s}"
+        );
+    }
+}
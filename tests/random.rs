@@ -0,0 +1,208 @@
+use xlformula_engine::{
+    calculate::{self, DefaultRandom, RandomSource, ReferenceCache},
+    parse_formula, types,
+    workbook::Workbook,
+    NoCustomFunction, NoReference,
+};
+
+/// A random source that repeats a sequence of numbers.
+struct Sequence {
+    numbers: Vec<f64>,
+    next: usize,
+}
+
+impl Sequence {
+    fn new(numbers: &[f64]) -> Self {
+        Sequence {
+            numbers: numbers.to_vec(),
+            next: 0,
+        }
+    }
+}
+
+impl RandomSource for Sequence {
+    fn next_f64(&mut self) -> f64 {
+        let number = self.numbers[self.next % self.numbers.len()];
+        self.next += 1;
+        number
+    }
+}
+
+fn eval_with_random(s: &str, random: &mut impl RandomSource) -> String {
+    let data = |reference: String| -> types::Value<f64> {
+        match reference.as_str() {
+            "A1" => types::Value::Number(10.0),
+            "B1" => types::Value::Text("=RAND()".to_string()),
+            _ => types::Value::Error(types::Error::Reference),
+        }
+    };
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::result_to_string(calculate::calculate_formula_with_random(
+        formula,
+        Some(&data),
+        random,
+    ))
+}
+
+fn eval_with_numbers(s: &str, numbers: &[f64]) -> String {
+    eval_with_random(s, &mut Sequence::new(numbers))
+}
+
+#[test]
+fn it_draws_from_the_random_source() {
+    assert_eq!(eval_with_numbers("=RAND()", &[0.5]), "0.5");
+    assert_eq!(eval_with_numbers("=RAND()+RAND()", &[0.25, 0.5]), "0.75");
+    assert_eq!(eval_with_numbers("=B1*2", &[0.25]), "0.5");
+    assert_eq!(eval_with_numbers("=SUM(RAND(),1)", &[0.25]), "1.25");
+    assert_eq!(
+        eval_with_numbers("=RAND()", &[0.9999999999999999]),
+        "0.999999999999999"
+    );
+}
+
+#[test]
+fn it_evaluates_randbetween() {
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(1,10)", &[0.0]), "1");
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(1,10)", &[0.5]), "6");
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(1,A1)", &[0.99]), "10");
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(-5,-1)", &[0.5]), "-3");
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(1.5,3.5)", &[0.99]), "3");
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(3,3)", &[0.5]), "3");
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(5,1)", &[0.5]), "#NUM!");
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(1.2,1.8)", &[0.5]), "#NUM!");
+    assert_eq!(
+        eval_with_numbers("=RANDBETWEEN(\"a\",1)", &[0.5]),
+        "#VALUE!"
+    );
+    assert_eq!(eval_with_numbers("=RANDBETWEEN(1)", &[0.5]), "#ARG!");
+}
+
+#[test]
+fn it_evaluates_randarray() {
+    assert_eq!(eval_with_numbers("=RANDARRAY()", &[0.5]), "{0.5}");
+    assert_eq!(
+        eval_with_numbers("=RANDARRAY(2,3)", &[0.1, 0.2, 0.3, 0.4, 0.5, 0.6]),
+        "{{0.1,0.2,0.3},{0.4,0.5,0.6}}"
+    );
+    assert_eq!(
+        eval_with_numbers("=RANDARRAY(1,2)", &[0.1, 0.2]),
+        "{0.1,0.2}"
+    );
+    assert_eq!(
+        eval_with_numbers("=RANDARRAY(2)", &[0.1, 0.2]),
+        "{{0.1},{0.2}}"
+    );
+    assert_eq!(eval_with_numbers("=RANDARRAY(1,,10,20)", &[0.5]), "{15}");
+    assert_eq!(
+        eval_with_numbers("=RANDARRAY(2,2,1,6,TRUE)", &[0.0, 0.5, 0.99, 0.2]),
+        "{{1,4},{6,2}}"
+    );
+    assert_eq!(eval_with_numbers("=RANDARRAY(0)", &[0.5]), "#VALUE!");
+    assert_eq!(eval_with_numbers("=RANDARRAY(1,1,5,1)", &[0.5]), "#VALUE!");
+    assert_eq!(
+        eval_with_numbers("=RANDARRAY(1,1,1.5,3,TRUE)", &[0.5]),
+        "#VALUE!"
+    );
+    assert_eq!(eval_with_numbers("=RANDARRAY(\"a\")", &[0.5]), "#VALUE!");
+    assert_eq!(eval_with_numbers("=RANDARRAY(1048577)", &[0.5]), "#VALUE!");
+    assert_eq!(eval_with_numbers("=RANDARRAY(1,16385)", &[0.5]), "#VALUE!");
+    assert_eq!(
+        eval_with_numbers("=RANDARRAY(10^8,10^8)", &[0.5]),
+        "#VALUE!"
+    );
+    assert_eq!(
+        eval_with_numbers("=RANDARRAY(1048576,16384)", &[0.5]),
+        "#LIMIT!"
+    );
+}
+
+#[test]
+fn it_repeats_random_numbers_with_the_same_seed() {
+    let s = "=RANDARRAY(3,3)&RAND()&RANDBETWEEN(1,1000000)";
+    assert_eq!(
+        eval_with_random(s, &mut DefaultRandom::with_seed(42)),
+        eval_with_random(s, &mut DefaultRandom::with_seed(42))
+    );
+    assert_ne!(
+        eval_with_random("=RAND()", &mut DefaultRandom::with_seed(1)),
+        eval_with_random("=RAND()", &mut DefaultRandom::with_seed(2))
+    );
+    let mut random = DefaultRandom::with_seed(7);
+    assert!((0..1000)
+        .map(|_| random.next_f64())
+        .all(|number| (0.0..1.0).contains(&number)));
+}
+
+#[test]
+fn it_draws_from_the_random_source_in_every_evaluation() {
+    let parse = |s: &str| parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    let (value, steps) = calculate::calculate_formula_with_trace_and_random(
+        parse("=RAND()*2"),
+        None::<NoReference<f64>>,
+        &mut Sequence::new(&[0.25]),
+    );
+    assert_eq!(value, types::Value::Number(0.5));
+    assert_eq!(steps[0].value, types::Value::Number(0.25));
+    assert_eq!(
+        calculate::calculate_formula_with_limits_and_random(
+            parse("=RANDBETWEEN(1,10)"),
+            None::<NoReference<f64>>,
+            &types::Limits::default(),
+            &mut Sequence::new(&[0.5]),
+        ),
+        types::Value::Number(6.0)
+    );
+    assert_eq!(
+        calculate::calculate_formula_with_cache_and_random(
+            parse("=RAND()+RAND()"),
+            None::<NoReference<f64>>,
+            &mut ReferenceCache::new(),
+            &mut Sequence::new(&[0.25, 0.5]),
+        ),
+        types::Value::Number(0.75)
+    );
+}
+
+#[test]
+fn it_recalculates_workbooks_with_the_random_source() {
+    let mut workbook = Workbook::<f64>::new();
+    for (name, formula) in [("A", "=RAND()"), ("B", "=A+RAND()"), ("C", "=RAND()*10")] {
+        workbook.insert(
+            name,
+            parse_formula::parse_string_to_formula(formula, None::<NoCustomFunction<f64>>),
+        );
+    }
+    let results = workbook.recalculate_with_random(
+        None::<NoReference<f64>>,
+        &mut Sequence::new(&[0.25, 0.5, 0.125]),
+    );
+    assert_eq!(results["A"], types::Value::Number(0.25));
+    assert_eq!(results["C"], types::Value::Number(5.0));
+    assert_eq!(results["B"], types::Value::Number(0.375));
+    assert_eq!(
+        workbook
+            .recalculate_with_random(None::<NoReference<f64>>, &mut DefaultRandom::with_seed(3)),
+        workbook
+            .recalculate_with_random(None::<NoReference<f64>>, &mut DefaultRandom::with_seed(3))
+    );
+}
+
+#[test]
+fn it_uses_a_default_random_source() {
+    let formula = parse_formula::parse_string_to_formula("=RAND()", None::<NoCustomFunction<f64>>);
+    match calculate::calculate_formula(formula, None::<NoReference<f64>>) {
+        types::Value::Number(number) => assert!((0.0..1.0).contains(&number)),
+        value => panic!("{value:?}"),
+    }
+    let formula = parse_formula::parse_string_to_formula(
+        "=RANDBETWEEN(3,3)+RANDARRAY(1,1,2,2)",
+        None::<NoCustomFunction<f32>>,
+    );
+    assert_eq!(
+        calculate::result_to_string(calculate::calculate_formula(
+            formula,
+            None::<NoReference<f32>>
+        )),
+        "{5}"
+    );
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{assert_all_types, eval};
+
+#[test]
+fn it_evaluates_round() {
+    assert_all_types("=ROUND(2.5,0)", "3");
+    assert_all_types("=ROUND(-2.5,0)", "-3");
+    assert_all_types("=ROUND(2.675,2)", "2.68");
+    assert_all_types("=ROUND(1.005,2)", "1.01");
+    assert_all_types("=ROUND(1234.567,-2)", "1200");
+    assert_all_types("=ROUND(-50,-2)", "-100");
+    assert_all_types("=ROUND(49,-2)", "0");
+    assert_all_types("=ROUND(9.99,1)", "10");
+    assert_all_types("=ROUND(3.14159,2.9)", "3.14");
+    assert_all_types("=ROUND(0.1+0.2,20)", "0.3");
+    assert_all_types("=ROUND(\"1.25\",1)", "1.3");
+    assert_all_types("=ROUND({1.25,-1.25},1)", "{1.3,-1.3}");
+    assert_all_types("=ROUND(1.5,{0,1})", "{2,1.5}");
+    assert_all_types("=ROUND(\"a\",1)", "#VALUE!");
+    assert_all_types("=ROUND(1/0,1)", "#DIV/0!");
+    assert_eq!(eval::<f64>("=ROUND(0.1+0.2,2)=0.3"), "TRUE");
+}
+
+#[test]
+fn it_evaluates_roundup_rounddown_and_trunc() {
+    assert_all_types("=ROUNDUP(3.2,0)", "4");
+    assert_all_types("=ROUNDUP(-3.2,0)", "-4");
+    assert_all_types("=ROUNDUP(3.14159,3)", "3.142");
+    assert_all_types("=ROUNDUP(31415.92654,-2)", "31500");
+    assert_all_types("=ROUNDUP(0.1+0.2,1)", "0.3");
+    assert_all_types("=ROUNDUP(0.001,0)", "1");
+    assert_all_types("=ROUNDDOWN(3.9,0)", "3");
+    assert_all_types("=ROUNDDOWN(-3.9,0)", "-3");
+    assert_all_types("=ROUNDDOWN(31415.92654,-2)", "31400");
+    assert_all_types("=TRUNC(8.9)", "8");
+    assert_all_types("=TRUNC(-8.9)", "-8");
+    assert_all_types("=TRUNC(3.14159,2)", "3.14");
+}
+
+#[test]
+fn it_evaluates_int_mod_and_quotient() {
+    assert_all_types("=INT(8.9)", "8");
+    assert_all_types("=INT(-8.9)", "-9");
+    assert_all_types("=INT(-8)", "-8");
+    assert_all_types("=MOD(3,2)", "1");
+    assert_all_types("=MOD(-3,2)", "1");
+    assert_all_types("=MOD(3,-2)", "-1");
+    assert_all_types("=MOD(-3,-2)", "-1");
+    assert_all_types("=MOD(5.5,2)", "1.5");
+    assert_all_types("=MOD(3,0)", "#DIV/0!");
+    assert_all_types("=MOD({5,6,7},3)", "{2,0,1}");
+    assert_all_types("=QUOTIENT(5,2)", "2");
+    assert_all_types("=QUOTIENT(-10,3)", "-3");
+    assert_all_types("=QUOTIENT(4.5,3.1)", "1");
+    assert_all_types("=QUOTIENT(1,0)", "#DIV/0!");
+    assert_eq!(eval::<f64>("=MOD(3.1,1)"), "0.1");
+}
+
+#[test]
+fn it_evaluates_mround_ceiling_and_floor() {
+    assert_all_types("=MROUND(10,3)", "9");
+    assert_all_types("=MROUND(-10,-3)", "-9");
+    assert_all_types("=MROUND(1.3,0.2)", "1.4");
+    assert_all_types("=MROUND(5,-2)", "#NUM!");
+    assert_all_types("=MROUND(5,0)", "0");
+    assert_all_types("=CEILING(2.5,1)", "3");
+    assert_all_types("=CEILING(-2.5,-2)", "-4");
+    assert_all_types("=CEILING(-2.5,2)", "-2");
+    assert_all_types("=CEILING(1.5,0.1)", "1.5");
+    assert_all_types("=CEILING(0.234,0.01)", "0.24");
+    assert_all_types("=CEILING(2.5,-2)", "#NUM!");
+    assert_all_types("=CEILING(2.5,0)", "0");
+    assert_all_types("=FLOOR(3.7,2)", "2");
+    assert_all_types("=FLOOR(-2.5,-2)", "-2");
+    assert_all_types("=FLOOR(-2.5,2)", "-4");
+    assert_all_types("=FLOOR(1.58,0.1)", "1.5");
+    assert_all_types("=FLOOR(0.3,0.1)", "0.3");
+    assert_all_types("=FLOOR(2.5,-2)", "#NUM!");
+    assert_all_types("=FLOOR(2.5,0)", "#DIV/0!");
+}
+
+#[test]
+fn it_evaluates_ceiling_math_and_floor_math() {
+    assert_all_types("=CEILING.MATH(24.3,5)", "25");
+    assert_all_types("=CEILING.MATH(6.7)", "7");
+    assert_all_types("=CEILING.MATH(-8.1,2)", "-8");
+    assert_all_types("=CEILING.MATH(-5.5,2,-1)", "-6");
+    assert_all_types("=CEILING.MATH(5.5,-2)", "6");
+    assert_all_types("=FLOOR.MATH(24.3,5)", "20");
+    assert_all_types("=FLOOR.MATH(6.7)", "6");
+    assert_all_types("=FLOOR.MATH(-8.1,2)", "-10");
+    assert_all_types("=FLOOR.MATH(-5.5,2,-1)", "-4");
+    assert_all_types("=floor.math({1.5,-1.5})", "{1,-2}");
+    assert_all_types("=CEILING.MATH(1,1,\"a\")", "#VALUE!");
+}
+
+#[test]
+fn it_shows_dotted_function_names() {
+    let formula = xlformula_engine::parse_formula::parse_string_to_formula::<f64>(
+        "=CEILING.MATH(A1,2)+FLOOR.MATH(1)",
+        None::<xlformula_engine::NoCustomFunction<f64>>,
+    );
+    assert_eq!(
+        xlformula_engine::calculate::formula_to_string(&formula),
+        "CEILING.MATH(A1,2)+FLOOR.MATH(1)"
+    );
+}
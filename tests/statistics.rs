@@ -0,0 +1,129 @@
+mod common;
+
+use common::{assert_all_types, eval};
+use xlformula_engine::{calculate, parse_formula, types, NoCustomFunction};
+
+/// Evaluates a formula with the range `A1:A6` holding 4, "a", TRUE, a blank cell, 1 and 4,
+/// and the cells `B1` holding "2", `C1` holding nothing, `D1` holding "a" and `E1` holding
+/// TRUE.
+fn eval_with_range(s: &str) -> String {
+    let data = |reference: String| -> types::Value<f64> {
+        match reference.as_str() {
+            "A1:A6" => types::Value::Iterator(vec![
+                types::Value::Number(4.0),
+                types::Value::Text("a".to_string()),
+                types::Value::Boolean(types::Boolean::True),
+                types::Value::Blank,
+                types::Value::Number(1.0),
+                types::Value::Number(4.0),
+            ]),
+            "B1" => types::Value::Text("2".to_string()),
+            "C1" => types::Value::Blank,
+            "D1" => types::Value::Text("a".to_string()),
+            "E1" => types::Value::Boolean(types::Boolean::True),
+            _ => types::Value::Error(types::Error::Reference),
+        }
+    };
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::result_to_string(calculate::calculate_formula(formula, Some(&data)))
+}
+
+#[test]
+fn it_evaluates_min_and_max() {
+    assert_all_types("=MIN(3,1,2)", "1");
+    assert_all_types("=MAX(3,1,2)", "3");
+    assert_all_types("=MAX({1,5;7,2},3)", "7");
+    assert_all_types("=MIN({\"a\",TRUE,5})", "5");
+    assert_all_types("=MAX(\"9\",TRUE)", "9");
+    assert_all_types("=MIN(TRUE,5)", "1");
+    assert_all_types("=MAX(\"a\",1)", "#VALUE!");
+    assert_all_types("=MAX({1,1/0})", "#DIV/0!");
+    assert_all_types("=MAX(1,1/0)", "#DIV/0!");
+    assert_all_types("=MAX({\"a\"})", "0");
+    assert_all_types("=MIN()", "0");
+    assert_eq!(eval_with_range("=MAX(A1:A6)"), "4");
+    assert_eq!(eval_with_range("=MIN(A1:A6,C1)"), "1");
+    assert_eq!(eval_with_range("=MIN(A1:A6,B1)"), "1");
+    assert_eq!(eval_with_range("=MIN(D1)"), "0");
+    assert_eq!(eval_with_range("=MAX(D1,E1,-1)"), "-1");
+}
+
+#[test]
+fn it_counts_values() {
+    assert_all_types("=COUNT(1,\"2\",TRUE,\"a\",1/0)", "3");
+    assert_all_types("=COUNT({1,\"2\",TRUE,1/0})", "1");
+    assert_all_types("=COUNTA(1,\"a\",1/0)", "3");
+    assert_all_types("=COUNTA({1,\"\",FALSE})", "3");
+    assert_all_types("=COUNTBLANK({1,\"\",0})", "1");
+    assert_eq!(eval_with_range("=COUNT(A1:A6)"), "3");
+    assert_eq!(eval_with_range("=COUNTA(A1:A6)"), "5");
+    assert_eq!(eval_with_range("=COUNTBLANK(A1:A6)"), "1");
+    assert_eq!(eval_with_range("=COUNTA(C1)"), "0");
+    assert_eq!(eval_with_range("=COUNTBLANK(C1)"), "1");
+    assert_eq!(eval_with_range("=COUNT(D1,E1)"), "0");
+    assert_eq!(eval_with_range("=COUNTA(D1,E1)"), "2");
+}
+
+#[test]
+fn it_evaluates_median_and_mode() {
+    assert_all_types("=MEDIAN(3,1,2)", "2");
+    assert_all_types("=MEDIAN({4,1,3,2})", "2.5");
+    assert_all_types("=MEDIAN({\"a\"})", "#NUM!");
+    assert_all_types("=MODE(1,2,2,3,3)", "2");
+    assert_all_types("=MODE({3,1,1,3})", "3");
+    assert_all_types("=MODE.SNGL(5,5)", "5");
+    assert_all_types("=MODE(1,2,3)", "#N/A");
+    assert_eq!(eval_with_range("=MEDIAN(A1:A6)"), "4");
+    assert_eq!(eval_with_range("=MODE(A1:A6)"), "4");
+}
+
+#[test]
+fn it_evaluates_variance_and_standard_deviation() {
+    assert_eq!(eval::<f64>("=VAR(2,4,4,4,5,5,7,9)"), "4.57142857142857");
+    assert_all_types("=VAR.P(2,4,4,4,5,5,7,9)", "4");
+    assert_all_types("=STDEV.P({2,4,4,4,5,5,7,9})", "2");
+    assert_eq!(eval::<f64>("=STDEV(2,4,4,4,5,5,7,9)"), "2.1380899352994");
+    assert_eq!(eval::<f64>("=STDEV.S(1,2,3,4)"), "1.29099444873581");
+    assert_all_types("=VAR.S(1,3)", "2");
+    assert_all_types("=VAR(1)", "#DIV/0!");
+    assert_all_types("=STDEV.P(5)", "0");
+    assert_all_types("=STDEV.P({\"a\"})", "#DIV/0!");
+}
+
+#[test]
+fn it_evaluates_large_and_small() {
+    assert_all_types("=LARGE({3,5,1,4},1)", "5");
+    assert_all_types("=LARGE({3,5,1,4},2)", "4");
+    assert_all_types("=SMALL({3,5,1,4},1)", "1");
+    assert_all_types("=SMALL({3,5,1,4},1.5)", "3");
+    assert_all_types("=SMALL({3,5,1,4},{1,2})", "{1,3}");
+    assert_all_types("=LARGE({3,5,1,4},0)", "#NUM!");
+    assert_all_types("=LARGE({3,5,1,4},5)", "#NUM!");
+    assert_all_types("=LARGE({3,5})", "#ARG!");
+    assert_eq!(eval_with_range("=SMALL(A1:A6,2)"), "4");
+}
+
+#[test]
+fn it_evaluates_rank() {
+    assert_all_types("=RANK(4,{3,5,1,4})", "2");
+    assert_all_types("=RANK(4,{3,5,1,4},1)", "3");
+    assert_all_types("=RANK.EQ(4,{4,5,4,1})", "2");
+    assert_all_types("=RANK.AVG(4,{4,5,4,1})", "2.5");
+    assert_all_types("=RANK(2,{3,5,1,4})", "#N/A");
+    assert_all_types("=RANK({5,1},{3,5,1,4})", "{1,4}");
+    assert_eq!(eval_with_range("=RANK(1,A1:A6)"), "3");
+}
+
+#[test]
+fn it_evaluates_percentiles_and_quartiles() {
+    assert_all_types("=PERCENTILE({1,2,3,4},0.3)", "1.9");
+    assert_all_types("=PERCENTILE.INC({1,2,3,4},1)", "4");
+    assert_all_types("=PERCENTILE.INC({1,2,3,4},1.5)", "#NUM!");
+    assert_all_types("=PERCENTILE.EXC({1,2,3,4},0.4)", "2");
+    assert_all_types("=PERCENTILE.EXC({1,2,3,4},0.1)", "#NUM!");
+    assert_all_types("=QUARTILE({1,2,4,7,8,9,10,12},1)", "3.5");
+    assert_all_types("=QUARTILE.INC({1,2,4,7,8,9,10,12},{0,4})", "{1,12}");
+    assert_all_types("=QUARTILE.EXC({1,2,4,7,8,9,10,12},1)", "2.5");
+    assert_all_types("=QUARTILE.EXC({1,2,4,7,8,9,10,12},0)", "#NUM!");
+    assert_all_types("=QUARTILE({\"a\"},1)", "#NUM!");
+}
@@ -798,12 +798,12 @@ fn it_evaluate_iterator_in_logic_functions() {
 
 #[test]
 fn it_evaluate_iterator_with_diffrent_number_of_entries() {
-    test_all_types!(evaluate_formula_string, "={0,0}+{1,2,3}", "{1,2,#ARG!}");
-    test_all_types!(evaluate_formula_string, "={0,0}*{1,2,3}", "{0,0,#ARG!}");
+    test_all_types!(evaluate_formula_string, "={0,0}+{1,2,3}", "{1,2,#N/A}");
+    test_all_types!(evaluate_formula_string, "={0,0}*{1,2,3}", "{0,0,#N/A}");
     test_all_types!(
         evaluate_formula_string,
         "={1,2,3}/{0,0}",
-        "{#DIV/0!,#DIV/0!,#ARG!}"
+        "{#DIV/0!,#DIV/0!,#N/A}"
     );
     test_all_types!(evaluate_formula_string, "={0,0}+{1,\"Hi\"}", "{1,#CAST!}");
 }
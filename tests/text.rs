@@ -0,0 +1,132 @@
+mod common;
+
+use chrono::{FixedOffset, TimeZone};
+use common::{assert_all_types, eval};
+use xlformula_engine::{
+    format::{format_value, NumberFormat},
+    types,
+};
+
+fn format(value: f64, code: &str) -> String {
+    format_value(&types::Value::Number(value), code).unwrap()
+}
+
+#[test]
+fn it_formats_digit_placeholders() {
+    assert_eq!(format(1234.567, "0"), "1235");
+    assert_eq!(format(1234.567, "0.00"), "1234.57");
+    assert_eq!(format(1234.567, "#,##0.00"), "1,234.57");
+    assert_eq!(format(1234567.891, "#,##0"), "1,234,568");
+    assert_eq!(format(0.5, "#.##"), ".5");
+    assert_eq!(format(5.0, "#.##"), "5.");
+    assert_eq!(format(0.0, "#,##0"), "0");
+    assert_eq!(format(5.0, "000"), "005");
+    assert_eq!(format(12.5, "0.0#"), "12.5");
+    assert_eq!(format(12.345, "0.0#"), "12.35");
+    assert_eq!(format(1.5, "??0.0?"), "  1.5 ");
+    assert_eq!(format(12.5, ".00"), "12.50");
+    assert_eq!(format(2.5, "0"), "3");
+    assert_eq!(format(-2.5, "0"), "-3");
+    assert_eq!(format(-0.001, "0.00"), "0.00");
+    assert_eq!(format(5551234567.0, "(000) 000-0000"), "(555) 123-4567");
+}
+
+#[test]
+fn it_formats_percent_scaling_and_scientific_notation() {
+    assert_eq!(format(0.256, "0%"), "26%");
+    assert_eq!(format(0.256, "0.0%"), "25.6%");
+    assert_eq!(format(1234567.0, "#,##0,"), "1,235");
+    assert_eq!(format(1234567.0, "0.0,,\" M\""), "1.2 M");
+    assert_eq!(format(12345.0, "0.00E+00"), "1.23E+04");
+    assert_eq!(format(0.000123, "0.0E+0"), "1.2E-4");
+    assert_eq!(format(12345.0, "0.00e-00"), "1.23e04");
+    assert_eq!(format(99999.0, "0.0E+00"), "1.0E+05");
+    assert_eq!(format(12345.0, "##0.0E+0"), "12.3E+3");
+    assert_eq!(format(0.0, "0.00E+00"), "0.00E+00");
+}
+
+#[test]
+fn it_formats_sections_conditions_and_literals() {
+    assert_eq!(format(-5.0, "0.0;(0.0)"), "(5.0)");
+    assert_eq!(format(-5.0, "[Red]-0.0;0.0"), "5.0");
+    assert_eq!(format(-5.0, "0.0;[Red]-0.0"), "-5.0");
+    assert_eq!(format(-5.0, "0.0;[Red]0.0"), "5.0");
+    assert_eq!(format(0.0, "0;-0;\"zero\""), "zero");
+    assert_eq!(format(-5.0, "0"), "-5");
+    assert_eq!(format(150.0, "[>100]\"big\";[<=100]0"), "big");
+    assert_eq!(format(50.0, "[>100]\"big\";0"), "50");
+    assert_eq!(format(5.0, "\"$\"#,##0.00"), "$5.00");
+    assert_eq!(format(5.0, "\"x\""), "x");
+    assert_eq!(format(5.0, "[$€-407] 0"), "€ 5");
+    assert_eq!(format(5.0, "0\\x_)"), "5x ");
+    assert_eq!(format(-5.0, "$#,##0"), "-$5");
+    assert_eq!(format(0.1 + 0.2, "General"), "0.3");
+    assert_eq!(format(-1.5, "General;(General)"), "(1.5)");
+    assert_eq!(
+        format_value(&types::Value::<f64>::Text("abc".to_string()), "0;0;0;\"<\"@\">\""),
+        Ok("<abc>".to_string())
+    );
+    assert_eq!(
+        format_value(&types::Value::<f64>::Text("abc".to_string()), "0.00"),
+        Ok("abc".to_string())
+    );
+}
+
+#[test]
+fn it_formats_dates_and_times() {
+    let date = FixedOffset::east_opt(0)
+        .unwrap()
+        .with_ymd_and_hms(2024, 3, 5, 14, 7, 9)
+        .unwrap();
+    let date = types::Value::<f64>::Date(date);
+    let format_date = |code: &str| format_value(&date, code).unwrap();
+    assert_eq!(format_date("yyyy-mm-dd"), "2024-03-05");
+    assert_eq!(format_date("d/m/yy"), "5/3/24");
+    assert_eq!(format_date("dddd, mmmm d, yyyy"), "Tuesday, March 5, 2024");
+    assert_eq!(format_date("ddd mmm mmmmm"), "Tue Mar M");
+    assert_eq!(format_date("hh:mm:ss"), "14:07:09");
+    assert_eq!(format_date("h:mm AM/PM"), "2:07 PM");
+    assert_eq!(format_date("h:mm a/p"), "2:07 p");
+    assert_eq!(format_date("0.00"), "45356.59");
+    assert_eq!(format(45356.5, "yyyy-mm-dd hh:mm"), "2024-03-05 12:00");
+    assert_eq!(format(1.5, "[h]:mm"), "36:00");
+    assert_eq!(format(0.0000115741, "[ss].000"), "01.000");
+    assert_eq!(format(0.5001, "hh:mm:ss.00"), "12:00:08.64");
+    assert_eq!(
+        format_value(&types::Value::Number(-1.0), "yyyy"),
+        Err(types::Error::Value)
+    );
+}
+
+#[test]
+fn it_rejects_invalid_format_codes() {
+    assert_eq!(NumberFormat::parse("0;0;0;@;0"), Err(types::Error::Value));
+    assert_eq!(NumberFormat::parse("\"abc"), Err(types::Error::Value));
+    assert_eq!(NumberFormat::parse("[Purple]0"), Err(types::Error::Value));
+    assert!(NumberFormat::parse("[Color10]0").is_ok());
+}
+
+#[test]
+fn it_evaluates_text() {
+    assert_all_types("=TEXT(1234.5,\"#,##0.00\")", "1,234.50");
+    assert_all_types("=TEXT(0.25,\"0%\")", "25%");
+    assert_all_types("=TEXT(-1.5,\"0.0;[Red]-0.0\")", "-1.5");
+    assert_all_types("=TEXT(\"12\",\"000\")", "012");
+    assert_all_types("=TEXT(\"abc\",\"000\")", "abc");
+    assert_all_types("=TEXT(TRUE,\"0\")", "TRUE");
+    assert_all_types("=TEXT(BLANK(),\"0.0\")", "0.0");
+    assert_all_types("=TEXT(5,0)", "5");
+    assert_all_types("=TEXT(1/0,\"0\")", "#DIV/0!");
+    assert_all_types("=TEXT(5,\"\"\"abc\")", "#VALUE!");
+    assert_all_types("=TEXT(1.5,\"# ?/?\")", "#VALUE!");
+    assert_all_types("=TEXT(0.25,\"0/100\")", "#VALUE!");
+    assert_all_types("=TEXT(5,\"0 \"\"/\"\" 0\")", "0 / 5");
+    assert_all_types("=TEXT({1,2.5},\"0.0\")", "{1.0,2.5}");
+    assert_all_types("=TEXT(3,{\"0\",\"0.0\"})", "{3,3.0}");
+    assert_all_types("=\"Total: \"&TEXT(1234,\"#,##0\")", "Total: 1,234");
+    assert_eq!(eval::<f64>("=TEXT(0.1+0.2,\"0.00000000000000000\")"), "0.30000000000000000");
+    assert_all_types("=TEXT(2958465,\"yyyy-mm-dd\")", "9999-12-31");
+    assert_all_types("=TEXT(2958466,\"yyyy\")", "#VALUE!");
+    assert_all_types("=TEXT(10^14,\"yyyy\")", "#VALUE!");
+    assert_all_types("=TEXT(-1,\"yyyy\")", "#VALUE!");
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use xlformula_engine::{
+    calculate::{self, TraceStep},
+    parse_formula, types, NoCustomFunction, NoReference,
+};
+
+fn data_function(s: String) -> types::Value<f64> {
+    match s.as_str() {
+        "A" => types::Value::Number(2.0),
+        "B" => types::Value::Text("=A*3".to_string()),
+        "Name" => types::Value::Text("Test".to_string()),
+        _ => types::Value::Error(types::Error::Value),
+    }
+}
+
+fn trace(s: &str) -> (types::Value<f64>, Vec<TraceStep<f64>>) {
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::calculate_formula_with_trace(formula, Some(&data_function))
+}
+
+fn step(depth: usize, text: &str, value: types::Value<f64>) -> TraceStep<f64> {
+    TraceStep {
+        depth,
+        text: text.to_string(),
+        value,
+    }
+}
+
+#[test]
+fn it_converts_formulas_back_to_text() {
+    for (s, text) in [
+        ("=1+2*3", "1+2*3"),
+        ("=(1+2)*3", "(1+2)*3"),
+        ("=1-(2-3)", "1-(2-3)"),
+        ("=2^3^2", "2^3^2"),
+        ("=(2^3)^2", "(2^3)^2"),
+        (r#"="a"&"b""c""#, r#""a"&"b""c""#),
+        ("=SUM(1, 2, , A)", "SUM(1,2,,A)"),
+        ("=IF(A>0,TRUE,-A)", "IF(A>0,TRUE,-A)"),
+        ("=-(1+2)", "-(1+2)"),
+        ("={1,2,A}", "{1,2,A}"),
+        ("=1/0", "1/0"),
+    ] {
+        let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+        assert_eq!(calculate::formula_to_string(&formula), text, "{s}");
+    }
+}
+
+#[test]
+fn it_keeps_the_result_when_reparsing_the_text() {
+    for s in [
+        "=1+2*3-4/5^2",
+        "=(1&2)=12",
+        "=1=(1>0)",
+        "=AVERAGE(1,,3)",
+        r#"=LEFT("apple",3)&RIGHT("apple")"#,
+        "=PRODUCT({1,2,3},2)-SUM({1,2},-{3,4})",
+        "=OR(1>1,NOT(0),XOR(TRUE,FALSE))",
+        r#"=FIND("b","abc")+SEARCH("?c","abc",2)"#,
+    ] {
+        let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+        let text = format!("={}", calculate::formula_to_string(&formula));
+        assert_eq!(
+            common::evaluate_formula_string::<f64>(&text),
+            common::evaluate_formula_string::<f64>(s),
+            "{s} -> {text}"
+        );
+    }
+}
+
+#[test]
+fn it_records_sub_expressions_in_evaluation_order() {
+    let (value, steps) = trace("=IF(A>1,A*2,0)+1");
+    assert_eq!(value, types::Value::Number(5.0));
+    assert_eq!(
+        steps,
+        vec![
+            step(3, "A", types::Value::Number(2.0)),
+            step(3, "1", types::Value::Number(1.0)),
+            step(2, "A>1", types::Value::Boolean(types::Boolean::True)),
+            step(3, "A", types::Value::Number(2.0)),
+            step(3, "2", types::Value::Number(2.0)),
+            step(2, "A*2", types::Value::Number(4.0)),
+            step(2, "0", types::Value::Number(0.0)),
+            step(1, "IF(A>1,A*2,0)", types::Value::Number(4.0)),
+            step(1, "1", types::Value::Number(1.0)),
+            step(0, "IF(A>1,A*2,0)+1", types::Value::Number(5.0)),
+        ]
+    );
+}
+
+#[test]
+fn it_records_where_an_error_comes_from() {
+    let (value, steps) = trace(r#"=SUM(1,"x"&Name)+1"#);
+    assert_eq!(value, types::Value::Error(types::Error::Cast));
+    let first_error = steps
+        .iter()
+        .find(|step| matches!(step.value, types::Value::Error(_)))
+        .unwrap();
+    assert_eq!(first_error.text, r#"SUM(1,"x"&Name)"#);
+    assert_eq!(
+        steps[steps.len() - 4],
+        step(2, r#""x"&Name"#, types::Value::Text("xTest".to_string()))
+    );
+}
+
+#[test]
+fn it_steps_into_referenced_formulas() {
+    let (value, steps) = trace("=B+1");
+    assert_eq!(value, types::Value::Number(7.0));
+    let texts: Vec<(usize, &str)> = steps
+        .iter()
+        .map(|step| (step.depth, step.text.as_str()))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            (3, "A"),
+            (3, "3"),
+            (2, "A*3"),
+            (1, "B"),
+            (1, "1"),
+            (0, "B+1")
+        ]
+    );
+}
+
+#[test]
+fn it_returns_the_same_value_as_calculate_formula() {
+    for s in [
+        "=1+2",
+        "=SUM({1,2,3},4)",
+        "=-{1,2}",
+        r#"=1+"a""#,
+        "=Missing",
+    ] {
+        let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+        let (value, _) =
+            calculate::calculate_formula_with_trace(formula.clone(), None::<NoReference<f64>>);
+        assert_eq!(
+            value,
+            calculate::calculate_formula(formula, None::<NoReference<f64>>)
+        );
+    }
+}
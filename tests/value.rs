@@ -0,0 +1,71 @@
+mod common;
+
+use chrono::DateTime;
+use common::assert_all_types;
+use xlformula_engine::{calculate, parse_formula, types, NoCustomFunction};
+
+/// Evaluates a formula with the date 2024-02-29 in `D1`.
+fn eval_with_date(s: &str) -> String {
+    let data = |reference: String| -> types::Value<f64> {
+        match reference.as_str() {
+            "D1" => {
+                types::Value::Date(DateTime::parse_from_rfc3339("2024-02-29T00:00:00Z").unwrap())
+            }
+            _ => types::Value::Error(types::Error::Reference),
+        }
+    };
+    let formula = parse_formula::parse_string_to_formula(s, None::<NoCustomFunction<f64>>);
+    calculate::result_to_string(calculate::calculate_formula(formula, Some(&data)))
+}
+
+#[test]
+fn it_converts_text_in_arithmetic_like_excel() {
+    assert_all_types("=\"1,000\"+1", "1001");
+    assert_all_types("=\" 42 \"*2", "84");
+    assert_all_types("=\"50%\"*2", "1");
+    assert_all_types("=\"$3.10\"*2", "6.2");
+    assert_all_types("=\"(5)\"+0", "-5");
+    assert_all_types("=\"-$1,234.5\"+0", "-1234.5");
+    assert_all_types("=\"1e3\"+1", "1001");
+    assert_all_types("=\".5\"*2", "1");
+    assert_all_types("=SQRT(\"1,600\")", "40");
+    assert_all_types("=\"abc\"+1", "#CAST!");
+    assert_all_types("=\"1,00\"+1", "#CAST!");
+    assert_all_types("=\"1.2.3\"+1", "#CAST!");
+    assert_all_types("=\"\"+1", "#CAST!");
+    assert_all_types("=\"(-5)\"+1", "#CAST!");
+}
+
+#[test]
+fn it_evaluates_value() {
+    assert_all_types("=VALUE(\"1,000\")", "1000");
+    assert_all_types("=VALUE(\" 42 \")", "42");
+    assert_all_types("=VALUE(\"50%\")", "0.5");
+    assert_all_types("=VALUE(\"$3.10\")", "3.1");
+    assert_all_types("=VALUE(\"(5)\")", "-5");
+    assert_all_types("=VALUE(\"+2.5E-1\")", "0.25");
+    assert_all_types("=VALUE(12)", "12");
+    assert_all_types("=VALUE(\"abc\")", "#VALUE!");
+    assert_all_types("=VALUE(TRUE)", "#VALUE!");
+    assert_all_types("=VALUE(1/0)", "#DIV/0!");
+    assert_all_types("=VALUE({\"1\",\"2%\",\"x\"})", "{1,0.02,#VALUE!}");
+    assert_all_types("=VALUE(\"1\")+VALUE(\"2\")", "3");
+    assert_eq!(eval_with_date("=VALUE(D1)"), "45351");
+}
+
+#[test]
+fn it_evaluates_numbervalue() {
+    assert_all_types("=NUMBERVALUE(\"2.500,27\",\",\",\".\")", "2500.27");
+    assert_all_types("=NUMBERVALUE(\"1,000.5\")", "1000.5");
+    assert_all_types("=NUMBERVALUE(\"3.5%\")", "0.035");
+    assert_all_types("=NUMBERVALUE(\"50%%\")", "0.005");
+    assert_all_types("=NUMBERVALUE(\" 1 000 \",\",\",\" \")", "1000");
+    assert_all_types("=NUMBERVALUE(\"\")", "0");
+    assert_all_types("=NUMBERVALUE(\"-1e2\")", "-100");
+    assert_all_types("=NUMBERVALUE(\"1,5\",\",\")", "1.5");
+    assert_all_types("=NUMBERVALUE(\"1.5.0\")", "#VALUE!");
+    assert_all_types("=NUMBERVALUE(\"1.000,5\",\".\",\",\")", "#VALUE!");
+    assert_all_types("=NUMBERVALUE(\"1\",\",\",\",\")", "#VALUE!");
+    assert_all_types("=NUMBERVALUE(\"1\",\"\")", "#VALUE!");
+    assert_all_types("=NUMBERVALUE({\"1,5\",\"2\"},\",\")", "{1.5,2}");
+}
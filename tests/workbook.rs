@@ -0,0 +1,246 @@
+use std::{fmt::Debug, str::FromStr};
+use xlformula_engine::{
+    parse_formula,
+    types::{self, XlNum},
+    workbook::{IterationSettings, Workbook},
+    NoCustomFunction, NoReference,
+};
+
+fn workbook<N>(cells: &[(&str, &str)]) -> Workbook<N>
+where
+    N: XlNum,
+    <N as FromStr>::Err: Debug,
+{
+    let mut workbook = Workbook::new();
+    for (name, formula) in cells {
+        workbook.insert(
+            name,
+            parse_formula::parse_string_to_formula(formula, None::<NoCustomFunction<N>>),
+        );
+    }
+    workbook
+}
+
+#[test]
+fn it_groups_cells_into_dependency_levels() {
+    let workbook = workbook::<f64>(&[
+        ("A", "=1"),
+        ("B", "=A+1"),
+        ("C", "=A*2"),
+        ("D", "=B+C"),
+        ("E", "=5"),
+    ]);
+    let levels = workbook.dependency_levels();
+    assert_eq!(
+        levels.levels,
+        vec![
+            vec!["A".to_string(), "E".to_string()],
+            vec!["B".to_string(), "C".to_string()],
+            vec!["D".to_string()],
+        ]
+    );
+    assert!(levels.circular.is_empty());
+}
+
+#[test]
+fn it_groups_long_chains_of_cells() {
+    let names: Vec<String> = (1..=5000).map(|row| format!("A{row}")).collect();
+    let mut cells = vec![(names[0].as_str(), "=1".to_string())];
+    cells.extend(
+        names
+            .windows(2)
+            .map(|pair| (pair[1].as_str(), format!("={}+1", pair[0]))),
+    );
+    let cells: Vec<(&str, &str)> = cells
+        .iter()
+        .map(|(name, formula)| (*name, formula.as_str()))
+        .collect();
+    let workbook = workbook::<f64>(&cells);
+    assert_eq!(workbook.dependency_levels().levels.len(), 5000);
+    let results = workbook.recalculate(None::<NoReference<f64>>);
+    assert_eq!(results["A5000"], types::Value::Number(5000.0));
+}
+
+#[test]
+fn it_recalculates_cells_in_dependency_order() {
+    let workbook = workbook::<f64>(&[
+        ("D", "=B+C"),
+        ("C", "=A*2"),
+        ("B", "=A+1"),
+        ("A", "=1"),
+        ("Text", r#"="x"&D"#),
+    ]);
+    let results = workbook.recalculate(None::<NoReference<f64>>);
+    assert_eq!(results["A"], types::Value::Number(1.0));
+    assert_eq!(results["B"], types::Value::Number(2.0));
+    assert_eq!(results["C"], types::Value::Number(2.0));
+    assert_eq!(results["D"], types::Value::Number(4.0));
+    assert_eq!(results["Text"], types::Value::Text("x4".to_string()));
+}
+
+#[test]
+fn it_resolves_external_references_with_the_closure() {
+    let workbook = workbook::<f32>(&[("A", "=Input*2"), ("B", "=SUM(A, Input)")]);
+    let data_function = |s: String| match s.as_str() {
+        "Input" => types::Value::Number(3.0),
+        _ => types::Value::Error(types::Error::Value),
+    };
+    let results = workbook.recalculate(Some(&data_function));
+    assert_eq!(results["A"], types::Value::Number(6.0));
+    assert_eq!(results["B"], types::Value::Number(9.0));
+
+    let results = workbook.recalculate(None::<NoReference<f32>>);
+    assert_eq!(results["A"], types::Value::Error(types::Error::Reference));
+}
+
+#[test]
+fn it_passes_evaluated_values_through_unchanged() {
+    let workbook = workbook::<f64>(&[
+        ("A", "=1/0"),
+        ("B", "=A"),
+        ("C", r#"="=2*3""#),
+        ("D", "=C"),
+        ("E", "=D&Input"),
+        ("F", "=E&B"),
+    ]);
+    let data_function = |s: String| match s.as_str() {
+        "Input" => types::Value::Text("=C".to_string()),
+        _ => types::Value::Error(types::Error::Value),
+    };
+    let results = workbook.recalculate(Some(&data_function));
+    assert_eq!(results["B"], types::Value::Error(types::Error::Div0));
+    assert_eq!(results["D"], types::Value::Text("=2*3".to_string()));
+    assert_eq!(results["E"], types::Value::Text("=2*3=2*3".to_string()));
+    assert_eq!(results["F"], types::Value::Error(types::Error::Div0));
+}
+
+#[test]
+fn it_evaluates_ranges_of_cells() {
+    let workbook = workbook::<f64>(&[
+        ("C1", "=SUM(A1:A3)"),
+        ("A1", "=1"),
+        ("A2", "=A1+1"),
+        ("A3", "=A2+1"),
+        ("C2", "=COUNTBLANK(A1:B3)"),
+        ("C3", "=SUM(X1:X2)"),
+    ]);
+    assert_eq!(
+        workbook.precedents("C1"),
+        ["A1", "A2", "A3"].map(String::from).into()
+    );
+    assert_eq!(
+        workbook.dependency_levels().levels,
+        vec![
+            vec!["A1".to_string(), "C3".to_string()],
+            vec!["A2".to_string()],
+            vec!["A3".to_string()],
+            vec!["C1".to_string(), "C2".to_string()],
+        ]
+    );
+    let data_function = |s: String| match s.as_str() {
+        "X1:X2" => types::Value::Iterator(vec![types::Value::Number(5.0); 2]),
+        _ => types::Value::Error(types::Error::Value),
+    };
+    let results = workbook.recalculate(Some(&data_function));
+    assert_eq!(results["C1"], types::Value::Number(6.0));
+    assert_eq!(results["C2"], types::Value::Number(3.0));
+    assert_eq!(results["C3"], types::Value::Number(10.0));
+    assert_eq!(
+        workbook.recalculate_with_cache(Some(&data_function)),
+        results
+    );
+}
+
+#[test]
+fn it_limits_the_size_of_ranges() {
+    let workbook = workbook::<f64>(&[("A1", "=1"), ("Total", "=SUM(A1:XFD1048576)")]);
+    let results = workbook.recalculate(None::<NoReference<f64>>);
+    assert_eq!(results["Total"], types::Value::Error(types::Error::Limit));
+}
+
+#[test]
+fn it_reports_circular_references() {
+    let workbook = workbook::<f64>(&[("A", "=B+1"), ("B", "=A+1"), ("C", "=A"), ("D", "=2")]);
+    let levels = workbook.dependency_levels();
+    assert_eq!(levels.levels, vec![vec!["D".to_string()]]);
+    assert_eq!(levels.circular, vec!["A", "B", "C"]);
+    let results = workbook.recalculate(None::<NoReference<f64>>);
+    assert_eq!(results["A"], types::Value::Error(types::Error::Reference));
+    assert_eq!(results["C"], types::Value::Error(types::Error::Reference));
+    assert_eq!(results["D"], types::Value::Number(2.0));
+}
+
+#[test]
+fn it_iterates_intentional_circular_references() {
+    // Interest depends on the average balance, which depends on the interest.
+    let workbook = workbook::<f64>(&[
+        ("Start", "=1000"),
+        ("Rate", "=0.1"),
+        ("Interest", "=Rate*(Start+Balance)/2"),
+        ("Balance", "=Start+Interest"),
+    ]);
+    let results = workbook.recalculate_iterative(
+        None::<NoReference<f64>>,
+        &IterationSettings {
+            max_iterations: 100,
+            max_change: 0.000001,
+        },
+    );
+    assert!(results.converged);
+    assert!(results.iterations > 1 && results.iterations < 100);
+    match results.values["Interest"] {
+        types::Value::Number(interest) => assert!((interest - 100.0 / 0.95).abs() < 0.00001),
+        ref value => panic!("unexpected value {value:?}"),
+    }
+    assert_eq!(results.values["Start"], types::Value::Number(1000.0));
+}
+
+#[test]
+fn it_stops_iterating_after_max_iterations() {
+    let workbook = workbook::<f32>(&[("A", "=B+1"), ("B", "=A+1"), ("C", "=A")]);
+    let results =
+        workbook.recalculate_iterative(None::<NoReference<f32>>, &IterationSettings::default());
+    assert!(!results.converged);
+    assert_eq!(results.iterations, 100);
+    assert_eq!(results.values["A"], types::Value::Number(199.0));
+    assert_eq!(results.values["B"], types::Value::Number(200.0));
+    assert_eq!(results.values["C"], types::Value::Number(199.0));
+}
+
+#[test]
+fn it_converges_immediately_without_circular_references() {
+    let workbook = workbook::<f64>(&[("A", "=1"), ("B", "=A+1")]);
+    let results =
+        workbook.recalculate_iterative(None::<NoReference<f64>>, &IterationSettings::default());
+    assert!(results.converged);
+    assert_eq!(results.iterations, 0);
+    assert_eq!(
+        results.values,
+        workbook.recalculate(None::<NoReference<f64>>)
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn it_recalculates_in_parallel_with_identical_results() {
+    let mut cells = vec![("C0".to_string(), "=1".to_string())];
+    for i in 1..500 {
+        cells.push((format!("C{i}"), format!("=C{}+{}*2", i / 2, i)));
+    }
+    for i in 0..200 {
+        cells.push((format!("T{i}"), format!(r#"="t"&C{}"#, i * 2)));
+    }
+    let cells: Vec<(&str, &str)> = cells
+        .iter()
+        .map(|(name, formula)| (name.as_str(), formula.as_str()))
+        .collect();
+    let workbook = workbook::<f64>(&cells);
+    let data_function = |_: String| types::Value::Error(types::Error::Value);
+    let sequential = workbook.recalculate(Some(&data_function));
+    for _ in 0..5 {
+        assert_eq!(
+            workbook.recalculate_parallel(Some(&data_function)),
+            sequential
+        );
+    }
+}